@@ -1,5 +1,8 @@
+use std::borrow::Cow;
+
 use crate::{parser, span::Span, token::Token};
 
+#[derive(Debug, Clone)]
 pub struct AstNodeChildren<'stream>(Vec<AstNode<'stream>>);
 
 impl<'stream> FromIterator<AstNode<'stream>> for AstNodeChildren<'stream> {
@@ -10,14 +13,14 @@ impl<'stream> FromIterator<AstNode<'stream>> for AstNodeChildren<'stream> {
 
 impl AstNodeChildren<'_> {
     pub fn span(&self) -> Span {
-        Span::from_iter(self.iter().map(|ast| ast.span).copied())
+        Span::from_iter(self.0.iter().map(|ast| ast.span))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct AstNode<'stream> {
     pub kind: String,
-    pub value: Option<&'stream str>,
+    pub value: Option<Cow<'stream, str>>,
     pub children: AstNodeChildren<'stream>,
     pub span: Span,
 }
@@ -35,7 +38,7 @@ impl<'stream> AstNode<'stream> {
     }
 }
 
-impl<'stream> parser::Ast for AstNode<'stream> {
+impl<'stream> parser::traits::Ast for AstNode<'stream> {
     fn symbol_id(&self) -> &str {
         &self.kind
     }
@@ -48,9 +51,9 @@ impl<'stream> parser::Ast for AstNode<'stream> {
 impl<'stream> From<Token<'stream>> for AstNode<'stream> {
     fn from(token: Token<'stream>) -> Self {
         Self {
-            kind: token.kind,
+            kind: token.kind.into_owned(),
             value: Some(token.value),
-            children: vec![],
+            children: AstNodeChildren(vec![]),
             span: token.span,
         }
     }
@@ -0,0 +1,881 @@
+use std::collections::{BTreeSet, HashMap};
+
+use itertools::Itertools as _;
+
+use super::{
+    charset::CharSet,
+    graph::{Graph, Node},
+    Alphabet, Classes, Set,
+};
+
+pub type DfaStateId = usize;
+
+/// A state of a [Dfa].
+#[derive(Debug, Clone)]
+pub struct DfaState<S> {
+    /// Outgoing transitions, their sets are disjoint.
+    pub edges: Vec<(S, DfaStateId)>,
+    /// The tags accepted in this state, by priority.
+    pub accepts: Vec<usize>,
+}
+
+/// A deterministic finite automaton, whose states may accept a tag.
+///
+/// The initial state is #0.
+#[derive(Debug, Clone)]
+pub struct Dfa<S> {
+    states: Vec<DfaState<S>>,
+}
+
+impl<S> Dfa<S>
+where
+    S: Set,
+{
+    /// The state reached from the state by consuming the item.
+    pub fn next(&self, state: DfaStateId, item: &S::Item) -> Option<DfaStateId> {
+        self.states[state]
+            .edges
+            .iter()
+            .find(|(set, _)| set.contains(item))
+            .map(|(_, to)| *to)
+    }
+
+    /// The tag accepted by the state, if any.
+    pub fn accept(&self, state: DfaStateId) -> Option<usize> {
+        self.states[state].accepts.first().copied()
+    }
+
+    /// The tags accepted by the state, by priority.
+    pub fn accepts(&self, state: DfaStateId) -> &[usize] {
+        &self.states[state].accepts
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DfaState<S>> {
+        self.states.iter()
+    }
+}
+
+/// A flattened NFA, with numbered nodes.
+struct Nfa<'a, S> {
+    edges: Vec<(usize, &'a S, usize)>,
+    epsilons: Vec<Vec<usize>>,
+    accept: HashMap<usize, usize>,
+}
+
+impl<'a, S> Nfa<'a, S> {
+    fn new<A>(graphs: &'a [(usize, Graph<S, A>)]) -> Self {
+        let mut nfa = Self {
+            edges: vec![],
+            epsilons: vec![vec![]],
+            accept: HashMap::default(),
+        };
+
+        for (tag, graph) in graphs {
+            let base = nfa.epsilons.len();
+            let end = base + graph.len() + 1;
+            let index = |node: Node| match node {
+                Node::Start => base,
+                Node::Internal(id) => base + 1 + id,
+                Node::End => end,
+            };
+
+            nfa.epsilons.resize(end + 1, vec![]);
+            nfa.epsilons[0].push(base);
+            nfa.accept.insert(end, *tag);
+
+            for id in graph.iter_epsilons() {
+                nfa.epsilons[index(id.from)].push(index(id.to));
+            }
+
+            nfa.edges.extend(
+                graph
+                    .iter_edges()
+                    .map(|edge| (index(edge.from), &edge.set, index(edge.to))),
+            );
+        }
+
+        nfa
+    }
+
+    fn closure(&self, mut nodes: BTreeSet<usize>) -> BTreeSet<usize> {
+        let mut stack = nodes.iter().copied().collect::<Vec<_>>();
+
+        while let Some(node) = stack.pop() {
+            for &to in self.epsilons[node].iter() {
+                if nodes.insert(to) {
+                    stack.push(to);
+                }
+            }
+        }
+
+        nodes
+    }
+}
+
+impl<S> Dfa<S>
+where
+    S: Set + Clone,
+{
+    /// Build the DFA recognizing the union of the tagged graphs,
+    /// by subset construction.
+    ///
+    /// When several graphs match the same sequence, the lowest tag wins.
+    pub fn from_graphs<A>(graphs: &[(usize, Graph<S, A>)]) -> Self {
+        let nfa = Nfa::new(graphs);
+
+        let mut subsets = vec![nfa.closure(BTreeSet::from([0]))];
+        let mut ids = HashMap::from([(subsets[0].clone(), 0)]);
+        let mut states = Vec::<DfaState<S>>::default();
+
+        while let Some(subset) = subsets.get(states.len()).cloned() {
+            let edges = nfa
+                .edges
+                .iter()
+                .filter(|(from, _, _)| subset.contains(from))
+                .collect::<Vec<_>>();
+
+            let sets = edges.iter().map(|(_, set, _)| *set).collect::<Vec<_>>();
+            let mut transitions = Vec::<(S, DfaStateId)>::default();
+
+            for (part, owners) in S::partition(&sets) {
+                let target = nfa.closure(owners.iter().map(|&i| edges[i].2).collect());
+
+                let to = *ids.entry(target.clone()).or_insert_with(|| {
+                    subsets.push(target);
+                    subsets.len() - 1
+                });
+
+                match transitions.iter_mut().find(|(_, id)| *id == to) {
+                    Some((set, _)) => *set = S::union(set.clone(), part),
+                    None => transitions.push((part, to)),
+                }
+            }
+
+            states.push(DfaState {
+                edges: transitions,
+                accepts: subset
+                    .iter()
+                    .filter_map(|node| nfa.accept.get(node))
+                    .copied()
+                    .sorted()
+                    .dedup()
+                    .collect(),
+            });
+        }
+
+        Self { states }
+    }
+}
+
+impl<S> Dfa<S>
+where
+    S: Set + Clone,
+{
+    /// Merge the equivalent states, with Hopcroft's algorithm.
+    ///
+    /// Two states are equivalent if they accept the same tags, and their
+    /// transitions lead to equivalent states. States which cannot reach
+    /// any accepting state are removed.
+    pub fn minimize(&self) -> Self {
+        // The missing transitions lead to an implicit dead state.
+        let dead = self.states.len();
+
+        let sets = self
+            .states
+            .iter()
+            .flat_map(|state| state.edges.iter().map(|(set, _)| set))
+            .collect::<Vec<_>>();
+
+        let owners = self
+            .states
+            .iter()
+            .enumerate()
+            .flat_map(|(id, state)| state.edges.iter().map(move |(_, to)| (id, *to)))
+            .collect::<Vec<_>>();
+
+        let classes = S::partition(&sets);
+        let mut delta = vec![vec![dead; classes.len()]; dead + 1];
+
+        for (class, (_, edges)) in classes.iter().enumerate() {
+            for &edge in edges {
+                let (from, to) = owners[edge];
+                delta[from][class] = to;
+            }
+        }
+
+        let accepts = |state: DfaStateId| {
+            self.states
+                .get(state)
+                .map(|s| s.accepts.as_slice())
+                .unwrap_or_default()
+        };
+
+        // The sources of the transitions, by class and target state.
+        let mut inverse = vec![vec![Vec::<DfaStateId>::default(); dead + 1]; classes.len()];
+
+        for (from, row) in delta.iter().enumerate() {
+            for (class, &to) in row.iter().enumerate() {
+                inverse[class][to].push(from);
+            }
+        }
+
+        // Initial partition, by accepted tags.
+        let mut block_of = vec![0; dead + 1];
+        let mut position = vec![0; dead + 1];
+        let mut blocks = Vec::<Vec<DfaStateId>>::default();
+        let mut tags = HashMap::<&[usize], usize>::default();
+
+        for state in 0..=dead {
+            let block = *tags.entry(accepts(state)).or_insert_with(|| {
+                blocks.push(vec![]);
+                blocks.len() - 1
+            });
+
+            block_of[state] = block;
+            position[state] = blocks[block].len();
+            blocks[block].push(state);
+        }
+
+        // Any block but one is enough to split the others.
+        let largest = (0..blocks.len())
+            .max_by_key(|&block| blocks[block].len())
+            .unwrap_or_default();
+        let mut work = (0..blocks.len())
+            .filter(|&block| block != largest)
+            .collect::<Vec<_>>();
+        let mut in_work = (0..blocks.len())
+            .map(|block| block != largest)
+            .collect::<Vec<_>>();
+
+        // The states of each block having a transition into the splitter.
+        let mut marked = vec![Vec::<DfaStateId>::default(); blocks.len()];
+        let mut touched = Vec::<usize>::default();
+
+        while let Some(splitter) = work.pop() {
+            in_work[splitter] = false;
+
+            // The splitter is taken as it is when popped, as it may be split
+            // while its classes are processed.
+            let members = blocks[splitter].clone();
+
+            for sources in inverse.iter() {
+                for &source in members.iter().flat_map(|&target| sources[target].iter()) {
+                    let block = block_of[source];
+
+                    if marked[block].is_empty() {
+                        touched.push(block);
+                    }
+
+                    marked[block].push(source);
+                }
+
+                for block in touched.drain(..) {
+                    let inside = std::mem::take(&mut marked[block]);
+
+                    if inside.len() == blocks[block].len() {
+                        continue;
+                    }
+
+                    // The marked states move to a new block.
+                    for &state in &inside {
+                        let index = position[state];
+                        blocks[block].swap_remove(index);
+
+                        if let Some(&moved) = blocks[block].get(index) {
+                            position[moved] = index;
+                        }
+                    }
+
+                    let split = blocks.len();
+
+                    for (index, &state) in inside.iter().enumerate() {
+                        block_of[state] = split;
+                        position[state] = index;
+                    }
+
+                    blocks.push(inside);
+                    marked.push(vec![]);
+
+                    // Both halves are queued if the block was, otherwise the
+                    // smaller half is enough.
+                    let queued = if in_work[block] || blocks[split].len() <= blocks[block].len() {
+                        split
+                    } else {
+                        block
+                    };
+
+                    in_work.push(false);
+                    in_work[queued] = true;
+                    work.push(queued);
+                }
+            }
+        }
+
+        // Number the live blocks from the initial state.
+        let live = self.live_states();
+        let mut ids = HashMap::from([(block_of[0], 0)]);
+        let mut order = vec![block_of[0]];
+        let mut states = Vec::<DfaState<S>>::default();
+
+        while let Some(&block) = order.get(states.len()) {
+            let repr = blocks[block][0];
+            let mut edges = Vec::<(S, DfaStateId)>::default();
+
+            for (class, (set, _)) in classes.iter().enumerate() {
+                let target = delta[repr][class];
+
+                if target == dead || !live[target] {
+                    continue;
+                }
+
+                let to = *ids.entry(block_of[target]).or_insert_with(|| {
+                    order.push(block_of[target]);
+                    order.len() - 1
+                });
+
+                match edges.iter_mut().find(|(_, id)| *id == to) {
+                    Some((edge, _)) => *edge = S::union(edge.clone(), set.clone()),
+                    None => edges.push((set.clone(), to)),
+                }
+            }
+
+            states.push(DfaState {
+                edges,
+                accepts: accepts(repr).to_vec(),
+            });
+        }
+
+        Self { states }
+    }
+
+    /// The states from which an accepting state can be reached.
+    fn live_states(&self) -> Vec<bool> {
+        let mut live = self
+            .states
+            .iter()
+            .map(|state| !state.accepts.is_empty())
+            .collect::<Vec<_>>();
+
+        loop {
+            let mut changed = false;
+
+            for (id, state) in self.states.iter().enumerate() {
+                if !live[id] && state.edges.iter().any(|(_, to)| live[*to]) {
+                    live[id] = true;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                return live;
+            }
+        }
+    }
+}
+
+/// A [Dfa] whose transitions are compressed into a table indexed by
+/// state and class of units.
+#[derive(Debug, Clone)]
+pub struct CompactDfa<S = CharSet>
+where
+    S: Alphabet,
+{
+    classes: S::Classes,
+    /// The transitions, row by state, column by class.
+    transitions: Vec<Option<DfaStateId>>,
+    accepts: Vec<Vec<usize>>,
+}
+
+impl<S> CompactDfa<S>
+where
+    S: Alphabet,
+{
+    pub fn next(&self, state: DfaStateId, unit: S::Item) -> Option<DfaStateId> {
+        self.transitions[state * self.classes.len() + self.classes.classify(unit)]
+    }
+
+    pub fn accept(&self, state: DfaStateId) -> Option<usize> {
+        self.accepts[state].first().copied()
+    }
+
+    /// The tags accepted by the state, by priority.
+    pub fn accepts(&self, state: DfaStateId) -> &[usize] {
+        &self.accepts[state]
+    }
+
+    /// Iterate over the tags accepted by the automaton.
+    pub fn iter_tags(&self) -> impl Iterator<Item = usize> + '_ {
+        self.accepts.iter().flatten().copied()
+    }
+
+    /// The number of unit classes.
+    pub fn class_count(&self) -> usize {
+        self.classes.len()
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.accepts.len()
+    }
+
+    pub fn classes(&self) -> &S::Classes {
+        &self.classes
+    }
+
+    /// The transitions, row by state, column by class.
+    pub fn transitions(&self) -> &[Option<DfaStateId>] {
+        &self.transitions
+    }
+
+    /// Assemble an automaton from its parts, such as the ones generated
+    /// at compile time by the `lexer!` macro.
+    ///
+    /// # Panics
+    /// Panics if the transitions do not match the classes and the states.
+    pub fn from_raw_parts(
+        classes: S::Classes,
+        transitions: Vec<Option<DfaStateId>>,
+        accepts: Vec<Vec<usize>>,
+    ) -> Self {
+        assert_eq!(
+            transitions.len(),
+            classes.len() * accepts.len(),
+            "the transitions do not match the classes and the states"
+        );
+
+        Self {
+            classes,
+            transitions,
+            accepts,
+        }
+    }
+}
+
+impl<S> From<&Dfa<S>> for CompactDfa<S>
+where
+    S: Alphabet,
+{
+    fn from(dfa: &Dfa<S>) -> Self {
+        let classes = S::classes(
+            dfa.iter()
+                .flat_map(|state| state.edges.iter().map(|(set, _)| set)),
+        );
+
+        let mut transitions = vec![None; dfa.len() * classes.len()];
+
+        for (id, state) in dfa.iter().enumerate() {
+            for (class, unit) in classes.representatives().iter().enumerate() {
+                transitions[id * classes.len() + class] = state
+                    .edges
+                    .iter()
+                    .find(|(set, _)| set.contains(unit))
+                    .map(|(_, to)| *to);
+            }
+        }
+
+        Self {
+            classes,
+            transitions,
+            accepts: dfa.iter().map(|state| state.accepts.clone()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeSet, HashSet},
+        fmt::Display,
+    };
+
+    use itertools::Itertools as _;
+
+    use crate::{
+        dfa::{charset::CharSet, graph::Graph, ByteSet, CharClasses, Classes, IntoGraph},
+        lexer::regex::parse,
+        NoCustomError,
+    };
+
+    use super::{CompactDfa, Dfa};
+
+    fn run(dfa: &Dfa<CharSet>, input: &str) -> Option<usize> {
+        input
+            .chars()
+            .try_fold(0, |state, ch| dfa.next(state, &ch))
+            .and_then(|state| dfa.accept(state))
+    }
+
+    #[test]
+    fn test_001_subset_construction() {
+        let digits: Graph<CharSet, ()> = CharSet::from('0'..='9').into_graph().plus();
+        let keyword: Graph<CharSet, ()> = "if".into_graph();
+        let ident: Graph<CharSet, ()> = CharSet::from('a'..='z').into_graph().plus();
+
+        let dfa = Dfa::from_graphs(&[(0, keyword), (1, ident), (2, digits)]);
+
+        assert_eq!(run(&dfa, "if"), Some(0));
+        assert_eq!(run(&dfa, "iff"), Some(1));
+        assert_eq!(run(&dfa, "i"), Some(1));
+        assert_eq!(run(&dfa, "42"), Some(2));
+        assert_eq!(run(&dfa, "4a"), None);
+        assert_eq!(run(&dfa, ""), None);
+    }
+
+    #[test]
+    fn test_002_fragments() {
+        // a(b|c)?d*
+        let graph: Graph<CharSet, ()> = 'a'.into_graph()
+            + 'b'.into_graph().merge('c'.into_graph()).optional()
+            + 'd'.into_graph().star();
+
+        let dfa = Dfa::from_graphs(&[(0, graph)]);
+
+        for ok in ["a", "ab", "ac", "abd", "addd"] {
+            assert_eq!(run(&dfa, ok), Some(0), "{}", ok);
+        }
+
+        for ko in ["", "abc", "b", "ada"] {
+            assert_eq!(run(&dfa, ko), None, "{}", ko);
+        }
+    }
+
+    /// A random pattern, matched by backtracking over its own structure
+    /// to check the automata built from its text.
+    enum Pattern {
+        Leaf(&'static str),
+        Concat(Box<Pattern>, Box<Pattern>),
+        Either(Box<Pattern>, Box<Pattern>),
+        Repeat(Box<Pattern>, &'static str),
+    }
+
+    impl Pattern {
+        /// The bounds of the quantifier.
+        fn bounds(quantifier: &str) -> (usize, Option<usize>) {
+            match quantifier {
+                "*" => (0, None),
+                "+" => (1, None),
+                "?" | "{,1}" => (0, Some(1)),
+                "{2}" => (2, Some(2)),
+                "{1,2}" => (1, Some(2)),
+                _ => unreachable!(),
+            }
+        }
+
+        /// The positions where a match starting at start can end.
+        fn ends(&self, input: &[char], start: usize) -> BTreeSet<usize> {
+            match self {
+                Pattern::Leaf(leaf) => {
+                    let matches = input.get(start).is_some_and(|&ch| match *leaf {
+                        "[ab]" => ch == 'a' || ch == 'b',
+                        "[^a]" => ch != 'a',
+                        "." => ch != '\n',
+                        leaf => leaf.starts_with(ch),
+                    });
+
+                    matches.then_some(start + 1).into_iter().collect()
+                }
+                Pattern::Concat(lhs, rhs) => lhs
+                    .ends(input, start)
+                    .into_iter()
+                    .flat_map(|mid| rhs.ends(input, mid))
+                    .collect(),
+                Pattern::Either(lhs, rhs) => {
+                    let mut ends = lhs.ends(input, start);
+                    ends.extend(rhs.ends(input, start));
+                    ends
+                }
+                Pattern::Repeat(inner, quantifier) => {
+                    let (min, max) = Self::bounds(quantifier);
+                    // Beyond min, a repetition must consume a char to reach new ends.
+                    let max = max.unwrap_or(min + input.len() - start);
+                    let mut ends = BTreeSet::new();
+                    let mut frontier = BTreeSet::from([start]);
+
+                    for count in 0..=max {
+                        if count >= min {
+                            ends.extend(frontier.iter().copied());
+                        }
+
+                        frontier = frontier
+                            .into_iter()
+                            .flat_map(|pos| inner.ends(input, pos))
+                            .collect();
+                    }
+
+                    ends
+                }
+            }
+        }
+
+        fn matches(&self, input: &str) -> bool {
+            let input = input.chars().collect::<Vec<_>>();
+            self.ends(&input, 0).contains(&input.len())
+        }
+    }
+
+    impl Display for Pattern {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Pattern::Leaf(leaf) => write!(f, "{}", leaf),
+                Pattern::Concat(lhs, rhs) => write!(f, "{}{}", lhs, rhs),
+                Pattern::Either(lhs, rhs) => write!(f, "({}|{})", lhs, rhs),
+                Pattern::Repeat(inner, quantifier) => write!(f, "({}){}", inner, quantifier),
+            }
+        }
+    }
+
+    /// A xorshift generator, enough to build random patterns.
+    struct Random(u64);
+
+    impl Random {
+        fn below(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+
+        fn pattern(&mut self, depth: usize) -> Pattern {
+            let leaf = ["a", "b", "c", "[ab]", "[^a]", "."];
+
+            match if depth == 0 { 0 } else { self.below(5) } {
+                0 => Pattern::Leaf(leaf[self.below(leaf.len() as u64) as usize]),
+                1 => Pattern::Concat(
+                    Box::new(self.pattern(depth - 1)),
+                    Box::new(self.pattern(depth - 1)),
+                ),
+                2 => Pattern::Either(
+                    Box::new(self.pattern(depth - 1)),
+                    Box::new(self.pattern(depth - 1)),
+                ),
+                3 => {
+                    let quantifier = ["*", "+", "?", "{2}", "{1,2}", "{,1}"];
+                    let quantifier = quantifier[self.below(quantifier.len() as u64) as usize];
+                    Pattern::Repeat(Box::new(self.pattern(depth - 1)), quantifier)
+                }
+                _ => self.pattern(depth - 1),
+            }
+        }
+    }
+
+    /// Whether no two states of the DFA, nor a state and the implicit
+    /// dead state, are equivalent, by table filling.
+    fn is_minimal(dfa: &Dfa<CharSet>, alphabet: &[char]) -> bool {
+        let dead = dfa.len();
+        let next = |state: usize, ch: char| {
+            if state == dead {
+                dead
+            } else {
+                dfa.next(state, &ch).unwrap_or(dead)
+            }
+        };
+        let accept = |state: usize| {
+            if state == dead {
+                None
+            } else {
+                dfa.accept(state)
+            }
+        };
+
+        let pairs = (0..=dead)
+            .cartesian_product(0..=dead)
+            .filter(|(p, q)| p < q)
+            .collect::<Vec<_>>();
+
+        let mut distinct = pairs
+            .iter()
+            .copied()
+            .filter(|&(p, q)| accept(p) != accept(q))
+            .collect::<HashSet<_>>();
+
+        loop {
+            let more = pairs
+                .iter()
+                .copied()
+                .filter(|pair| !distinct.contains(pair))
+                .filter(|&(p, q)| {
+                    alphabet.iter().any(|&ch| {
+                        let (p, q) = (next(p, ch), next(q, ch));
+                        distinct.contains(&(p.min(q), p.max(q)))
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            if more.is_empty() {
+                return pairs.iter().all(|pair| distinct.contains(pair));
+            }
+
+            distinct.extend(more);
+        }
+    }
+
+    /// Every string over the alphabet, up to the given length.
+    fn words(alphabet: &[char], max: usize) -> Vec<String> {
+        (0..max).fold(vec![String::new()], |mut words, _| {
+            let longer = words
+                .iter()
+                .filter(|word| word.chars().count() == words.last().unwrap().chars().count())
+                .flat_map(|word| alphabet.iter().map(move |ch| format!("{}{}", word, ch)))
+                .collect::<Vec<_>>();
+
+            words.extend(longer);
+            words
+        })
+    }
+
+    #[test]
+    fn test_003_compact() {
+        let graph = parse::<NoCustomError>("[a-z_][a-z0-9_]*|[0-9]+")
+            .unwrap()
+            .into_graph();
+        let dfa = Dfa::<CharSet>::from_graphs::<()>(&[(0, graph)]).minimize();
+        let compact = CompactDfa::from(&dfa);
+
+        for input in ["a", "_x1", "42", "4a", "", "é"] {
+            let got = input
+                .chars()
+                .try_fold(0, |state, ch| compact.next(state, ch))
+                .and_then(|state| compact.accept(state));
+
+            assert_eq!(got, run(&dfa, input), "{:?}", input);
+        }
+
+        // [\0-/], [0-9], [:-^], [_], [`], [a-z], [{-MAX]
+        assert_eq!(compact.class_count(), 7);
+    }
+
+    #[test]
+    fn test_004_minimize() {
+        let graph = parse::<NoCustomError>("(a|b)*abb").unwrap().into_graph();
+        let dfa = Dfa::<CharSet>::from_graphs::<()>(&[(0, graph)]);
+        let min = dfa.minimize();
+
+        assert_eq!(min.len(), 4);
+        assert_eq!(run(&min, "babb"), Some(0));
+        assert_eq!(run(&min, "abab"), None);
+    }
+
+    #[test]
+    fn test_005_minimize_keeps_tags() {
+        let graphs = [
+            (0, "ab".into_graph()),
+            (1, CharSet::from('a'..='b').into_graph().plus()),
+        ];
+        let min = Dfa::<CharSet>::from_graphs::<()>(&graphs).minimize();
+
+        assert_eq!(run(&min, "ab"), Some(0));
+        assert_eq!(run(&min, "ba"), Some(1));
+        assert_eq!(run(&min, "a"), Some(1));
+    }
+
+    #[test]
+    fn test_006_randomized_equivalence() {
+        let mut random = Random(0x2545F4914F6CDD1D);
+        let inputs = words(&['a', 'b', 'c'], 5);
+        // A char of each class the leaves of the patterns tell apart.
+        let alphabet = ['a', 'b', 'c', 'd', '\n'];
+
+        for _ in 0..200 {
+            let pattern = random.pattern(4);
+            let text = pattern.to_string();
+            let graph: Graph<CharSet, ()> = parse::<NoCustomError>(&text).unwrap().into_graph();
+            let dfa = Dfa::from_graphs(&[(0, graph)]);
+            let min = dfa.minimize();
+
+            assert!(is_minimal(&min, &alphabet), "{}", text);
+
+            for input in inputs.iter() {
+                let expected = pattern.matches(input);
+
+                assert_eq!(
+                    run(&dfa, input).is_some(),
+                    expected,
+                    "{} on {:?}",
+                    text,
+                    input
+                );
+                assert_eq!(
+                    run(&min, input).is_some(),
+                    expected,
+                    "{} on {:?}",
+                    text,
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_007_bytes() {
+        let digits: Graph<ByteSet, ()> = ByteSet::from(b'0'..=b'9').into_graph().plus();
+        let get: Graph<ByteSet, ()> = b"GET".into_graph();
+
+        let dfa = Dfa::from_graphs(&[(0, get), (1, digits)]).minimize();
+        let compact = CompactDfa::from(&dfa);
+
+        for (input, expected) in [(&b"GET"[..], Some(0)), (b"404", Some(1)), (b"GE", None)] {
+            let got = input
+                .iter()
+                .try_fold(0, |state, &byte| compact.next(state, byte))
+                .and_then(|state| compact.accept(state));
+
+            assert_eq!(got, expected, "{:?}", input);
+        }
+
+        // [G], [E], [T], [0-9], and the other bytes.
+        assert_eq!(compact.class_count(), 5);
+    }
+
+    #[test]
+    fn test_008_raw_parts() {
+        let graph = parse::<NoCustomError>("[a-z]+[0-9]?").unwrap().into_graph();
+        let dfa = Dfa::<CharSet>::from_graphs::<()>(&[(0, graph)]).minimize();
+        let compact = CompactDfa::from(&dfa);
+
+        let rebuilt = CompactDfa::<CharSet>::from_raw_parts(
+            CharClasses::from_starts(compact.classes().representatives()),
+            compact.transitions().to_vec(),
+            (0..compact.state_count())
+                .map(|state| compact.accepts(state).to_vec())
+                .collect(),
+        );
+
+        for input in ["abc", "a1", "1", "ab12"] {
+            let run = |dfa: &CompactDfa| {
+                input
+                    .chars()
+                    .try_fold(0, |state, ch| dfa.next(state, ch))
+                    .and_then(|state| dfa.accept(state))
+            };
+
+            assert_eq!(run(&rebuilt), run(&compact), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_009_minimal_state_counts() {
+        for (pattern, expected) in [
+            ("a*", 1),
+            ("(a|aa)*", 1),
+            ("(ab)*", 2),
+            ("a(b|c)", 3),
+            ("a*|b*", 3),
+            ("a{2,3}", 4),
+            ("(a|b)*a(a|b)", 4),
+            ("(a|b)*a(a|b){4}", 32),
+            ("a{0,500}", 501),
+        ] {
+            let graph = parse::<NoCustomError>(pattern).unwrap().into_graph();
+            let min = Dfa::<CharSet>::from_graphs::<()>(&[(0, graph)]).minimize();
+
+            assert_eq!(min.len(), expected, "{}", pattern);
+        }
+    }
+}
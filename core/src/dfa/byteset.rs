@@ -0,0 +1,285 @@
+use std::{
+    collections::HashMap,
+    ops::{BitAnd, BitOr, Not, RangeInclusive, Sub},
+};
+
+use crate::dfa::{self, graph::Graph, Alphabet, Classes, IntoGraph};
+
+/// A set of bytes, the counterpart of [CharSet](super::CharSet) for
+/// binary inputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ByteSet([u128; 2]);
+
+impl ByteSet {
+    /// The empty set.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Every byte.
+    pub fn all() -> Self {
+        Self([u128::MAX; 2])
+    }
+
+    pub fn eq(byte: u8) -> Self {
+        let mut set = Self::empty();
+        set.insert(byte);
+        set
+    }
+
+    pub fn r#in<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        let mut set = Self::empty();
+        iter.into_iter().for_each(|byte| set.insert(byte));
+        set
+    }
+
+    /// The ASCII chars of the set.
+    pub fn ascii(chars: &str) -> Self {
+        Self::r#in(chars.bytes().filter(u8::is_ascii))
+    }
+
+    pub fn insert(&mut self, byte: u8) {
+        self.0[(byte >> 7) as usize] |= 1 << (byte & 0x7f);
+    }
+
+    pub fn contains(&self, byte: &u8) -> bool {
+        self.0[(byte >> 7) as usize] & (1 << (byte & 0x7f)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == [0; 2]
+    }
+
+    /// The number of bytes in the set.
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// Iterate over the bytes of the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=u8::MAX).filter(|byte| self.contains(byte))
+    }
+}
+
+impl dfa::Set for ByteSet {
+    type Item = u8;
+
+    fn intersect(lhs: Self, rhs: Self) -> Self {
+        lhs & rhs
+    }
+
+    fn union(lhs: Self, rhs: Self) -> Self {
+        lhs | rhs
+    }
+
+    fn difference(lhs: Self, rhs: Self) -> Self {
+        lhs - rhs
+    }
+
+    fn is_empty(&self) -> bool {
+        ByteSet::is_empty(self)
+    }
+
+    fn contains(&self, byte: &Self::Item) -> bool {
+        ByteSet::contains(self, byte)
+    }
+
+    fn partition(sets: &[&Self]) -> Vec<(Self, Vec<usize>)> {
+        let mut parts = Vec::<(Self, Vec<usize>)>::default();
+
+        for byte in 0..=u8::MAX {
+            let signature = sets
+                .iter()
+                .enumerate()
+                .filter(|(_, set)| set.contains(&byte))
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+
+            if signature.is_empty() {
+                continue;
+            }
+
+            match parts.iter_mut().find(|(_, sig)| *sig == signature) {
+                Some((set, _)) => set.insert(byte),
+                None => parts.push((Self::eq(byte), signature)),
+            }
+        }
+
+        parts
+    }
+}
+
+/// The classes of bytes, on which the membership to every set of a
+/// family is constant.
+#[derive(Debug, Clone)]
+pub struct ByteClasses {
+    /// The class of each byte.
+    classes: Vec<usize>,
+    /// The first byte of each class.
+    representatives: Vec<u8>,
+}
+
+impl Classes<u8> for ByteClasses {
+    fn len(&self) -> usize {
+        self.representatives.len()
+    }
+
+    fn classify(&self, byte: u8) -> usize {
+        self.classes[byte as usize]
+    }
+
+    fn representatives(&self) -> Vec<u8> {
+        self.representatives.clone()
+    }
+}
+
+impl Alphabet for ByteSet {
+    type Classes = ByteClasses;
+
+    fn classes<'a>(sets: impl IntoIterator<Item = &'a Self>) -> Self::Classes {
+        let sets = sets.into_iter().collect::<Vec<_>>();
+        let mut ids = HashMap::<Vec<bool>, usize>::default();
+        let mut classes = ByteClasses {
+            classes: Vec::with_capacity(256),
+            representatives: vec![],
+        };
+
+        for byte in 0..=u8::MAX {
+            let signature = sets.iter().map(|set| set.contains(&byte)).collect();
+            let next = ids.len();
+            let class = *ids.entry(signature).or_insert(next);
+
+            if class == next {
+                classes.representatives.push(byte);
+            }
+
+            classes.classes.push(class);
+        }
+
+        classes
+    }
+}
+
+impl From<RangeInclusive<u8>> for ByteSet {
+    fn from(value: RangeInclusive<u8>) -> Self {
+        Self::r#in(value)
+    }
+}
+
+impl From<u8> for ByteSet {
+    fn from(value: u8) -> Self {
+        Self::eq(value)
+    }
+}
+
+impl BitAnd for ByteSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self([self.0[0] & rhs.0[0], self.0[1] & rhs.0[1]])
+    }
+}
+
+impl BitOr for ByteSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self([self.0[0] | rhs.0[0], self.0[1] | rhs.0[1]])
+    }
+}
+
+impl Sub for ByteSet {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self & !rhs
+    }
+}
+
+impl Not for ByteSet {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self([!self.0[0], !self.0[1]])
+    }
+}
+
+impl<A> IntoGraph<ByteSet, A> for ByteSet {
+    fn into_graph(self) -> Graph<ByteSet, A> {
+        Graph::single(self)
+    }
+}
+
+impl<A> IntoGraph<ByteSet, A> for u8 {
+    fn into_graph(self) -> Graph<ByteSet, A> {
+        Graph::single(ByteSet::eq(self))
+    }
+}
+
+/// A literal sequence of bytes.
+impl<A> IntoGraph<ByteSet, A> for &[u8] {
+    fn into_graph(self) -> Graph<ByteSet, A> {
+        self.iter()
+            .map(|&byte| IntoGraph::into_graph(byte))
+            .reduce(|a: Graph<ByteSet, A>, b| a + b)
+            .unwrap_or_else(Graph::empty)
+    }
+}
+
+impl<A, const N: usize> IntoGraph<ByteSet, A> for &[u8; N] {
+    fn into_graph(self) -> Graph<ByteSet, A> {
+        self.as_slice().into_graph()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dfa::{Alphabet, Classes, Set as _};
+
+    use super::ByteSet;
+
+    #[test]
+    fn test_operations() {
+        let digits = ByteSet::from(b'0'..=b'9');
+        let hex = digits | ByteSet::from(b'a'..=b'f');
+
+        assert_eq!(hex.len(), 16);
+        assert!(hex.contains(&b'c'));
+        assert!(!digits.contains(&b'c'));
+        assert_eq!(hex - digits, ByteSet::from(b'a'..=b'f'));
+        assert_eq!((!hex).len(), 240);
+        assert!((hex & !hex).is_empty());
+        assert!((!ByteSet::empty()).contains(&0xff));
+    }
+
+    #[test]
+    fn test_partition() {
+        let a = ByteSet::from(b'a'..=b'm');
+        let b = ByteSet::from(b'h'..=b'z');
+
+        let parts = ByteSet::partition(&[&a, &b]);
+
+        assert_eq!(
+            parts,
+            vec![
+                (ByteSet::from(b'a'..=b'g'), vec![0]),
+                (ByteSet::from(b'h'..=b'm'), vec![0, 1]),
+                (ByteSet::from(b'n'..=b'z'), vec![1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classes() {
+        let sets = [ByteSet::from(b'0'..=b'9'), ByteSet::eq(b'\n')];
+        let classes = ByteSet::classes(&sets);
+
+        assert_eq!(classes.len(), 3);
+        assert_eq!(classes.classify(b'0'), classes.classify(b'7'));
+        assert_ne!(classes.classify(b'0'), classes.classify(b'\n'));
+        assert_eq!(classes.classify(0), classes.classify(0xff));
+    }
+}
@@ -1,437 +1,376 @@
-use std::{
-    collections::HashSet,
-    ops::{BitAnd, BitOr, Not, Range, RangeInclusive},
-};
-
-use crate::dfa;
-
-/// A set of chars
-pub enum CharSet {
-    And(And),
-    Or(Or),
-    Gt(Gt),
-    Gte(Gte),
-    Lt(Lt),
-    Lte(Lte),
-    Eq(Eq),
-    NotEq(NotEq),
-    In(In),
-    NotIn(NotIn),
-    All,
-    Epsilon,
-}
+use std::ops::{BitAnd, BitOr, Not, Range, RangeInclusive, Sub};
+
+use crate::dfa::{self, graph::Graph, Alphabet, Classes, IntoGraph};
+
+/// A set of chars.
+///
+/// It is kept in canonical form : sorted, disjoint and non-adjacent
+/// ranges, so set operations and emptiness are exact, and two equal
+/// sets have the same representation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CharSet(Vec<RangeInclusive<char>>);
 
 impl CharSet {
+    /// The empty set.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Every char.
+    pub fn all() -> Self {
+        Self(vec!['\0'..=char::MAX])
+    }
+
     pub fn eq(ch: char) -> Self {
-        Self::Eq(Eq(ch))
+        Self(vec![ch..=ch])
     }
 
     pub fn gt(ch: char) -> Self {
-        Self::Gt(Gt(ch))
+        succ(ch).map(Self::gte).unwrap_or_default()
     }
+
     pub fn gte(ch: char) -> Self {
-        Self::Gte(Gte(ch))
+        Self(vec![ch..=char::MAX])
     }
 
     pub fn lt(ch: char) -> Self {
-        Self::Lt(Lt(ch))
+        pred(ch).map(Self::lte).unwrap_or_default()
     }
 
     pub fn lte(ch: char) -> Self {
-        Self::Lte(Lte(ch))
+        Self(vec!['\0'..=ch])
     }
 
     pub fn r#in<I>(iter: I) -> Self
     where
         I: IntoIterator<Item = char>,
     {
-        Self::In(In(iter.into_iter().collect()))
+        Self::from_ranges(iter.into_iter().map(|ch| ch..=ch))
     }
-}
 
-impl dfa::Set for CharSet {
-    type Item = char;
+    /// Build the set from arbitrary ranges, empty ranges are ignored.
+    pub fn from_ranges<I>(ranges: I) -> Self
+    where
+        I: IntoIterator<Item = RangeInclusive<char>>,
+    {
+        let mut ranges = ranges
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect::<Vec<_>>();
+
+        ranges.sort_by_key(|range| *range.start());
+
+        let mut canonical = Vec::<RangeInclusive<char>>::with_capacity(ranges.len());
+
+        for range in ranges {
+            match canonical.last_mut() {
+                Some(last) if succ(*last.end()).is_none_or(|next| next >= *range.start()) => {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => canonical.push(range),
+            }
+        }
 
-    fn intersect(lhs: Self, rhs: Self) -> Self {
-        lhs & rhs
+        Self(canonical)
     }
 
-    fn union(lhs: Self, rhs: Self) -> Self {
-        lhs | rhs
+    /// Iterate over the sorted, disjoint, ranges of the set.
+    pub fn iter_ranges(&self) -> impl Iterator<Item = &RangeInclusive<char>> {
+        self.0.iter()
     }
 
-    fn difference(lhs: Self, rhs: Self) -> Self {
-        lhs & !rhs
+    pub fn contains(&self, ch: &char) -> bool {
+        let pos = self.0.partition_point(|range| range.end() < ch);
+        self.0.get(pos).is_some_and(|range| range.contains(ch))
     }
 
-    fn is_empty(&self) -> bool {
-        match self {
-            CharSet::And(a) => a.is_empty(),
-            CharSet::Or(a) => a.is_empty(),
-            CharSet::Gt(a) => a.is_empty(),
-            CharSet::Gte(a) => a.is_empty(),
-            CharSet::Lt(a) => a.is_empty(),
-            CharSet::Lte(a) => a.is_empty(),
-            CharSet::Eq(a) => a.is_empty(),
-            CharSet::NotEq(a) => a.is_empty(),
-            CharSet::In(a) => a.is_empty(),
-            CharSet::NotIn(a) => a.is_empty(),
-            CharSet::All => false,
-            CharSet::Epsilon => true,
-        }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
 
-    fn contains(&self, ch: &Self::Item) -> bool {
-        match self {
-            CharSet::And(a) => a.contains(ch),
-            CharSet::Or(a) => a.contains(ch),
-            CharSet::Gt(a) => a.contains(ch),
-            CharSet::Gte(a) => a.contains(ch),
-            CharSet::Lt(a) => a.contains(ch),
-            CharSet::Lte(a) => a.contains(ch),
-            CharSet::Eq(a) => a.contains(ch),
-            CharSet::NotEq(a) => a.contains(ch),
-            CharSet::In(a) => a.contains(ch),
-            CharSet::NotIn(a) => a.contains(ch),
-            CharSet::All => true,
-            CharSet::Epsilon => false,
-        }
+    /// The number of chars in the set.
+    pub fn len(&self) -> usize {
+        self.0
+            .iter()
+            .map(|range| {
+                let surrogates = CharSet::surrogates_in(range);
+                (*range.end() as usize) - (*range.start() as usize) + 1 - surrogates
+            })
+            .sum()
     }
-}
 
-impl From<Range<char>> for CharSet {
-    fn from(value: Range<char>) -> Self {
-        CharSet::gte(value.start) & CharSet::lt(value.end)
+    fn surrogates_in(range: &RangeInclusive<char>) -> usize {
+        if *range.start() <= '\u{D7FF}' && *range.end() >= '\u{E000}' {
+            0x800
+        } else {
+            0
+        }
     }
-}
 
-impl From<RangeInclusive<char>> for CharSet {
-    fn from(value: RangeInclusive<char>) -> Self {
-        CharSet::gte(*value.start()) & CharSet::lte(*value.end())
-    }
-}
+    pub fn intersection(&self, rhs: &Self) -> Self {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
 
-impl From<char> for CharSet {
-    fn from(value: char) -> Self {
-        CharSet::Eq(Eq(value))
-    }
-}
+        while let (Some(a), Some(b)) = (self.0.get(i), rhs.0.get(j)) {
+            let lo = *a.start().max(b.start());
+            let hi = *a.end().min(b.end());
 
-impl From<And> for CharSet {
-    fn from(value: And) -> Self {
-        if value.is_empty() {
-            Self::Epsilon
-        } else {
-            Self::And(value)
-        }
-    }
-}
+            if lo <= hi {
+                ranges.push(lo..=hi);
+            }
 
-impl From<Or> for CharSet {
-    fn from(value: Or) -> Self {
-        if value.is_empty() {
-            Self::Epsilon
-        } else {
-            Self::Or(value)
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
         }
-    }
-}
 
-impl From<Gt> for CharSet {
-    fn from(value: Gt) -> Self {
-        if value.is_empty() {
-            Self::Epsilon
-        } else {
-            Self::Gt(value)
-        }
+        Self(ranges)
     }
-}
 
-impl From<Gte> for CharSet {
-    fn from(value: Gte) -> Self {
-        Self::Gte(value)
-    }
-}
+    pub fn complement(&self) -> Self {
+        let mut ranges = vec![];
+        let mut lo = Some('\0');
 
-impl From<Lt> for CharSet {
-    fn from(value: Lt) -> Self {
-        if value.is_empty() {
-            Self::Epsilon
-        } else {
-            Self::Lt(value)
+        for range in self.0.iter() {
+            if let Some(from) = lo {
+                if let Some(to) = pred(*range.start()).filter(|to| from <= *to) {
+                    ranges.push(from..=to);
+                }
+            }
+
+            lo = succ(*range.end());
         }
-    }
-}
 
-impl From<Lte> for CharSet {
-    fn from(value: Lte) -> Self {
-        CharSet::Lte(value)
+        ranges.extend(lo.map(|from| from..=char::MAX));
+        Self(ranges)
     }
 }
 
-impl From<Eq> for CharSet {
-    fn from(value: Eq) -> Self {
-        CharSet::Eq(value)
-    }
-}
+impl dfa::Set for CharSet {
+    type Item = char;
 
-impl From<NotEq> for CharSet {
-    fn from(value: NotEq) -> Self {
-        CharSet::NotEq(value)
+    fn intersect(lhs: Self, rhs: Self) -> Self {
+        lhs & rhs
     }
-}
 
-impl From<In> for CharSet {
-    fn from(value: In) -> Self {
-        if value.is_empty() {
-            Self::Epsilon
-        } else {
-            CharSet::In(value)
-        }
+    fn union(lhs: Self, rhs: Self) -> Self {
+        lhs | rhs
     }
-}
 
-impl From<NotIn> for CharSet {
-    fn from(value: NotIn) -> Self {
-        if value.is_empty() {
-            Self::Epsilon
-        } else {
-            CharSet::NotIn(value)
-        }
+    fn difference(lhs: Self, rhs: Self) -> Self {
+        lhs - rhs
     }
-}
 
-impl CharSet {
-    pub fn contains(&self, ch: &char) -> bool {
-        match self {
-            CharSet::And(a) => a.contains(ch),
-            CharSet::Or(a) => a.contains(ch),
-            CharSet::Gt(a) => a.contains(ch),
-            CharSet::Gte(a) => a.contains(ch),
-            CharSet::Lt(a) => a.contains(ch),
-            CharSet::Lte(a) => a.contains(ch),
-            CharSet::Eq(a) => a.contains(ch),
-            CharSet::NotEq(a) => a.contains(ch),
-            CharSet::In(a) => a.contains(ch),
-            CharSet::NotIn(a) => a.contains(ch),
-            CharSet::All => true,
-            CharSet::Epsilon => false,
-        }
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
     }
-}
-
-impl BitOr for CharSet {
-    type Output = Self;
 
-    fn bitor(self, rhs: Self) -> Self::Output {
-        Or(vec![self, rhs]).into()
+    fn contains(&self, ch: &Self::Item) -> bool {
+        CharSet::contains(self, ch)
     }
-}
 
-impl BitAnd for CharSet {
-    type Output = Self;
+    fn partition(sets: &[&Self]) -> Vec<(Self, Vec<usize>)> {
+        let classes = CharClasses::new(sets.iter().copied());
 
-    fn bitand(self, rhs: Self) -> Self::Output {
-        And(vec![self, rhs]).into()
-    }
-}
+        let mut parts = Vec::<(Vec<usize>, Vec<RangeInclusive<char>>)>::default();
 
-impl Not for CharSet {
-    type Output = Self;
+        for range in classes.iter_intervals() {
+            let signature = sets
+                .iter()
+                .enumerate()
+                .filter(|(_, set)| set.contains(range.start()))
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
 
-    fn not(self) -> Self::Output {
-        match self {
-            CharSet::And(a) => a.not().into(),
-            CharSet::Or(a) => a.not().into(),
-            CharSet::Gt(a) => a.not().into(),
-            CharSet::Gte(a) => a.not().into(),
-            CharSet::Lt(a) => a.not().into(),
-            CharSet::Lte(a) => a.not().into(),
-            CharSet::Eq(a) => a.not().into(),
-            CharSet::NotEq(a) => a.not().into(),
-            CharSet::In(a) => a.not().into(),
-            CharSet::NotIn(a) => a.not().into(),
-            CharSet::All => CharSet::Epsilon,
-            CharSet::Epsilon => CharSet::All,
-        }
-    }
-}
+            if signature.is_empty() {
+                continue;
+            }
 
-pub struct In(HashSet<char>);
-impl In {
-    pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
-    }
+            match parts.iter_mut().find(|(sig, _)| *sig == signature) {
+                Some((_, ranges)) => ranges.push(range),
+                None => parts.push((signature, vec![range])),
+            }
+        }
 
-    pub fn contains(&self, ch: &char) -> bool {
-        self.0.contains(ch)
+        parts
+            .into_iter()
+            .map(|(signature, ranges)| (CharSet::from_ranges(ranges), signature))
+            .collect()
     }
 }
-impl Not for In {
-    type Output = NotIn;
 
-    fn not(self) -> Self::Output {
-        NotIn(self.0)
-    }
+/// Elementary intervals of the chars, on which the membership to
+/// every set of a family is constant.
+///
+/// It is used to compress the transitions of an automaton into
+/// character classes.
+#[derive(Debug, Clone)]
+pub struct CharClasses {
+    /// The first char of each interval, the first one being \0.
+    starts: Vec<char>,
 }
 
-pub struct NotIn(HashSet<char>);
-impl Not for NotIn {
-    type Output = In;
+impl CharClasses {
+    pub fn new<'a>(sets: impl IntoIterator<Item = &'a CharSet>) -> Self {
+        let mut starts = vec!['\0'];
 
-    fn not(self) -> Self::Output {
-        In(self.0)
-    }
-}
-impl NotIn {
-    pub fn contains(&self, ch: &char) -> bool {
-        !self.0.contains(ch)
+        for range in sets.into_iter().flat_map(|set| set.iter_ranges()) {
+            starts.push(*range.start());
+            starts.extend(succ(*range.end()));
+        }
+
+        starts.sort();
+        starts.dedup();
+
+        Self { starts }
     }
-    pub fn is_empty(&self) -> bool {
-        self.0.len() == (char::MAX as usize) + 1
+
+    /// The classes starting at each char, as listed by their
+    /// representatives.
+    pub fn from_starts(starts: Vec<char>) -> Self {
+        Self { starts }
     }
-}
 
-pub struct And(Vec<CharSet>);
-impl And {
-    pub fn contains(&self, ch: &char) -> bool {
-        self.0.iter().all(|a| a.contains(ch))
+    pub fn len(&self) -> usize {
+        self.starts.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.0.iter().any(dfa::Set::is_empty)
+        self.starts.is_empty()
     }
-}
 
-impl Not for And {
-    type Output = Or;
-
-    fn not(self) -> Self::Output {
-        Or(self.0.into_iter().map(CharSet::not).collect())
-    }
-}
-pub struct Or(Vec<CharSet>);
-impl Or {
-    pub fn contains(&self, ch: &char) -> bool {
-        self.0.iter().any(|a| a.contains(ch))
+    /// The interval containing the char.
+    pub fn classify(&self, ch: char) -> usize {
+        self.starts.partition_point(|start| *start <= ch) - 1
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.0.iter().all(dfa::Set::is_empty)
+    pub fn iter_intervals(&self) -> impl Iterator<Item = RangeInclusive<char>> + '_ {
+        self.starts.iter().enumerate().map(|(i, &start)| {
+            let end = self
+                .starts
+                .get(i + 1)
+                .and_then(|&next| pred(next))
+                .unwrap_or(char::MAX);
+
+            start..=end
+        })
     }
 }
-impl Not for Or {
-    type Output = And;
 
-    fn not(self) -> Self::Output {
-        And(self.0.into_iter().map(CharSet::not).collect())
+impl Classes<char> for CharClasses {
+    fn len(&self) -> usize {
+        CharClasses::len(self)
     }
-}
 
-pub struct Gt(char);
-impl Gt {
-    pub fn contains(&self, ch: &char) -> bool {
-        *ch > self.0
+    fn classify(&self, ch: char) -> usize {
+        CharClasses::classify(self, ch)
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.0 == char::MAX
+    fn representatives(&self) -> Vec<char> {
+        self.starts.clone()
     }
 }
-impl Not for Gt {
-    type Output = Lte;
 
-    fn not(self) -> Self::Output {
-        Lte(self.0)
+impl Alphabet for CharSet {
+    type Classes = CharClasses;
+
+    fn classes<'a>(sets: impl IntoIterator<Item = &'a Self>) -> Self::Classes {
+        CharClasses::new(sets)
     }
 }
 
-pub struct Gte(char);
-impl Gte {
-    pub fn contains(&self, ch: &char) -> bool {
-        *ch >= self.0
+impl From<Range<char>> for CharSet {
+    fn from(value: Range<char>) -> Self {
+        match pred(value.end) {
+            Some(end) => Self::from_ranges([value.start..=end]),
+            None => Self::empty(),
+        }
     }
+}
 
-    pub fn is_empty(&self) -> bool {
-        true
+impl From<RangeInclusive<char>> for CharSet {
+    fn from(value: RangeInclusive<char>) -> Self {
+        Self::from_ranges([value])
     }
 }
-impl Not for Gte {
-    type Output = Lt;
 
-    fn not(self) -> Self::Output {
-        Lt(self.0)
+impl From<char> for CharSet {
+    fn from(value: char) -> Self {
+        Self::eq(value)
     }
 }
 
-pub struct Lt(char);
-impl Lt {
-    pub fn contains(&self, ch: &char) -> bool {
-        *ch < self.0
-    }
+impl BitAnd for CharSet {
+    type Output = Self;
 
-    pub fn is_empty(&self) -> bool {
-        self.0 == '\0'
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(&rhs)
     }
 }
-impl Not for Lt {
-    type Output = Gte;
 
-    fn not(self) -> Gte {
-        Gte(self.0)
+impl BitOr for CharSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self::from_ranges(self.0.into_iter().chain(rhs.0))
     }
 }
 
-pub struct Lte(char);
-impl Lte {
-    pub fn contains(&self, ch: &char) -> bool {
-        *ch <= self.0
-    }
+impl Sub for CharSet {
+    type Output = Self;
 
-    pub fn is_empty(&self) -> bool {
-        true
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.intersection(&rhs.complement())
     }
 }
-impl Not for Lte {
-    type Output = Gt;
+
+impl Not for CharSet {
+    type Output = Self;
 
     fn not(self) -> Self::Output {
-        Gt(self.0)
+        self.complement()
     }
 }
-pub struct Eq(char);
-impl Eq {
-    pub fn contains(&self, ch: &char) -> bool {
-        *ch == self.0
-    }
 
-    pub fn is_empty(&self) -> bool {
-        false
+/// The next char, skipping surrogates.
+fn succ(ch: char) -> Option<char> {
+    match ch {
+        '\u{D7FF}' => Some('\u{E000}'),
+        _ => char::from_u32(ch as u32 + 1),
     }
 }
-impl Not for Eq {
-    type Output = NotEq;
 
-    fn not(self) -> Self::Output {
-        NotEq(self.0)
+/// The previous char, skipping surrogates.
+fn pred(ch: char) -> Option<char> {
+    match ch {
+        '\u{E000}' => Some('\u{D7FF}'),
+        _ => (ch as u32).checked_sub(1).and_then(char::from_u32),
     }
 }
 
-pub struct NotEq(char);
-impl NotEq {
-    pub fn contains(&self, ch: &char) -> bool {
-        *ch != self.0
+impl<A> IntoGraph<CharSet, A> for CharSet {
+    fn into_graph(self) -> Graph<CharSet, A> {
+        Graph::single(self)
     }
+}
 
-    pub fn is_empty(&self) -> bool {
-        false
+impl<A> IntoGraph<CharSet, A> for char {
+    fn into_graph(self) -> Graph<CharSet, A> {
+        Graph::single(CharSet::eq(self))
     }
 }
-impl Not for NotEq {
-    type Output = Eq;
 
-    fn not(self) -> Self::Output {
-        Eq(self.0)
+/// A literal string.
+impl<A> IntoGraph<CharSet, A> for &str {
+    fn into_graph(self) -> Graph<CharSet, A> {
+        self.chars()
+            .map(IntoGraph::into_graph)
+            .reduce(|a: Graph<CharSet, A>, b| a + b)
+            .unwrap_or_else(Graph::empty)
     }
 }
 
@@ -439,7 +378,7 @@ impl Not for NotEq {
 mod tests {
     use crate::dfa::Set as _;
 
-    use super::CharSet;
+    use super::{CharClasses, CharSet};
 
     #[test]
     fn test_eq() {
@@ -522,4 +461,75 @@ mod tests {
         assert!(!at.contains(&'f'));
         assert!(at.contains(&'z'));
     }
+
+    #[test]
+    fn test_canonical_form() {
+        let set = CharSet::from_ranges(['d'..='f', 'a'..='b', 'c'..='c', 'x'..='z', 'y'..='y']);
+        let ranges = set.iter_ranges().cloned().collect::<Vec<_>>();
+
+        assert_eq!(ranges, vec!['a'..='f', 'x'..='z']);
+        assert_eq!(set, CharSet::from('a'..='f') | CharSet::from('x'..='z'));
+        assert_eq!(set.len(), 9);
+    }
+
+    #[test]
+    fn test_emptiness() {
+        let az = CharSet::from('a'..='z');
+
+        assert!(CharSet::intersect(CharSet::lt('a'), CharSet::gt('z')).is_empty());
+        assert!(CharSet::difference(az.clone(), CharSet::from('a'..='z')).is_empty());
+        assert!((az.clone() & !az.clone()).is_empty());
+        assert!(!(az & CharSet::eq('q')).is_empty());
+        assert!(CharSet::gt(char::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_complement() {
+        let set = CharSet::from('b'..='y') | CharSet::eq('\0');
+        let not = !set.clone();
+
+        assert!(!not.contains(&'\0'));
+        assert!(not.contains(&'a'));
+        assert!(!not.contains(&'m'));
+        assert!(not.contains(&'z'));
+        assert!(not.contains(&char::MAX));
+        assert_eq!(!not, set);
+        assert_eq!(!CharSet::all(), CharSet::empty());
+        assert_eq!(CharSet::all().len(), 0x110000 - 0x800);
+    }
+
+    #[test]
+    fn test_classes() {
+        let az = CharSet::from('a'..='z');
+        let f = CharSet::eq('f');
+        let classes = CharClasses::new([&az, &f]);
+
+        // [\0-`], [a-e], [f], [g-z], [{-MAX]
+        assert_eq!(classes.len(), 5);
+        assert_eq!(classes.classify('\0'), 0);
+        assert_eq!(classes.classify('a'), 1);
+        assert_eq!(classes.classify('e'), 1);
+        assert_eq!(classes.classify('f'), 2);
+        assert_eq!(classes.classify('z'), 3);
+        assert_eq!(classes.classify(char::MAX), 4);
+    }
+
+    #[test]
+    fn test_partition() {
+        let az = CharSet::from('a'..='z');
+        let f = CharSet::eq('f');
+
+        let parts = CharSet::partition(&[&az, &f]);
+        assert_eq!(parts.len(), 2);
+
+        let (only_az, signature) = parts.iter().find(|(_, sig)| sig.len() == 1).unwrap();
+        assert_eq!(signature, &vec![0]);
+        assert!(only_az.contains(&'a'));
+        assert!(only_az.contains(&'z'));
+        assert!(!only_az.contains(&'f'));
+
+        let (shared, _) = parts.iter().find(|(_, sig)| sig.len() == 2).unwrap();
+        assert!(shared.contains(&'f'));
+        assert!(!shared.contains(&'e'));
+    }
 }
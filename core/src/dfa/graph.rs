@@ -11,14 +11,51 @@ where
     fn into_graph(self) -> Graph<S, A>;
 }
 
-#[derive(Default)]
+impl<S, A> IntoGraph<S, A> for Graph<S, A>
+where
+    S: Set,
+{
+    fn into_graph(self) -> Graph<S, A> {
+        self
+    }
+}
+
+/// A fragment of an automaton, between a start and an end node.
+///
+/// The start node has no entering edge, and the end node has no
+/// leaving edge, so fragments can be composed without interfering.
 pub struct Graph<S, A> {
     edges: EdgeSet<S, A>,
-    offset: usize,
+    /// Transitions taken without consuming any item.
+    epsilons: Vec<EdgeId>,
     count: usize,
 }
 
+impl<S, A> Default for Graph<S, A> {
+    fn default() -> Self {
+        Self {
+            edges: Default::default(),
+            epsilons: Default::default(),
+            count: 0,
+        }
+    }
+}
+
 impl<S, A> Graph<S, A> {
+    /// A fragment matching a single item of the set.
+    pub fn single(set: S) -> Self {
+        let mut graph = Self::default();
+        graph.on(Node::Start, Node::End, set, []);
+        graph
+    }
+
+    /// A fragment matching the empty sequence.
+    pub fn empty() -> Self {
+        let mut graph = Self::default();
+        graph.epsilon(Node::Start, Node::End);
+        graph
+    }
+
     /// Add a new node.
     pub fn add(&mut self) -> Node {
         let node = self.count;
@@ -26,6 +63,15 @@ impl<S, A> Graph<S, A> {
         node.into()
     }
 
+    /// The number of internal nodes.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty() && self.epsilons.is_empty()
+    }
+
     /// Connect two states
     pub fn on<I>(&mut self, from: Node, to: Node, set: S, actions: I)
     where
@@ -39,6 +85,19 @@ impl<S, A> Graph<S, A> {
         })
     }
 
+    /// Connect two states without consuming any item.
+    pub fn epsilon(&mut self, from: Node, to: Node) {
+        self.epsilons.push(EdgeId { from, to })
+    }
+
+    pub fn iter_edges(&self) -> impl Iterator<Item = &Edge<S, A>> {
+        self.edges.iter()
+    }
+
+    pub fn iter_epsilons(&self) -> impl Iterator<Item = &EdgeId> {
+        self.epsilons.iter()
+    }
+
     pub fn iter_entering_edges(&self) -> impl Iterator<Item = &Edge<S, A>> {
         self.edges.iter().filter(|edge| edge.from.is_start())
     }
@@ -53,9 +112,62 @@ impl<S, A> Graph<S, A> {
             .filter(|edge| edge.to.is_internal() && edge.from.is_internal())
     }
 
+    /// Offset the internal nodes.
     pub fn offset(&mut self, n: usize) {
-        self.offset += n;
-        self.edges.iter_mut().for_each(|edge| *edge += n);
+        self.map_nodes(|mut node| {
+            node += n;
+            node
+        })
+    }
+
+    fn map_nodes(&mut self, f: impl Fn(Node) -> Node) {
+        self.edges.iter_mut().for_each(|edge| {
+            edge.from = f(edge.from);
+            edge.to = f(edge.to);
+        });
+
+        self.epsilons.iter_mut().for_each(|id| {
+            id.from = f(id.from);
+            id.to = f(id.to);
+        });
+    }
+
+    fn extend(&mut self, rhs: Self) {
+        self.edges.extend(rhs.edges.0);
+        self.epsilons.extend(rhs.epsilons);
+    }
+
+    /// Append a graph, the end of self becomes the start of rhs.
+    pub fn append(&mut self, mut rhs: Self) {
+        let mid = Node::Internal(self.count);
+        rhs.offset(self.count + 1);
+        self.count += rhs.count + 1;
+
+        self.map_nodes(|node| if node.is_end() { mid } else { node });
+        rhs.map_nodes(|node| if node.is_start() { mid } else { node });
+        self.extend(rhs);
+    }
+
+    /// Either self, or rhs.
+    pub fn merge(mut self, mut rhs: Self) -> Self {
+        rhs.offset(self.count);
+        self.count += rhs.count;
+        self.extend(rhs);
+        self
+    }
+
+    /// Zero or more repetitions.
+    pub fn star(mut self) -> Self {
+        let hub = self.add();
+        self.map_nodes(|node| if node.is_internal() { node } else { hub });
+        self.epsilon(Node::Start, hub);
+        self.epsilon(hub, Node::End);
+        self
+    }
+
+    /// Zero or one occurrence.
+    pub fn optional(self) -> Self {
+        self.merge(Self::empty())
     }
 }
 
@@ -64,29 +176,40 @@ where
     S: Clone,
     A: Clone,
 {
-    /// Append a graph
-    pub fn append(&mut self, mut rhs: Self) {
-        rhs += self.offset + self.count;
-
-        self.count += rhs.count;
-        let mut edges = EdgeSet::default();
-
-        edges.extend(self.iter_entering_edges().cloned());
-        edges.extend(self.iter_internal_edges().cloned());
-        edges.extend(rhs.iter_internal_edges().cloned());
-        edges.extend(rhs.iter_leaving_edges().cloned());
+    /// One or more repetitions.
+    pub fn plus(self) -> Self {
+        self.clone() + self.star()
+    }
 
-        // connect lhs::leaving to rhs::entering
-        edges.extend(self.iter_leaving_edges().flat_map(|leaving| {
-            rhs.iter_entering_edges().map(|entering| Edge {
-                from: leaving.from,
-                set: entering.set.clone(),
-                actions: entering.actions.clone() + leaving.actions.clone(),
-                to: entering.to,
-            })
-        }));
+    /// Between min and max repetitions, unbounded if max is None.
+    pub fn repeat(self, min: usize, max: Option<usize>) -> Self {
+        let mandatory = (0..min).map(|_| self.clone()).collect::<Vec<_>>();
+
+        let tail = match max {
+            None => self.star(),
+            Some(max) => (min..max)
+                .map(|_| self.clone().optional())
+                .fold(Self::empty(), |acc, opt| acc + opt),
+        };
+
+        mandatory
+            .into_iter()
+            .fold(Self::empty(), |acc, graph| acc + graph)
+            + tail
+    }
+}
 
-        self.edges = edges;
+impl<S, A> Clone for Graph<S, A>
+where
+    S: Clone,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            edges: self.edges.iter().cloned().collect(),
+            epsilons: self.epsilons.clone(),
+            count: self.count,
+        }
     }
 }
 
@@ -98,11 +221,7 @@ impl<S, A> std::ops::AddAssign<usize> for Graph<S, A> {
 }
 
 /// Append two fragments
-impl<S, A> std::ops::Add<Self> for Graph<S, A>
-where
-    S: Clone,
-    A: Clone,
-{
+impl<S, A> std::ops::Add<Self> for Graph<S, A> {
     type Output = Self;
 
     fn add(mut self, rhs: Self) -> Self::Output {
@@ -111,7 +230,7 @@ where
     }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Node {
     Start,
     Internal(usize),
@@ -152,7 +271,7 @@ impl std::ops::AddAssign<usize> for Node {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EdgeId {
     pub from: Node,
     pub to: Node,
@@ -212,7 +331,7 @@ where
     A: Clone,
 {
     pub fn cartesian_product(self, rhs: Self) -> impl Iterator<Item = (Edge<S, A>, Edge<S, A>)> {
-        self.0.into_iter().cartesian_product(rhs.0.into_iter())
+        self.0.into_iter().cartesian_product(rhs.0)
     }
 }
 
@@ -270,3 +389,52 @@ impl<A> FromIterator<A> for ActionSequence<A> {
         Self(iter.into_iter().collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::dfa::{automaton::Dfa, charset::CharSet, IntoGraph};
+
+    use super::Graph;
+
+    fn matches(graph: Graph<CharSet, ()>, input: &str) -> bool {
+        let dfa = Dfa::from_graphs(&[(0, graph)]);
+
+        input
+            .chars()
+            .try_fold(0, |state, ch| dfa.next(state, &ch))
+            .and_then(|state| dfa.accept(state))
+            .is_some()
+    }
+
+    #[test]
+    fn test_repeat_bounded() {
+        // a{2,3}
+        let graph: Graph<CharSet, ()> = 'a'.into_graph().repeat(2, Some(3));
+
+        for (input, expected) in [
+            ("", false),
+            ("a", false),
+            ("aa", true),
+            ("aaa", true),
+            ("aaaa", false),
+        ] {
+            assert_eq!(matches(graph.clone(), input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_repeat_unbounded() {
+        // a{2,}
+        let graph: Graph<CharSet, ()> = 'a'.into_graph().repeat(2, None);
+
+        for (input, expected) in [
+            ("", false),
+            ("a", false),
+            ("aa", true),
+            ("aaaaa", true),
+            ("aab", false),
+        ] {
+            assert_eq!(matches(graph.clone(), input), expected, "{:?}", input);
+        }
+    }
+}
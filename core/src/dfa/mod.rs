@@ -2,9 +2,16 @@
   Deterministic finite automaton (DFA)
 */
 
+pub mod automaton;
+pub mod byteset;
 pub mod charset;
-pub mod cross;
 pub mod graph;
+pub mod unicode;
+
+pub use automaton::{CompactDfa, Dfa, DfaState};
+pub use byteset::{ByteClasses, ByteSet};
+pub use charset::{CharClasses, CharSet};
+pub use graph::{Graph, IntoGraph};
 
 /// A trait defining set-related basic operations.
 pub trait Set {
@@ -15,4 +22,39 @@ pub trait Set {
     fn difference(lhs: Self, rhs: Self) -> Self;
     fn is_empty(&self) -> bool;
     fn contains(&self, item: &Self::Item) -> bool;
+
+    /// Split the sets into disjoint, non-empty, parts.
+    ///
+    /// Each part comes with the indexes of the sets containing it,
+    /// so every set is the union of the parts referencing it.
+    fn partition(sets: &[&Self]) -> Vec<(Self, Vec<usize>)>
+    where
+        Self: Sized;
+}
+
+/// A set of input units, whose automata can be compacted into a
+/// transition table indexed by classes of units.
+pub trait Alphabet: Set + Sized {
+    type Classes: Classes<Self::Item> + std::fmt::Debug + Clone;
+
+    /// The classes of units distinguished by the sets.
+    fn classes<'a>(sets: impl IntoIterator<Item = &'a Self>) -> Self::Classes
+    where
+        Self: 'a;
+}
+
+/// A partition of the units into classes, whose units belong to the
+/// same sets.
+pub trait Classes<Item> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The class of the unit.
+    fn classify(&self, item: Item) -> usize;
+
+    /// A unit of each class, by class index.
+    fn representatives(&self) -> Vec<Item>;
 }
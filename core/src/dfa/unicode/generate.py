@@ -0,0 +1,130 @@
+#!/usr/bin/env python3
+"""Generate the Unicode range tables of tables.rs.
+
+It relies on the Unicode database of the `regex` package, so the
+tables can be regenerated offline:
+
+    python3 generate.py > tables.rs
+"""
+import regex
+
+GENERAL_CATEGORIES = [
+    ("Lu", "Uppercase_Letter"), ("Ll", "Lowercase_Letter"), ("Lt", "Titlecase_Letter"),
+    ("Lm", "Modifier_Letter"), ("Lo", "Other_Letter"),
+    ("Mn", "Nonspacing_Mark"), ("Mc", "Spacing_Mark"), ("Me", "Enclosing_Mark"),
+    ("Nd", "Decimal_Number"), ("Nl", "Letter_Number"), ("No", "Other_Number"),
+    ("Pc", "Connector_Punctuation"), ("Pd", "Dash_Punctuation"), ("Ps", "Open_Punctuation"),
+    ("Pe", "Close_Punctuation"), ("Pi", "Initial_Punctuation"), ("Pf", "Final_Punctuation"),
+    ("Po", "Other_Punctuation"),
+    ("Sm", "Math_Symbol"), ("Sc", "Currency_Symbol"), ("Sk", "Modifier_Symbol"),
+    ("So", "Other_Symbol"),
+    ("Zs", "Space_Separator"), ("Zl", "Line_Separator"), ("Zp", "Paragraph_Separator"),
+    ("Cc", "Control"), ("Cf", "Format"), ("Co", "Private_Use"), ("Cn", "Unassigned"),
+]
+
+SCRIPTS = """
+Adlam Ahom Anatolian_Hieroglyphs Arabic Armenian Avestan Balinese Bamum Bassa_Vah
+Batak Beria_Erfe Bengali Bhaiksuki Bopomofo Brahmi Braille Buginese Buhid Canadian_Aboriginal
+Carian Caucasian_Albanian Chakma Cham Cherokee Chorasmian Common Coptic Cuneiform
+Cypriot Cypro_Minoan Cyrillic Deseret Devanagari Dives_Akuru Dogra Duployan
+Egyptian_Hieroglyphs Elbasan Elymaic Ethiopic Garay Georgian Glagolitic Gothic Grantha
+Greek Gujarati Gunjala_Gondi Gurmukhi Gurung_Khema Han Hangul Hanifi_Rohingya Hanunoo
+Hatran Hebrew Hiragana Imperial_Aramaic Inherited Inscriptional_Pahlavi
+Inscriptional_Parthian Javanese Kaithi Kannada Katakana Kawi Kayah_Li Kharoshthi
+Khitan_Small_Script Khmer Khojki Khudawadi Kirat_Rai Lao Latin Lepcha Limbu Linear_A
+Linear_B Lisu Lycian Lydian Mahajani Makasar Malayalam Mandaic Manichaean Marchen
+Masaram_Gondi Medefaidrin Meetei_Mayek Mende_Kikakui Meroitic_Cursive
+Meroitic_Hieroglyphs Miao Modi Mongolian Mro Multani Myanmar Nabataean Nag_Mundari
+Nandinagari New_Tai_Lue Newa Nko Nushu Nyiakeng_Puachue_Hmong Ogham Ol_Chiki Ol_Onal
+Old_Hungarian Old_Italic Old_North_Arabian Old_Permic Old_Persian Old_Sogdian
+Old_South_Arabian Old_Turkic Old_Uyghur Oriya Osage Osmanya Pahawh_Hmong Palmyrene
+Pau_Cin_Hau Phags_Pa Phoenician Psalter_Pahlavi Rejang Runic Samaritan Saurashtra
+Sharada Shavian Sidetic Siddham SignWriting Sinhala Sogdian Sora_Sompeng Soyombo Sundanese
+Sunuwar Syloti_Nagri Syriac Tagalog Tagbanwa Tai_Le Tai_Tham Tai_Viet Tai_Yo Takri Tamil
+Tangsa Tangut Telugu Thaana Thai Tibetan Tifinagh Tirhuta Todhri Tolong_Siki Toto Tulu_Tigalari
+Ugaritic Vai Vithkuqi Wancho Warang_Citi Yezidi Yi Zanabazar_Square
+""".split()
+
+BINARY_PROPERTIES = [
+    "Alphabetic", "Lowercase", "Uppercase", "White_Space",
+    "ID_Start", "ID_Continue", "XID_Start", "XID_Continue",
+]
+
+# A char first assigned by each version of Unicode.
+VERSION_PROBES = [
+    ("17.0.0", 0x10940), ("16.0.0", 0x1CC00), ("15.1.0", 0x2EBF0), ("15.0.0", 0x1E030),
+]
+
+# Every char, surrogates excluded.
+CODEPOINTS = [cp for cp in range(0x110000) if not 0xD800 <= cp <= 0xDFFF]
+TEXT = "".join(map(chr, CODEPOINTS))
+
+
+def ranges(pattern):
+    """The ranges of chars matching the single-char pattern."""
+    found = []
+    for m in regex.finditer(pattern + "+", TEXT):
+        lo, hi = CODEPOINTS[m.start()], CODEPOINTS[m.end() - 1]
+        found.append((lo, hi))
+    return found
+
+
+def unicode_version():
+    for version, cp in VERSION_PROBES:
+        if not regex.match(r"\p{Cn}", chr(cp)):
+            return version
+    raise SystemExit("the regex package is too old")
+
+
+def table(name, found):
+    items = ["('\\u{%X}', '\\u{%X}')" % r for r in found]
+    lines = [", ".join(items[i:i + 4]) + "," for i in range(0, len(items), 4)]
+    body = "\n".join("    " + line for line in lines)
+    return "const %s: Ranges = &[\n%s\n];\n" % (name, body)
+
+
+def ident(name):
+    return regex.sub(r"[^A-Za-z0-9]", "_", name).upper()
+
+
+def main():
+    out = [
+        "//! Unicode range tables, generated by generate.py : do not edit.",
+        "#![cfg_attr(rustfmt, rustfmt_skip)]",
+        "",
+        "pub const UNICODE_VERSION: &str = \"%s\";" % unicode_version(),
+        "",
+        "/// The sorted, disjoint, inclusive ranges of a property.",
+        "pub type Ranges = &'static [(char, char)];",
+        "",
+    ]
+
+    out.append("pub const GENERAL_CATEGORIES: &[(&str, &str, Ranges)] = &[")
+    out += ["    (\"%s\", \"%s\", GC_%s)," % (short, long, ident(short)) for short, long in GENERAL_CATEGORIES]
+    out.append("];\n")
+
+    scripts = [(name, ranges(r"\p{Script=%s}" % name)) for name in SCRIPTS]
+    scripts = [(name, found) for name, found in scripts if found]
+
+    out.append("pub const SCRIPTS: &[(&str, Ranges)] = &[")
+    out += ["    (\"%s\", SC_%s)," % (name, ident(name)) for name, _ in scripts]
+    out.append("];\n")
+
+    out.append("pub const BINARY_PROPERTIES: &[(&str, Ranges)] = &[")
+    out += ["    (\"%s\", BP_%s)," % (name, ident(name)) for name in BINARY_PROPERTIES]
+    out.append("];\n")
+
+    for short, _ in GENERAL_CATEGORIES:
+        out.append(table("GC_" + ident(short), ranges(r"\p{gc=%s}" % short)))
+
+    for name, found in scripts:
+        out.append(table("SC_" + ident(name), found))
+
+    for name in BINARY_PROPERTIES:
+        out.append(table("BP_" + ident(name), ranges(r"\p{%s}" % name)))
+
+    print("\n".join(out))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,168 @@
+//! Unicode property classes.
+//!
+//! The range tables are generated offline by `generate.py`, names are
+//! matched loosely, ignoring case, spaces, hyphens and underscores.
+use super::CharSet;
+
+mod tables;
+
+pub use tables::UNICODE_VERSION;
+
+/// The general category groups, and their members.
+const GROUPS: &[(&str, &str, &[&str])] = &[
+    ("L", "Letter", &["Lu", "Ll", "Lt", "Lm", "Lo"]),
+    ("LC", "Cased_Letter", &["Lu", "Ll", "Lt"]),
+    ("M", "Mark", &["Mn", "Mc", "Me"]),
+    ("N", "Number", &["Nd", "Nl", "No"]),
+    (
+        "P",
+        "Punctuation",
+        &["Pc", "Pd", "Ps", "Pe", "Pi", "Pf", "Po"],
+    ),
+    ("S", "Symbol", &["Sm", "Sc", "Sk", "So"]),
+    ("Z", "Separator", &["Zs", "Zl", "Zp"]),
+    ("C", "Other", &["Cc", "Cf", "Co", "Cn"]),
+];
+
+fn loose_eq(lhs: &str, rhs: &str) -> bool {
+    let normalize = |name: &str| {
+        name.chars()
+            .filter(|ch| !matches!(ch, '_' | '-' | ' '))
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+
+    normalize(lhs) == normalize(rhs)
+}
+
+fn from_table(table: &[(char, char)]) -> CharSet {
+    CharSet::from_ranges(table.iter().map(|&(lo, hi)| lo..=hi))
+}
+
+impl CharSet {
+    /// The chars of a general category, by its short (Lu) or long
+    /// (Uppercase_Letter) name, the groups (L, LC, M, ...) included.
+    pub fn general_category(name: &str) -> Option<Self> {
+        if let Some((_, _, table)) = tables::GENERAL_CATEGORIES
+            .iter()
+            .find(|(short, long, _)| loose_eq(short, name) || loose_eq(long, name))
+        {
+            return Some(from_table(table));
+        }
+
+        GROUPS
+            .iter()
+            .find(|(short, long, _)| loose_eq(short, name) || loose_eq(long, name))
+            .map(|(_, _, members)| {
+                members
+                    .iter()
+                    .filter_map(|member| Self::general_category(member))
+                    .fold(Self::empty(), |acc, set| acc | set)
+            })
+    }
+
+    /// The chars of a script, by its name (Latin, Greek, ...).
+    pub fn script(name: &str) -> Option<Self> {
+        tables::SCRIPTS
+            .iter()
+            .find(|(script, _)| loose_eq(script, name))
+            .map(|(_, table)| from_table(table))
+    }
+
+    /// The chars having a binary property (White_Space, XID_Start, ...).
+    pub fn binary_property(name: &str) -> Option<Self> {
+        tables::BINARY_PROPERTIES
+            .iter()
+            .find(|(property, _)| loose_eq(property, name))
+            .map(|(_, table)| from_table(table))
+    }
+
+    /// The chars which may start an identifier, as defined by UAX #31.
+    pub fn xid_start() -> Self {
+        Self::binary_property("XID_Start").unwrap()
+    }
+
+    /// The chars which may continue an identifier, as defined by UAX #31.
+    pub fn xid_continue() -> Self {
+        Self::binary_property("XID_Continue").unwrap()
+    }
+
+    /// Resolve a property expression, as written in `\p{..}`.
+    ///
+    /// It is either a bare name (Lu, Greek, White_Space), looked up among
+    /// the general categories, the binary properties, then the scripts,
+    /// or a `key=value` pair, the keys being gc, General_Category, sc
+    /// and Script.
+    pub fn unicode_property(expr: &str) -> Option<Self> {
+        match expr.split_once(['=', ':']) {
+            Some((key, value)) if loose_eq(key, "gc") || loose_eq(key, "General_Category") => {
+                Self::general_category(value.trim())
+            }
+            Some((key, value)) if loose_eq(key, "sc") || loose_eq(key, "Script") => {
+                Self::script(value.trim())
+            }
+            Some(_) => None,
+            None => Self::general_category(expr)
+                .or_else(|| Self::binary_property(expr))
+                .or_else(|| Self::script(expr)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dfa::CharSet;
+
+    #[test]
+    fn test_001_general_categories() {
+        let lu = CharSet::general_category("Lu").unwrap();
+        assert!(lu.contains(&'A'));
+        assert!(lu.contains(&'É'));
+        assert!(!lu.contains(&'a'));
+
+        assert_eq!(
+            CharSet::general_category("uppercase letter"),
+            Some(lu.clone())
+        );
+
+        let letters = CharSet::general_category("L").unwrap();
+        assert!(letters.contains(&'a'));
+        assert!(letters.contains(&'漢'));
+        assert_eq!(letters.clone() & lu.clone(), lu);
+
+        let nd = CharSet::unicode_property("gc=Nd").unwrap();
+        assert!(nd.contains(&'7'));
+        assert!(nd.contains(&'٣'));
+        assert!(!nd.contains(&'Ⅷ'));
+
+        assert!(CharSet::general_category("Xx").is_none());
+    }
+
+    #[test]
+    fn test_002_scripts() {
+        let greek = CharSet::unicode_property("Script=Greek").unwrap();
+        assert!(greek.contains(&'λ'));
+        assert!(!greek.contains(&'l'));
+
+        let cyrillic = CharSet::unicode_property("Cyrillic").unwrap();
+        assert!(cyrillic.contains(&'ж'));
+        assert!((greek & cyrillic).is_empty());
+    }
+
+    #[test]
+    fn test_003_binary_properties() {
+        let ws = CharSet::binary_property("White_Space").unwrap();
+        assert!(ws.contains(&' '));
+        assert!(ws.contains(&'\u{A0}'));
+        assert!(!ws.contains(&'\u{1C}'));
+
+        let start = CharSet::xid_start();
+        let cont = CharSet::xid_continue();
+        assert!(start.contains(&'é'));
+        assert!(!start.contains(&'_'));
+        assert!(!start.contains(&'1'));
+        assert!(cont.contains(&'_'));
+        assert!(cont.contains(&'1'));
+        assert!((start.clone() - cont).is_empty());
+    }
+}
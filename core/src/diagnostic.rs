@@ -0,0 +1,370 @@
+//! Rendering of errors as diagnostics.
+//!
+//! A [Diagnostic] is built from a [YalpError], and rendered by a
+//! [Renderer] against the source the error comes from:
+//!
+//! ```text
+//! error[E0004]: unexpected +
+//!  --> main:1:5
+//!   |
+//! 1 | 1 + + 2
+//!   |     ^ unexpected +
+//!   |
+//!   = note: expected one of: n, (
+//! ```
+use std::fmt::{Display, Write as _};
+
+use crate::{source::SourceFile, span::Span, ErrorKind, YalpError};
+
+/// An error, ready to be rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The stable code of the error, see [ErrorKind::code].
+    pub code: &'static str,
+    pub message: String,
+    pub span: Option<Span>,
+    /// The label under the underlined span.
+    pub label: Option<String>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            span: None,
+            label: None,
+            notes: vec![],
+            help: vec![],
+        }
+    }
+
+    pub fn with_span(mut self, span: Option<Span>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn with_label(mut self, label: impl ToString) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl ToString) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl ToString) -> Self {
+        self.help.push(help.to_string());
+        self
+    }
+}
+
+impl<C: Display> From<&YalpError<C>> for Diagnostic {
+    fn from(err: &YalpError<C>) -> Self {
+        let kind = err.kind();
+        let diagnostic = match kind {
+            ErrorKind::UnexpectedSymbol { expecting, got } => {
+                let diagnostic = Diagnostic::new(kind.code(), format!("unexpected {got}"))
+                    .with_label(format!("unexpected {got}"));
+
+                if expecting.is_empty() {
+                    diagnostic
+                } else {
+                    diagnostic.with_note(format!("expected one of: {expecting}"))
+                }
+            }
+            ErrorKind::UnexpectedEndOfStream => {
+                Diagnostic::new(kind.code(), kind).with_label("the input ends here")
+            }
+            _ => Diagnostic::new(kind.code(), kind),
+        };
+
+        diagnostic.with_span(err.span())
+    }
+}
+
+/// The output of a [Renderer].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Plain,
+    /// Plain, coloured with ANSI escape sequences.
+    Ansi,
+    /// A JSON object per diagnostic, for tools.
+    Json,
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders diagnostics, with the lines of the source they point to.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Renderer {
+    format: Format,
+}
+
+impl Renderer {
+    pub fn new(format: Format) -> Self {
+        Self { format }
+    }
+
+    pub fn plain() -> Self {
+        Self::new(Format::Plain)
+    }
+
+    pub fn ansi() -> Self {
+        Self::new(Format::Ansi)
+    }
+
+    pub fn json() -> Self {
+        Self::new(Format::Json)
+    }
+
+    /// Render the error, located in the source.
+    pub fn render_error<C: Display>(&self, err: &YalpError<C>, source: &SourceFile) -> String {
+        self.render(&Diagnostic::from(err), Some(source))
+    }
+
+    /// Render the diagnostic, with the source it points to if any.
+    pub fn render(&self, diagnostic: &Diagnostic, source: Option<&SourceFile>) -> String {
+        match self.format {
+            Format::Plain => self.text(diagnostic, source, false),
+            Format::Ansi => self.text(diagnostic, source, true),
+            Format::Json => json(diagnostic, source),
+        }
+    }
+
+    fn text(&self, diagnostic: &Diagnostic, source: Option<&SourceFile>, ansi: bool) -> String {
+        let paint = |style: &str, text: &str| {
+            if ansi {
+                format!("{style}{text}{RESET}")
+            } else {
+                text.to_string()
+            }
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{}{}",
+            paint(RED, &format!("error[{}]", diagnostic.code)),
+            paint(BOLD, &format!(": {}", diagnostic.message))
+        );
+
+        let located = diagnostic.span.and_then(|span| {
+            let source = source?;
+            let text = source.line(span.from.line)?;
+            Some((span, source, text))
+        });
+
+        // The gutter is as wide as the line number.
+        let width = located.map_or(1, |(span, _, _)| span.from.line.to_string().len());
+        let gutter = paint(BLUE, &format!("{:width$} |", ""));
+
+        if let Some((span, source, text)) = located {
+            let _ = writeln!(
+                out,
+                "{:width$}{} {}:{}:{}",
+                "",
+                paint(BLUE, "-->"),
+                source.name(),
+                span.from.line,
+                span.from.column + 1
+            );
+
+            // The underline stops at the end of the first line.
+            let columns = text.chars().count();
+            let start = span.from.column.min(columns);
+            let end = if span.to.line == span.from.line {
+                span.to.column.min(columns)
+            } else {
+                columns
+            };
+            let carets = "^".repeat(end.saturating_sub(start).max(1));
+
+            let mut underline = format!("{:start$}{}", "", paint(RED, &carets));
+
+            if let Some(label) = &diagnostic.label {
+                underline.push(' ');
+                underline.push_str(&paint(RED, label));
+            }
+
+            let _ = writeln!(out, "{gutter}");
+            let _ = writeln!(
+                out,
+                "{} {text}",
+                paint(BLUE, &format!("{:width$} |", span.from.line))
+            );
+            let _ = writeln!(out, "{gutter} {underline}");
+        }
+
+        let remarks = diagnostic
+            .notes
+            .iter()
+            .map(|note| ("note", note))
+            .chain(diagnostic.help.iter().map(|help| ("help", help)))
+            .collect::<Vec<_>>();
+
+        if !remarks.is_empty() && located.is_some() {
+            let _ = writeln!(out, "{gutter}");
+        }
+
+        for (title, remark) in remarks {
+            let _ = writeln!(
+                out,
+                "{:width$} {} {}: {remark}",
+                "",
+                paint(BLUE, "="),
+                paint(BOLD, title)
+            );
+        }
+
+        out
+    }
+}
+
+fn json(diagnostic: &Diagnostic, source: Option<&SourceFile>) -> String {
+    let mut out = format!(
+        "{{\"severity\":\"error\",\"code\":{},\"message\":{}",
+        quote(diagnostic.code),
+        quote(&diagnostic.message)
+    );
+
+    if let Some(source) = source {
+        let _ = write!(out, ",\"file\":{}", quote(source.name()));
+    }
+
+    if let Some(span) = diagnostic.span {
+        let cursor = |cursor: crate::span::Cursor| {
+            format!(
+                "{{\"line\":{},\"column\":{},\"offset\":{}}}",
+                cursor.line, cursor.column, cursor.offset
+            )
+        };
+
+        let _ = write!(
+            out,
+            ",\"span\":{{\"start\":{},\"end\":{}}}",
+            cursor(span.from),
+            cursor(span.to)
+        );
+    }
+
+    if let Some(label) = &diagnostic.label {
+        let _ = write!(out, ",\"label\":{}", quote(label));
+    }
+
+    let list = |items: &[String]| {
+        items
+            .iter()
+            .map(|item| quote(item))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    let _ = write!(
+        out,
+        ",\"notes\":[{}],\"help\":[{}]}}",
+        list(&diagnostic.notes),
+        list(&diagnostic.help)
+    );
+
+    out
+}
+
+/// The JSON string of the text.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                let _ = write!(quoted, "\\u{:04x}", ch as u32);
+            }
+            ch => quoted.push(ch),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        source::SourceFile,
+        span::{Cursor, Span},
+        ErrorKind, NoCustomError, YalpError,
+    };
+
+    use super::{Diagnostic, Renderer};
+
+    fn unexpected() -> YalpError<NoCustomError> {
+        YalpError::new(
+            ErrorKind::unexpected_symbol("+", ["n", "("]),
+            Some(Span::new(Cursor::new(1, 4, 4), Cursor::new(1, 5, 5))),
+        )
+    }
+
+    #[test]
+    fn test_plain() {
+        let source = SourceFile::new("main", "1 + + 2\n");
+        let rendered = Renderer::plain().render(
+            &Diagnostic::from(&unexpected()).with_help("remove the second +"),
+            Some(&source),
+        );
+
+        assert_eq!(
+            rendered,
+            "error[E0004]: unexpected +\n \
+             --> main:1:5\n  \
+               |\n\
+             1 | 1 + + 2\n  \
+               |     ^ unexpected +\n  \
+               |\n  \
+               = note: expected one of: n, (\n  \
+               = help: remove the second +\n"
+        );
+    }
+
+    #[test]
+    fn test_ansi() {
+        let source = SourceFile::new("main", "1 + + 2\n");
+        let rendered = Renderer::ansi().render_error(&unexpected(), &source);
+
+        assert!(rendered.starts_with("\x1b[1;31merror[E0004]\x1b[0m"));
+        assert!(rendered.contains("\x1b[1;31m^\x1b[0m"));
+    }
+
+    #[test]
+    fn test_json() {
+        let source = SourceFile::new("main", "1 + + 2\n");
+        let rendered = Renderer::json().render_error(&unexpected(), &source);
+
+        assert_eq!(
+            rendered,
+            "{\"severity\":\"error\",\"code\":\"E0004\",\"message\":\"unexpected +\",\
+             \"file\":\"main\",\"span\":{\"start\":{\"line\":1,\"column\":4,\"offset\":4},\
+             \"end\":{\"line\":1,\"column\":5,\"offset\":5}},\"label\":\"unexpected +\",\
+             \"notes\":[\"expected one of: n, (\"],\"help\":[]}"
+        );
+    }
+
+    #[test]
+    fn test_without_span() {
+        let err: YalpError<NoCustomError> = ErrorKind::InvalidRegex("a\"b".into()).into();
+        let rendered = Renderer::plain().render(&Diagnostic::from(&err), None);
+
+        assert_eq!(rendered, "error[E0012]: invalid regular expression: a\"b\n");
+    }
+}
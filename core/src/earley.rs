@@ -0,0 +1,461 @@
+//! Earley parser enumerating every derivation of an input.
+//!
+//! It is a debugging tool for grammar authors: it accepts ambiguous
+//! grammars, and shows where a grammar is ambiguous on real inputs.
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use itertools::Itertools as _;
+
+use crate::{
+    lexer::traits::Lexer, parser::traits::Ast, syntax::Syntax, token::traits::Token as _,
+    ErrorKind, YalpError, YalpResult,
+};
+
+/// An Earley item : rule, position of the dot, and origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    rule: usize,
+    dot: usize,
+    origin: usize,
+}
+
+struct Grammar<'a> {
+    rules: Vec<(&'a str, Vec<&'a str>)>,
+    nullables: HashSet<&'a str>,
+}
+
+impl<'a> Grammar<'a> {
+    fn new(syntax: &'a Syntax<'_>) -> Self {
+        let rules: Vec<(&str, Vec<&str>)> = syntax
+            .as_ref()
+            .iter()
+            .map(|rule| {
+                (
+                    rule.lhs.as_ref(),
+                    rule.rhs.as_ref().iter().map(|sym| sym.as_ref()).collect(),
+                )
+            })
+            .collect();
+
+        let mut nullables = HashSet::<&str>::default();
+
+        loop {
+            let before = nullables.len();
+
+            for (lhs, rhs) in rules.iter() {
+                if rhs.iter().all(|sym| nullables.contains(sym)) {
+                    nullables.insert(*lhs);
+                }
+            }
+
+            if nullables.len() == before {
+                break;
+            }
+        }
+
+        Self { rules, nullables }
+    }
+
+    fn is_terminal(&self, symbol: &str) -> bool {
+        !self.rules.iter().any(|(lhs, _)| *lhs == symbol)
+    }
+
+    fn next_symbol(&self, item: &Item) -> Option<&'a str> {
+        self.rules[item.rule].1.get(item.dot).copied()
+    }
+
+    fn iter_rules_by_symbol<'b>(&'b self, symbol: &'b str) -> impl Iterator<Item = usize> + 'b {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(move |(_, (lhs, _))| *lhs == symbol)
+            .map(|(id, _)| id)
+    }
+}
+
+/// A symbol deriving tokens[start..end].
+type Derivation<'syntax> = (&'syntax str, usize, usize);
+
+/// The chart of an Earley parse, one item set per position.
+struct Chart<'a, 'syntax, Tok, A> {
+    grammar: &'a Grammar<'syntax>,
+    tokens: &'a [Tok],
+    /// Completed items, indexed by (lhs, origin) to (end, rule).
+    completed: HashMap<(&'syntax str, usize), Vec<(usize, usize)>>,
+    /// The most trees to enumerate for any derivation.
+    max: usize,
+    /// The trees of each derivation, once enumerated.
+    memo: HashMap<Derivation<'syntax>, Rc<[A]>>,
+    /// The derivations being enumerated.
+    path: Vec<Derivation<'syntax>>,
+}
+
+impl<'a, 'syntax, Tok, A> Chart<'a, 'syntax, Tok, A>
+where
+    Tok: crate::token::traits::Token,
+    A: Ast + From<Tok> + Clone,
+{
+    /// Enumerate at most max trees deriving the symbol over tokens[start..end].
+    ///
+    /// Returns false if a cyclic derivation (A -> A) was cut, in which
+    /// case the trees are not memoized, as they depend on the path.
+    fn trees(&mut self, derivation: Derivation<'syntax>) -> (Rc<[A]>, bool) {
+        if let Some(trees) = self.memo.get(&derivation) {
+            return (trees.clone(), true);
+        }
+
+        // Cyclic derivations would never end.
+        if self.path.contains(&derivation) {
+            return (Rc::from([]), false);
+        }
+
+        let (symbol, start, end) = derivation;
+        let rules = self
+            .completed
+            .get(&(symbol, start))
+            .into_iter()
+            .flatten()
+            .filter(|(to, _)| *to == end)
+            .map(|(_, rule)| *rule)
+            .unique()
+            .collect::<Vec<_>>();
+
+        self.path.push(derivation);
+
+        let mut trees = vec![];
+        let mut complete = true;
+
+        for rule in rules {
+            if trees.len() >= self.max {
+                break;
+            }
+
+            complete &= self.expand(rule, 0, start, end, &mut vec![], &mut trees);
+        }
+
+        self.path.pop();
+
+        let trees = Rc::<[A]>::from(trees);
+
+        if complete {
+            self.memo.insert(derivation, trees.clone());
+        }
+
+        (trees, complete)
+    }
+
+    /// Enumerate the children of the rule from the dot, until max trees are found.
+    fn expand(
+        &mut self,
+        rule: usize,
+        dot: usize,
+        pos: usize,
+        end: usize,
+        children: &mut Vec<A>,
+        trees: &mut Vec<A>,
+    ) -> bool {
+        let grammar = self.grammar;
+        let (lhs, rhs) = &grammar.rules[rule];
+
+        let Some(&symbol) = rhs.get(dot) else {
+            if pos == end {
+                trees.push(A::reduce(lhs, children.clone().into_iter()));
+            }
+            return true;
+        };
+
+        if grammar.is_terminal(symbol) {
+            if pos < end && self.tokens[pos].symbol_id() == symbol {
+                children.push(A::from(self.tokens[pos].clone()));
+                let complete = self.expand(rule, dot + 1, pos + 1, end, children, trees);
+                children.pop();
+                return complete;
+            }
+            return true;
+        }
+
+        // The last symbol must reach the end.
+        let last = dot + 1 == rhs.len();
+
+        let spans = self
+            .completed
+            .get(&(symbol, pos))
+            .into_iter()
+            .flatten()
+            .map(|(to, _)| *to)
+            .filter(|to| if last { *to == end } else { *to <= end })
+            .unique()
+            .collect::<Vec<_>>();
+
+        let mut complete = true;
+
+        for to in spans {
+            let (subtrees, sub_complete) = self.trees((symbol, pos, to));
+            complete &= sub_complete;
+
+            for subtree in subtrees.iter() {
+                if trees.len() >= self.max {
+                    return complete;
+                }
+
+                children.push(subtree.clone());
+                complete &= self.expand(rule, dot + 1, to, end, children, trees);
+                children.pop();
+            }
+        }
+
+        complete
+    }
+}
+
+/// Parse the stream, and returns at most max derivations of it.
+///
+/// The root symbol is the lhs of the root rule (#0).
+pub fn parse_all<A, L, Error>(
+    syntax: &Syntax,
+    lexer: &mut L,
+    max: usize,
+) -> YalpResult<Vec<A>, Error>
+where
+    A: Ast + From<L::Token> + Clone,
+    L: Lexer<Error>,
+{
+    let tokens = lexer.collect::<YalpResult<Vec<_>, Error>>()?;
+    let grammar = Grammar::new(syntax);
+    let root = grammar
+        .rules
+        .first()
+        .map(|(lhs, _)| *lhs)
+        .unwrap_or_default();
+
+    let mut sets: Vec<Vec<Item>> = vec![vec![]; tokens.len() + 1];
+
+    sets[0].extend(grammar.iter_rules_by_symbol(root).map(|rule| Item {
+        rule,
+        dot: 0,
+        origin: 0,
+    }));
+
+    let push = |sets: &mut Vec<Vec<Item>>, at: usize, item: Item| {
+        if !sets[at].contains(&item) {
+            sets[at].push(item);
+        }
+    };
+
+    for pos in 0..=tokens.len() {
+        let mut i = 0;
+
+        while i < sets[pos].len() {
+            let item = sets[pos][i];
+            i += 1;
+
+            match grammar.next_symbol(&item) {
+                // Complete
+                None => {
+                    let (lhs, _) = grammar.rules[item.rule];
+                    let parents = sets[item.origin]
+                        .iter()
+                        .filter(|parent| grammar.next_symbol(parent) == Some(lhs))
+                        .copied()
+                        .collect::<Vec<_>>();
+
+                    for parent in parents {
+                        push(
+                            &mut sets,
+                            pos,
+                            Item {
+                                dot: parent.dot + 1,
+                                ..parent
+                            },
+                        );
+                    }
+                }
+                // Scan
+                Some(symbol) if grammar.is_terminal(symbol) => {
+                    if tokens
+                        .get(pos)
+                        .map(|tok| tok.symbol_id() == symbol)
+                        .unwrap_or(false)
+                    {
+                        push(
+                            &mut sets,
+                            pos + 1,
+                            Item {
+                                dot: item.dot + 1,
+                                ..item
+                            },
+                        );
+                    }
+                }
+                // Predict
+                Some(symbol) => {
+                    for rule in grammar.iter_rules_by_symbol(symbol) {
+                        push(
+                            &mut sets,
+                            pos,
+                            Item {
+                                rule,
+                                dot: 0,
+                                origin: pos,
+                            },
+                        );
+                    }
+
+                    if grammar.nullables.contains(symbol) {
+                        push(
+                            &mut sets,
+                            pos,
+                            Item {
+                                dot: item.dot + 1,
+                                ..item
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        // The parse is stuck, report the furthest error.
+        if pos < tokens.len() && sets[pos + 1].is_empty() {
+            let expected = sets[pos]
+                .iter()
+                .flat_map(|item| grammar.next_symbol(item))
+                .filter(|sym| grammar.is_terminal(sym))
+                .unique()
+                .map(|sym| syntax.display_name(sym).unwrap_or(sym));
+
+            let got = tokens[pos].symbol_id();
+
+            return Err(YalpError::new(
+                ErrorKind::unexpected_symbol(syntax.display_name(got).unwrap_or(got), expected),
+                tokens[pos].span(),
+            ));
+        }
+    }
+
+    let mut completed = HashMap::<(&str, usize), Vec<(usize, usize)>>::default();
+
+    for (end, set) in sets.iter().enumerate() {
+        for item in set
+            .iter()
+            .filter(|item| grammar.next_symbol(item).is_none())
+        {
+            completed
+                .entry((grammar.rules[item.rule].0, item.origin))
+                .or_default()
+                .push((end, item.rule));
+        }
+    }
+
+    let mut chart = Chart::<_, A> {
+        grammar: &grammar,
+        tokens: &tokens,
+        completed,
+        max,
+        memo: HashMap::default(),
+        path: vec![],
+    };
+
+    let trees = chart.trees((root, 0, tokens.len())).0.to_vec();
+
+    if trees.is_empty() {
+        return Err(YalpError::new(ErrorKind::UnexpectedEndOfStream, None));
+    }
+
+    Ok(trees)
+}
+
+/// Render the trees side by side, with their Debug representation.
+pub fn render_side_by_side<T: std::fmt::Debug>(trees: &[T]) -> String {
+    let columns = trees
+        .iter()
+        .enumerate()
+        .map(|(i, tree)| {
+            format!("#{}\n{:#?}", i, tree)
+                .lines()
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let widths = columns
+        .iter()
+        .map(|lines| lines.iter().map(|l| l.chars().count()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+
+    let height = columns.iter().map(Vec::len).max().unwrap_or(0);
+
+    (0..height)
+        .map(|row| {
+            columns
+                .iter()
+                .zip(widths.iter())
+                .map(|(lines, width)| {
+                    format!(
+                        "{:<width$}",
+                        lines.get(row).map(String::as_str).unwrap_or("")
+                    )
+                })
+                .join(" | ")
+                .trim_end()
+                .to_owned()
+        })
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ast::AstNode,
+        fixtures::{FIXTURE_AMBIGUOUS_GRAMMAR, FIXTURE_CATALAN_GRAMMAR},
+        lexer::fixtures::lexer_fixture_lr1,
+        NoCustomError,
+    };
+
+    use super::{parse_all, render_side_by_side};
+
+    #[test]
+    fn test_001_parse_all_ambiguous() {
+        let mut lexer = lexer_fixture_lr1("n + n + n".chars());
+        let trees =
+            parse_all::<AstNode, _, NoCustomError>(&FIXTURE_AMBIGUOUS_GRAMMAR, &mut lexer, 10)
+                .unwrap();
+
+        // (n + n) + n, and n + (n + n)
+        assert_eq!(trees.len(), 2);
+        println!("{}", render_side_by_side(&trees));
+    }
+
+    #[test]
+    fn test_002_parse_all_capped() {
+        let mut lexer = lexer_fixture_lr1("n + n + n + n".chars());
+        let trees =
+            parse_all::<AstNode, _, NoCustomError>(&FIXTURE_AMBIGUOUS_GRAMMAR, &mut lexer, 3)
+                .unwrap();
+
+        assert_eq!(trees.len(), 3);
+    }
+
+    #[test]
+    fn test_004_parse_all_highly_ambiguous() {
+        // 40 tokens have about 10^21 derivations.
+        let source = vec!["n"; 40].join(" ");
+        let mut lexer = lexer_fixture_lr1(source.chars());
+        let trees = parse_all::<AstNode, _, NoCustomError>(&FIXTURE_CATALAN_GRAMMAR, &mut lexer, 5)
+            .unwrap();
+
+        assert_eq!(trees.len(), 5);
+    }
+
+    #[test]
+    fn test_003_parse_all_rejected() {
+        let mut lexer = lexer_fixture_lr1("n + + n".chars());
+        assert!(
+            parse_all::<AstNode, _, NoCustomError>(&FIXTURE_AMBIGUOUS_GRAMMAR, &mut lexer, 3)
+                .is_err()
+        );
+    }
+}
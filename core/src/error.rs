@@ -1,14 +1,30 @@
 use itertools::Itertools as _;
 use thiserror::Error;
 
-use crate::{OwnedSymbol, Span};
+use crate::Span;
 
 #[derive(Debug, Clone, Copy)]
 pub struct NoCustomError;
 
+impl std::fmt::Display for NoCustomError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("no custom error")
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExpectedSymbols(Vec<String>);
 
+impl ExpectedSymbols {
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
 impl std::fmt::Display for ExpectedSymbols {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.iter().join(", ").fmt(f)
@@ -39,13 +55,61 @@ pub enum ErrorKind<C> {
     #[error("a shift-reduce conflict has occurred for symbol {symbol} [{conflict:?}], state={state}")]
     ShiftReduceConflict{
         state: usize,
-        symbol: OwnedSymbol,
+        symbol: String,
         conflict: [crate::lr::Action; 2],
     },
 
     #[error("the algorithm is not supported")]
     UnsupportedAlgorithm,
 
+    #[error("missing reducer for rule {0}")]
+    MissingReducer(usize),
+
+    #[error("the reducer of rule {rule} takes {got} values, expecting {expected}")]
+    ReducerArityMismatch {
+        rule: usize,
+        expected: usize,
+        got: usize
+    },
+
+    #[error("the reducer of rule {rule} types symbol {symbol} as {got}, expecting {expected}")]
+    ReducerTypeMismatch {
+        rule: usize,
+        symbol: String,
+        expected: &'static str,
+        got: &'static str
+    },
+
+    #[error("the parsed value is not a {0}")]
+    UnexpectedValueType(&'static str),
+
+    #[error("invalid regular expression: {0}")]
+    InvalidRegex(String),
+
+    #[error("unknown lexer mode {0}")]
+    UnknownLexerMode(String),
+
+    #[error("i/o error: {0}")]
+    Io(String),
+
+    #[error("invalid UTF-8 sequence at byte {0}")]
+    InvalidUtf8(usize),
+
+    #[error("unterminated {0} token")]
+    UnterminatedToken(String),
+
+    #[error("the dedent to column {0} does not match any outer indentation level")]
+    InconsistentDedent(usize),
+
+    #[error("the repetition bound {bound} exceeds the limit of {limit}")]
+    RepetitionOverflow {
+        bound: String,
+        limit: usize
+    },
+
+    #[error("no token has been declared")]
+    NoTokenDeclared,
+
     #[error("{0}")]
     Other(C)
 }
@@ -63,12 +127,42 @@ impl<C> ErrorKind<C> {
     pub fn unknown_symbol(got: &str) -> Self {
         Self::UnknownSymbol(got.to_string())
     }
+
+    /// The stable code of the error, reported by diagnostics.
+    ///
+    /// Codes are never reused: a removed variant retires its code, and
+    /// a new variant takes the next free one.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Other(_) => "E0000",
+            Self::UnknownRule(_) => "E0001",
+            Self::DuplicatedSymbolId(_) => "E0002",
+            Self::UnknownSymbol(_) => "E0003",
+            Self::UnexpectedSymbol { .. } => "E0004",
+            Self::UnexpectedEndOfStream => "E0005",
+            Self::ShiftReduceConflict { .. } => "E0006",
+            Self::UnsupportedAlgorithm => "E0007",
+            Self::MissingReducer(_) => "E0008",
+            Self::ReducerArityMismatch { .. } => "E0009",
+            Self::ReducerTypeMismatch { .. } => "E0010",
+            Self::UnexpectedValueType(_) => "E0011",
+            Self::InvalidRegex(_) => "E0012",
+            Self::UnknownLexerMode(_) => "E0013",
+            Self::Io(_) => "E0014",
+            Self::InvalidUtf8(_) => "E0015",
+            Self::UnterminatedToken(_) => "E0016",
+            Self::InconsistentDedent(_) => "E0017",
+            Self::RepetitionOverflow { .. } => "E0018",
+            Self::NoTokenDeclared => "E0019",
+        }
+    }
 }
 
 #[derive(Error, Debug, Clone)]
+#[error("{kind}")]
 pub struct YalpError<C> {
-    /// Kind of error
-    kind: ErrorKind<C>,
+    /// Kind of error, boxed to keep results small.
+    kind: Box<ErrorKind<C>>,
     /// Location of the error in a stream.
     pub(crate) span: Option<Span>
 }
@@ -76,7 +170,7 @@ pub struct YalpError<C> {
 impl<C> YalpError<C> {
     pub fn new(kind: impl Into<ErrorKind<C>>, span: Option<Span>) -> Self {
         Self {
-            kind: kind.into(),
+            kind: Box::new(kind.into()),
             span
         }
     }
@@ -85,7 +179,7 @@ impl<C> YalpError<C> {
 impl<C> From<ErrorKind<C>> for YalpError<C> {
     fn from(kind: ErrorKind<C>) -> Self {
         Self {
-            kind,
+            kind: Box::new(kind),
             span: None
         }
     }
@@ -97,6 +191,6 @@ impl<C> YalpError<C> {
     }
 
     pub fn span(&self) -> Option<Span> {
-        self.span.clone()
+        self.span
     }
 }
\ No newline at end of file
@@ -1,7 +1,6 @@
 use std::{collections::HashSet, hash::Hash};
 
 use itertools::Itertools;
-use pb_bnf::symbol::Symbol;
 
 use crate::{
     array::Array,
@@ -15,20 +14,16 @@ impl<'syntax> PrepRule<'syntax> {
         Item::new(self, position)
     }
 
-    // Returns the item sets that follows this symbol.
+    // Returns the items that follows this symbol.
     pub fn follow<'gen>(
         &'gen self,
         symbol: PrepSymbol<'syntax>,
-    ) -> impl Iterator<Item = ItemCore<'syntax, 'gen>> + 'gen
-    where
-        'gen: 'syntax,
-    {
+    ) -> impl Iterator<Item = ItemCore<'syntax, 'gen>> + 'gen {
         self.rhs
             .iter()
             .enumerate()
             .filter(move |(_, &term)| term == symbol)
             .map(|(pos, _)| self.at::<0>(pos + 1).unwrap())
-            .filter(|i| i.is_exhausted() || i.is_symbol_terminal())
     }
 }
 
@@ -44,16 +39,14 @@ impl<'syntax> PrepSyntax<'syntax> {
         }
 
         while let Some(symbol) = stack.pop() {
-            if visited.contains(symbol) {
+            if !visited.insert(symbol) {
                 continue;
-            } else {
-                visited.insert(symbol);
             }
 
             // Follow(X)
             // Get all rules containing X in the rhs list.
-            for rule in self.iter().filter(|rule| rule.contains(&symbol)) {
-                for item in rule.follow(&symbol) {
+            for rule in self.iter().filter(|rule| rule.contains(symbol)) {
+                for item in rule.follow(symbol) {
                     // Follow(X, rule) -> {ItemCore...}
                     // If : A → αX•, we add Follow(A) to the Set.
                     if item.is_exhausted() {
@@ -61,12 +54,14 @@ impl<'syntax> PrepSyntax<'syntax> {
                     }
                     // A → αX•β
                     else {
-                        let subset = self.first(&item.symbol().unwrap());
+                        let subset = self.first(item.symbol().unwrap());
                         set.extend(subset);
                     }
                 }
             }
         }
+
+        set
     }
 
     /// Fetch the terminal symbols from deriving the given non-terminal symbol.
@@ -77,13 +72,11 @@ impl<'syntax> PrepSyntax<'syntax> {
 
         let mut set = HashSet::default();
         let mut visited = HashSet::<PrepSymbol<'syntax>>::default();
-        let mut stack = vec![*symbol];
+        let mut stack = vec![symbol];
 
         while let Some(symbol) = stack.pop() {
-            if visited.contains(&symbol) {
+            if !visited.insert(symbol) {
                 continue;
-            } else {
-                visited.insert(symbol);
             }
 
             if symbol.is_terminal() {
@@ -91,7 +84,7 @@ impl<'syntax> PrepSyntax<'syntax> {
                 continue;
             }
 
-            for rule in self.iter_by_symbol(&symbol) {
+            for rule in self.iter_by_symbol(symbol) {
                 let symbol = rule.rhs.first().copied().unwrap();
                 stack.push(symbol);
             }
@@ -100,18 +93,25 @@ impl<'syntax> PrepSyntax<'syntax> {
         set
     }
 
-    /// Returns the start item set (#0)
+    /// Returns the start item set of the given entry point.
+    ///
+    /// The entry #0 is the root rule (#0).
     ///
     /// # Panics
-    /// Panics if there are no start rule (#0), or the start rule is empty.
-    pub fn start_item_set<'gen, const K: usize>(&'gen self) -> ItemSet<'syntax, 'gen, K> {
-        let mut start = self.borrow_rule(0).at::<K>(0).unwrap();
+    /// Panics if the entry does not exist, or its start rule is empty.
+    pub fn start_item_set<'gen, const K: usize>(
+        &'gen self,
+        entry: usize,
+    ) -> ItemSet<'syntax, 'gen, K> {
+        let mut start = self.borrow_rule(self.entries[entry]).at::<K>(0).unwrap();
 
         if K > 0 {
             start.lookaheads = Array::from_iter([PrepSymbol::EOS]);
         }
 
-        [start].into_iter().collect()
+        let mut set: ItemSet<'syntax, 'gen, K> = [start].into_iter().collect();
+        set.id = entry;
+        set
     }
 }
 
@@ -162,9 +162,9 @@ impl<const K: usize> std::fmt::Display for Item<'_, '_, K> {
 }
 
 impl<'syntax, 'gen, const K: usize> Item<'syntax, 'gen, K> {
-    pub fn follow(&self, rules: &'gen PrepSyntax<'syntax>) -> HashSet<&'syntax Symbol> {
+    pub fn follow(&self, rules: &'gen PrepSyntax<'syntax>) -> HashSet<PrepSymbol<'syntax>> {
         self.symbol()
-            .map(|sym| rules.follow(&sym))
+            .map(|sym| rules.follow(sym))
             .unwrap_or_default()
     }
 }
@@ -313,14 +313,14 @@ impl<'syntax, 'gen, const K: usize> ItemSet<'syntax, 'gen, K> {
 
     pub fn iter_immediate_terminal_items<'set>(
         &'set self,
-    ) -> impl Iterator<Item = &Item<'syntax, 'gen, K>> + 'set {
+    ) -> impl Iterator<Item = &'set Item<'syntax, 'gen, K>> + 'set {
         self.iter().filter(|item| item.is_symbol_terminal())
     }
 
     /// Iterate over all exhausted items (A -> w •)
     pub fn iter_exhausted_items<'set>(
         &'set self,
-    ) -> impl Iterator<Item = &Item<'syntax, 'gen, K>> + 'set {
+    ) -> impl Iterator<Item = &'set Item<'syntax, 'gen, K>> + 'set {
         self.iter().filter(|item| item.is_exhausted())
     }
 
@@ -347,7 +347,7 @@ impl<'syntax, 'gen, const K: usize> ItemSet<'syntax, 'gen, K> {
 
     /// Iterate over all items within the set.
     pub fn iter(&self) -> impl Iterator<Item = &Item<'syntax, 'gen, K>> {
-        return self.kernel.iter().chain(self.items.iter());
+        self.kernel.iter().chain(self.items.iter())
     }
 
     fn push(&mut self, item: Item<'syntax, 'gen, K>) {
@@ -366,10 +366,10 @@ impl<'syntax, 'gen, const K: usize> ItemSet<'syntax, 'gen, K> {
     pub fn reachable_sets(
         &self,
         syntax: &'gen PrepSyntax<'syntax>,
-    ) -> Vec<(PrepSyntax<'syntax>, ItemSet<'syntax, 'gen, K>)> {
+    ) -> Vec<(PrepSymbol<'syntax>, ItemSet<'syntax, 'gen, K>)> {
         syntax
-            .iter_symbols()
-            .filter(|sym| !(sym.is_eos() || sym.is_epsilon()))
+            .symbols
+            .iter()
             .map(|sym| {
                 (
                     sym,
@@ -391,11 +391,11 @@ impl<'syntax, 'gen, const K: usize> ItemSet<'syntax, 'gen, K> {
         symbol: PrepSymbol<'syntax>,
         rules: &'gen PrepSyntax<'syntax>,
     ) -> HashSet<PrepSymbol<'syntax>> {
-        if symbol == &rules.start() {
-            return HashSet::from_iter([rules.eos()]);
+        if rules.is_start(symbol) {
+            return HashSet::from_iter([PrepSymbol::EOS]);
         }
         self.iter()
-            .filter(|item| item.symbol() == Some(*symbol))
+            .filter(|item| item.symbol() == Some(symbol))
             .flat_map(|item| item.follow(rules))
             .collect()
     }
@@ -407,7 +407,7 @@ impl<'syntax, 'gen, const K: usize> ItemSet<'syntax, 'gen, K> {
         let mut items = Vec::<Item<'syntax, 'gen, K>>::default();
 
         for item in self.items.iter() {
-            for symbol in rules.follow(&item.rule.lhs) {
+            for symbol in rules.follow(item.rule.lhs) {
                 let mut item = item.clone();
                 item.lookaheads = [symbol].into_iter().collect();
                 items.push(item);
@@ -426,7 +426,7 @@ impl<'syntax, 'gen, const K: usize> ItemSet<'syntax, 'gen, K> {
         while let Some(item) = stack.pop() {
             if item.is_symbol_non_terminal() {
                 let sym = item.symbol().unwrap();
-                for item in rules.iter_by_symbol(&sym).flat_map(|rule| rule.at(0)) {
+                for item in rules.iter_by_symbol(sym).flat_map(|rule| rule.at(0)) {
                     if !self.contains(&item) {
                         stack.push(item.clone());
                         self.push(item);
@@ -445,7 +445,7 @@ impl<'syntax, 'gen, const K: usize> ItemSet<'syntax, 'gen, K> {
 mod tests {
     use crate::{
         fixtures::{FIXTURE_LR0_GRAMMAR, FIXTURE_LR1_GRAMMAR},
-        syntax::PrepSyntax,
+        syntax::{PrepSymbol, PrepSyntax},
         ItemSet,
     };
 
@@ -456,7 +456,7 @@ mod tests {
         let syntax = FIXTURE_LR0_GRAMMAR.to_owned();
         let rules = PrepSyntax::from(&syntax);
 
-        let mut set = rules.start_item_set::<0>();
+        let mut set = rules.start_item_set::<0>(0);
         set.close(&rules);
 
         let expected_set = ItemSet::new(
@@ -483,55 +483,56 @@ mod tests {
 
     #[test]
     fn test_002_first_set() {
-        let g = &FIXTURE_LR1_GRAMMAR.to_owned();
-        let rules = PrepSyntax::from(&g);
+        let rules = PrepSyntax::from(&FIXTURE_LR1_GRAMMAR);
+        let sym = |id| rules.sym(id).unwrap();
 
-        let mut values = rules.first(&g.sym("T"));
-        let mut expected_values = HashSet::from_iter([g.sym("n"), g.sym("+")]);
+        let mut values = rules.first(sym("T"));
+        let mut expected_values = HashSet::from_iter([sym("n"), sym("+")]);
         assert_eq!(values, expected_values);
 
-        values = rules.first(&g.sym("E"));
-        expected_values = HashSet::from_iter([g.sym("n"), g.sym("("), g.sym("+")]);
+        values = rules.first(sym("E"));
+        expected_values = HashSet::from_iter([sym("n"), sym("("), sym("+")]);
         assert_eq!(values, expected_values);
 
-        values = rules.first(&g.start());
-        expected_values = HashSet::from_iter([g.sym("n"), g.sym("("), g.sym("+")]);
+        values = rules.first(rules.start().unwrap());
+        expected_values = HashSet::from_iter([sym("n"), sym("("), sym("+")]);
         assert_eq!(values, expected_values);
     }
 
     #[test]
     /// Follow(A)
     fn test_003_follow_set() {
-        let g = &FIXTURE_LR1_GRAMMAR;
-        let rules = RuleSet::new(g);
+        let rules = PrepSyntax::from(&FIXTURE_LR1_GRAMMAR);
+        let sym = |id| rules.sym(id).unwrap();
 
-        let values = rules.follow(&g.start());
-        let expected_values = HashSet::from_iter([g.eos()]);
+        let values = rules.follow(rules.start().unwrap());
+        let expected_values = HashSet::from_iter([PrepSymbol::EOS]);
         assert_eq!(values, expected_values);
 
-        let values = rules.follow(&g.sym("T"));
-        let expected_values = HashSet::from_iter([g.sym(")"), g.sym("+"), g.eos()]);
+        let values = rules.follow(sym("T"));
+        let expected_values = HashSet::from_iter([sym(")"), PrepSymbol::EOS]);
         assert_eq!(values, expected_values);
     }
 
     #[test]
     /// Follow(In, A)
     fn test_004_item_set_follow_set() {
-        let g = &FIXTURE_LR1_GRAMMAR;
-        let rules = RuleSet::new(g);
-        let mut i0 = rules.start_item_set::<0>();
+        let rules = PrepSyntax::from(&FIXTURE_LR1_GRAMMAR);
+        let sym = |id| rules.sym(id).unwrap();
+
+        let mut i0 = rules.start_item_set::<0>(0);
         i0.close(&rules);
 
-        let mut values = i0.follow(&g.start(), &rules);
-        let mut expected_values = HashSet::from_iter([g.eos()]);
+        let mut values = i0.follow(rules.start().unwrap(), &rules);
+        let mut expected_values = HashSet::from_iter([PrepSymbol::EOS]);
         assert_eq!(values, expected_values);
 
-        values = i0.follow(&g.sym("E"), &rules);
-        expected_values = HashSet::from_iter([g.eos(), g.sym(")")]);
+        values = i0.follow(sym("E"), &rules);
+        expected_values = HashSet::from_iter([PrepSymbol::EOS, sym(")")]);
         assert_eq!(values, expected_values);
 
-        values = i0.follow(&g.sym("T"), &rules);
-        expected_values = HashSet::from_iter([g.eos(), g.sym(")"), g.sym("+")]);
+        values = i0.follow(sym("T"), &rules);
+        expected_values = HashSet::from_iter([PrepSymbol::EOS, sym(")")]);
         assert_eq!(values, expected_values);
     }
 }
@@ -1,23 +1,28 @@
+//! The AST of the regular expressions describing tokens.
+use crate::dfa::{CharSet, Graph, IntoGraph};
+
 pub enum Expr {
     Sequence(Sequence),
     Either(Either),
+    Group(Group),
     Quantified(Quantified),
     Leaf(Leaf),
 }
 
-impl IntoGraph for Expr {
-    fn into_graph(self) -> Graph {
+impl<A: Clone> IntoGraph<CharSet, A> for Expr {
+    fn into_graph(self) -> Graph<CharSet, A> {
         match self {
             Expr::Sequence(seq) => seq.into_graph(),
-            Expr::Either(_) => todo!(),
-            Expr::Quantified(_) => todo!(),
-            Expr::Leaf(_) => todo!(),
+            Expr::Either(either) => either.into_graph(),
+            Expr::Group(group) => group.into_graph(),
+            Expr::Quantified(quantified) => quantified.into_graph(),
+            Expr::Leaf(leaf) => leaf.into_graph(),
         }
     }
 }
 
 /// A1..An
-pub struct Sequence(Vec<Expr>);
+pub struct Sequence(pub Vec<Expr>);
 
 impl IntoIterator for Sequence {
     type Item = Expr;
@@ -28,17 +33,18 @@ impl IntoIterator for Sequence {
     }
 }
 
-impl IntoGraph for Sequence {
-    fn into_graph(self) -> GraphFragment {
+/// The empty sequence matches the empty string.
+impl<A: Clone> IntoGraph<CharSet, A> for Sequence {
+    fn into_graph(self) -> Graph<CharSet, A> {
         self.into_iter()
-            .map(IntoGraphFragment::into_graph)
+            .map(IntoGraph::into_graph)
             .reduce(|a, b| a + b)
-            .unwrap_or_default()
+            .unwrap_or_else(Graph::empty)
     }
 }
 
 /// A1 | A2 | ... | An
-pub struct Either(Vec<Expr>);
+pub struct Either(pub Vec<Expr>);
 
 impl IntoIterator for Either {
     type Item = Expr;
@@ -49,43 +55,68 @@ impl IntoIterator for Either {
     }
 }
 
-impl IntoGraphFragment for Either {
-    fn into_graph_fragment(self) -> GraphFragment {
+/// The empty alternation matches nothing.
+impl<A: Clone> IntoGraph<CharSet, A> for Either {
+    fn into_graph(self) -> Graph<CharSet, A> {
         self.into_iter()
-            .map(IntoGraphFragment::into_graph_fragment)
-            .reduce(GraphFragment::merge)
+            .map(IntoGraph::into_graph)
+            .reduce(Graph::merge)
             .unwrap_or_default()
     }
 }
 
-/// (A1)
-pub struct Group(Box<Expr>, Option<String>);
+/// (A1), or (?<name>A1)
+pub struct Group(pub Box<Expr>, pub Option<String>);
 
+impl<A: Clone> IntoGraph<CharSet, A> for Group {
+    fn into_graph(self) -> Graph<CharSet, A> {
+        self.0.into_graph()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Quantifier {
     /// *, or {0,}
     Wild,
     /// ?, or {0,1}
     Optional,
-    /// {n,}
+    /// +, or {n,}
     RangeFrom(usize),
     /// {,m}
     RangeTo(usize),
-    /// {n,m}
+    /// {n,m}, or {n}
     Range(usize, usize),
 }
 
 /// A{n,m} or A+, or A?, or A*
-pub struct Quantified(Box<Expr>, Quantifier);
+pub struct Quantified(pub Box<Expr>, pub Quantifier);
+
+impl<A: Clone> IntoGraph<CharSet, A> for Quantified {
+    fn into_graph(self) -> Graph<CharSet, A> {
+        let graph: Graph<CharSet, A> = self.0.into_graph();
 
-#[derive(Clone)]
-pub struct Leaf {
-    kind: Atomic,
+        match self.1 {
+            Quantifier::Wild => graph.star(),
+            Quantifier::Optional => graph.optional(),
+            Quantifier::RangeFrom(min) => graph.repeat(min, None),
+            Quantifier::RangeTo(max) => graph.repeat(0, Some(max)),
+            Quantifier::Range(min, max) => graph.repeat(min, Some(max)),
+        }
+    }
 }
 
+/// A single char among a set.
+#[derive(Debug, Clone)]
+pub struct Leaf(pub CharSet);
+
 impl Leaf {
     pub fn intersect(&self, rhs: &Self) -> Self {
-        Self {
-            kind: self.kind.intersect(&rhs.kind),
-        }
+        Self(self.0.clone() & rhs.0.clone())
+    }
+}
+
+impl<A> IntoGraph<CharSet, A> for Leaf {
+    fn into_graph(self) -> Graph<CharSet, A> {
+        Graph::single(self.0)
     }
 }
@@ -0,0 +1,1503 @@
+//! Lexers generated from token patterns.
+//!
+//! The patterns are compiled into a DFA per lexer mode, which is run with
+//! the longest match rule. When several patterns match the longest input,
+//! the first declared one wins, so keywords are declared before identifiers.
+//!
+//! Modes are like flex start conditions : each one has its own token set,
+//! and tokens may push a mode, or pop back to the previous one.
+//!
+//! Tokens may be declared as skipped (whitespaces), or as trivia (comments)
+//! which are attached to the next token, so the parser never sees them.
+//!
+//! Lexers are built over chars by default, or over bytes for binary
+//! formats, from [ByteSet] patterns.
+//!
+//! Tokens which are not regular (nested comments, raw strings) match
+//! their opener, and a [SubLexer] scans the rest of them.
+//!
+//! Keywords are looked up in a [Keywords] table once an identifier is
+//! matched, and identifiers can be interned as [Symbol](super::Symbol)s.
+use std::{
+    any::{type_name, Any},
+    collections::VecDeque,
+    marker::PhantomData,
+};
+
+use crate::{
+    dfa::{Alphabet, ByteSet, CharSet, CompactDfa, Dfa, Graph, IntoGraph, Set},
+    source::FileId,
+    span::{Cursor, NextColumn, NextLine, Span},
+    token::Token,
+    ErrorKind, YalpError, YalpResult,
+};
+
+use super::{
+    input::{Bytes, Input, Str, Unit},
+    interner::Interner,
+    keywords::Keywords,
+    recovery::{Recovery, ERROR},
+    regex,
+    sub::{Delimited, Nested, SubLexer},
+    traits, Callback, InputToken, SliceOf,
+};
+
+/// The mode a generated lexer starts in.
+pub const DEFAULT_MODE: &str = "default";
+
+/// A change of lexer mode, triggered by a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeChange<Mode> {
+    Push(Mode),
+    Pop,
+}
+
+/// What the lexer does with a matched token.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TokenRole {
+    /// Emit the token.
+    #[default]
+    Token,
+    /// Drop the token.
+    Skip,
+    /// Attach the token to the next emitted one.
+    Trivia,
+}
+
+struct TokenDecl<'kind, S>
+where
+    S: Set,
+{
+    mode: usize,
+    kind: &'kind str,
+    graph: Graph<S, ()>,
+    change: Option<ModeChange<&'kind str>>,
+    role: TokenRole,
+    sub: Option<Box<dyn SubLexer<S::Item> + Send + Sync>>,
+    keywords: bool,
+    intern: bool,
+    callback: Option<TokenCallback>,
+}
+
+/// A [Callback] computing the payload of a token, whose types are erased
+/// so that the lexer tables do not depend on the error type.
+pub struct TokenCallback(Box<dyn Any + Send + Sync>);
+
+impl TokenCallback {
+    pub fn new<Error, Slice>(callback: Callback<Error, Slice>) -> Self
+    where
+        Error: 'static,
+        Slice: ?Sized + 'static,
+    {
+        Self(Box::new(callback))
+    }
+
+    /// The callback, if it has the given error and slice types.
+    pub fn get<Error, Slice>(&self) -> Option<Callback<Error, Slice>>
+    where
+        Error: 'static,
+        Slice: ?Sized + 'static,
+    {
+        self.0.downcast_ref().copied()
+    }
+}
+
+/// A token of a compiled lexer.
+struct TokenDef<'kind, U> {
+    kind: &'kind str,
+    change: Option<ModeChange<usize>>,
+    role: TokenRole,
+    /// Scans the token past the match of its pattern.
+    sub: Option<Box<dyn SubLexer<U> + Send + Sync>>,
+    /// Whether the values are looked up in the keywords.
+    keywords: bool,
+    /// Whether the values are interned.
+    intern: bool,
+    /// Computes the payload of the tokens.
+    callback: Option<TokenCallback>,
+}
+
+/// Declares the tokens of a generated lexer.
+pub struct LexerBuilder<'kind, S = CharSet>
+where
+    S: Set,
+{
+    modes: Vec<&'kind str>,
+    current: usize,
+    tokens: Vec<TokenDecl<'kind, S>>,
+    keywords: Vec<&'kind str>,
+}
+
+impl<'kind, S> Default for LexerBuilder<'kind, S>
+where
+    S: Set,
+{
+    fn default() -> Self {
+        Self {
+            modes: vec![DEFAULT_MODE],
+            current: 0,
+            tokens: vec![],
+            keywords: vec![],
+        }
+    }
+}
+
+impl<'kind> LexerBuilder<'kind> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a token from a regular expression.
+    pub fn regex<Error>(self, kind: &'kind str, pattern: &str) -> YalpResult<Self, Error> {
+        regex::parse(pattern).map(|expr| self.token(kind, expr))
+    }
+
+    /// Declare a nested comment, such as `/* /* */ */`.
+    pub fn nested_comment(self, kind: &'kind str, open: &'kind str, close: &'kind str) -> Self {
+        self.token(kind, open)
+            .sub_lexer(Nested::new(open.chars(), close.chars()))
+    }
+
+    /// Declare a Rust raw string, such as `r#"..."#`.
+    pub fn raw_string(self, kind: &'kind str) -> Self {
+        let opener = Graph::single(CharSet::eq('r'))
+            + Graph::single(CharSet::eq('#')).star()
+            + Graph::single(CharSet::eq('"'));
+
+        self.token(kind, opener).sub_lexer(Delimited::raw_string())
+    }
+
+    /// Declare a Lua long bracket, such as `[==[...]==]`.
+    pub fn long_bracket(self, kind: &'kind str) -> Self {
+        let opener = Graph::single(CharSet::eq('['))
+            + Graph::single(CharSet::eq('=')).star()
+            + Graph::single(CharSet::eq('['));
+
+        self.token(kind, opener)
+            .sub_lexer(Delimited::long_bracket())
+    }
+}
+
+impl<'kind> LexerBuilder<'kind, ByteSet> {
+    /// A builder of lexers over bytes.
+    pub fn bytes() -> Self {
+        Self::default()
+    }
+}
+
+impl<'kind, S> LexerBuilder<'kind, S>
+where
+    S: Alphabet + Clone,
+{
+    /// Declare the following tokens in the mode.
+    pub fn mode(mut self, name: &'kind str) -> Self {
+        self.current = self
+            .modes
+            .iter()
+            .position(|mode| *mode == name)
+            .unwrap_or_else(|| {
+                self.modes.push(name);
+                self.modes.len() - 1
+            });
+
+        self
+    }
+
+    /// Declare a token, earlier declarations take priority over later ones.
+    pub fn token(mut self, kind: &'kind str, pattern: impl IntoGraph<S, ()>) -> Self {
+        self.tokens.push(TokenDecl {
+            mode: self.current,
+            kind,
+            graph: pattern.into_graph(),
+            change: None,
+            role: TokenRole::Token,
+            sub: None,
+            keywords: false,
+            intern: false,
+            callback: None,
+        });
+        self
+    }
+
+    /// The last declared token, an identifier, is looked up in the
+    /// keywords once matched: a keyword becomes a token of its own kind,
+    /// spelled as the keyword.
+    ///
+    /// # Panics
+    /// Panics if no token has been declared yet.
+    pub fn keywords(mut self, keywords: impl IntoIterator<Item = &'kind str>) -> Self {
+        self.tokens
+            .last_mut()
+            .expect("no token has been declared")
+            .keywords = true;
+        self.keywords.extend(keywords);
+        self
+    }
+
+    /// The values of the last declared token are interned, keywords
+    /// excepted.
+    ///
+    /// # Panics
+    /// Panics if no token has been declared yet.
+    pub fn intern(mut self) -> Self {
+        self.tokens
+            .last_mut()
+            .expect("no token has been declared")
+            .intern = true;
+        self
+    }
+
+    /// The payload of the last declared token is computed by the callback.
+    ///
+    /// The errors it returns are raised at the token's span, and the
+    /// lexer resumes after the token. The lexer must raise errors of the
+    /// same type, or the token fails with an unexpected value type.
+    pub fn callback<Error>(
+        mut self,
+        callback: Callback<Error, <S::Item as Unit>::Slice>,
+    ) -> YalpResult<Self, Error>
+    where
+        Error: 'static,
+        S::Item: Unit,
+    {
+        self.tokens
+            .last_mut()
+            .ok_or_else(|| YalpError::new(ErrorKind::NoTokenDeclared, None))?
+            .callback = Some(TokenCallback::new(callback));
+        Ok(self)
+    }
+
+    /// The last declared token is scanned past its pattern by the
+    /// sub-lexer, its pattern only matches its opener.
+    ///
+    /// # Panics
+    /// Panics if no token has been declared yet.
+    pub fn sub_lexer(mut self, sub: impl SubLexer<S::Item> + Send + Sync + 'static) -> Self {
+        self.tokens
+            .last_mut()
+            .expect("no token has been declared")
+            .sub = Some(Box::new(sub));
+        self
+    }
+
+    /// The last declared token enters the mode.
+    ///
+    /// # Panics
+    /// Panics if no token has been declared yet.
+    pub fn push_mode(self, mode: &'kind str) -> Self {
+        self.change_mode(ModeChange::Push(mode))
+    }
+
+    /// The last declared token returns to the previous mode.
+    ///
+    /// # Panics
+    /// Panics if no token has been declared yet.
+    pub fn pop_mode(self) -> Self {
+        self.change_mode(ModeChange::Pop)
+    }
+
+    /// The last declared token is dropped.
+    ///
+    /// # Panics
+    /// Panics if no token has been declared yet.
+    pub fn skip(self) -> Self {
+        self.role(TokenRole::Skip)
+    }
+
+    /// The last declared token is attached to the next emitted token.
+    ///
+    /// # Panics
+    /// Panics if no token has been declared yet.
+    pub fn trivia(self) -> Self {
+        self.role(TokenRole::Trivia)
+    }
+
+    fn role(mut self, role: TokenRole) -> Self {
+        self.tokens
+            .last_mut()
+            .expect("no token has been declared")
+            .role = role;
+        self
+    }
+
+    fn change_mode(mut self, change: ModeChange<&'kind str>) -> Self {
+        self.tokens
+            .last_mut()
+            .expect("no token has been declared")
+            .change = Some(change);
+        self
+    }
+
+    /// Compile the patterns into the lexer's automata.
+    pub fn build<Error>(self) -> YalpResult<LexerTable<'kind, S>, Error> {
+        let mut tokens = Vec::with_capacity(self.tokens.len());
+        let mut graphs = vec![vec![]; self.modes.len()];
+
+        for (tag, decl) in self.tokens.into_iter().enumerate() {
+            let change = match decl.change {
+                Some(ModeChange::Push(name)) => Some(ModeChange::Push(
+                    self.modes
+                        .iter()
+                        .position(|mode| *mode == name)
+                        .ok_or_else(|| {
+                            YalpError::new(ErrorKind::UnknownLexerMode(name.to_string()), None)
+                        })?,
+                )),
+                Some(ModeChange::Pop) => Some(ModeChange::Pop),
+                None => None,
+            };
+
+            tokens.push(TokenDef {
+                kind: decl.kind,
+                change,
+                role: decl.role,
+                sub: decl.sub,
+                keywords: decl.keywords,
+                intern: decl.intern,
+                callback: decl.callback,
+            });
+            graphs[decl.mode].push((tag, decl.graph));
+        }
+
+        let modes = self
+            .modes
+            .into_iter()
+            .zip(graphs)
+            .map(|(name, graphs)| {
+                (
+                    name,
+                    CompactDfa::from(&Dfa::from_graphs(&graphs).minimize()),
+                )
+            })
+            .collect();
+
+        Ok(LexerTable {
+            tokens,
+            modes,
+            keywords: Keywords::new(self.keywords),
+        })
+    }
+}
+
+/// A token of a table compiled ahead of time, see [LexerTable::from_compiled].
+pub type CompiledToken<'kind> = (
+    &'kind str,
+    Option<ModeChange<usize>>,
+    TokenRole,
+    Option<TokenCallback>,
+);
+
+/// The compiled automata of a generated lexer.
+pub struct LexerTable<'kind, S = CharSet>
+where
+    S: Alphabet,
+{
+    tokens: Vec<TokenDef<'kind, S::Item>>,
+    /// The automaton of each mode, the default mode being the first one.
+    modes: Vec<(&'kind str, CompactDfa<S>)>,
+    keywords: Keywords<'kind>,
+}
+
+impl<'kind> LexerTable<'kind> {
+    /// Lex the string, the token values are slices of it.
+    pub fn lex<'table, 'stream, Error>(
+        &'table self,
+        input: &'stream str,
+    ) -> DfaLexer<'table, 'kind, 'stream, Error> {
+        DfaLexer::new(self, Str::new(input))
+    }
+}
+
+impl<'kind> LexerTable<'kind, ByteSet> {
+    /// Lex the bytes, the token values are slices of them.
+    pub fn lex_bytes<'table, 'stream, Error>(
+        &'table self,
+        input: &'stream [u8],
+    ) -> DfaLexer<'table, 'kind, 'stream, Error, Bytes<'stream>> {
+        DfaLexer::new(self, Bytes::new(input))
+    }
+}
+
+impl<'kind, S> LexerTable<'kind, S>
+where
+    S: Alphabet,
+{
+    /// Lex any input, such as a [Reader](super::input::Reader).
+    pub fn lex_input<'table, 'stream, Error, I>(
+        &'table self,
+        input: I,
+    ) -> DfaLexer<'table, 'kind, 'stream, Error, I>
+    where
+        I: Input<'stream>,
+        I::Unit: Unit<Set = S>,
+    {
+        DfaLexer::new(self, input)
+    }
+
+    /// Assemble a table from its compiled tokens, by tag, and the
+    /// automaton of each mode, such as the ones generated at compile time
+    /// by the `lexer!` macro.
+    ///
+    /// Each token comes with its mode change, role and payload callback.
+    pub fn from_compiled(
+        tokens: impl IntoIterator<Item = CompiledToken<'kind>>,
+        modes: Vec<(&'kind str, CompactDfa<S>)>,
+    ) -> Self {
+        let tokens = tokens
+            .into_iter()
+            .map(|(kind, change, role, callback)| TokenDef {
+                kind,
+                change,
+                role,
+                sub: None,
+                keywords: false,
+                intern: false,
+                callback,
+            })
+            .collect();
+
+        Self {
+            tokens,
+            modes,
+            keywords: Keywords::default(),
+        }
+    }
+
+    pub fn iter_kinds(&self) -> impl Iterator<Item = &'kind str> + '_ {
+        self.tokens.iter().map(|def| def.kind)
+    }
+
+    /// Iterate over the tokens, by tag, with their mode change and role.
+    pub fn iter_tokens(
+        &self,
+    ) -> impl Iterator<Item = (&'kind str, Option<ModeChange<usize>>, TokenRole)> + '_ {
+        self.tokens
+            .iter()
+            .map(|def| (def.kind, def.change, def.role))
+    }
+
+    /// Iterate over the automaton of each mode.
+    pub fn iter_automata(&self) -> impl Iterator<Item = (&'kind str, &CompactDfa<S>)> + '_ {
+        self.modes.iter().map(|(name, dfa)| (*name, dfa))
+    }
+
+    pub fn iter_modes(&self) -> impl Iterator<Item = &'kind str> + '_ {
+        self.modes.iter().map(|(name, _)| *name)
+    }
+
+    pub fn keywords(&self) -> &Keywords<'kind> {
+        &self.keywords
+    }
+}
+
+/// The table of the lexers reading the input.
+type InputTable<'kind, 'stream, I> = LexerTable<'kind, <<I as Input<'stream>>::Unit as Unit>::Set>;
+
+/// A lexer running a [LexerTable] over an input.
+pub struct DfaLexer<'table, 'kind, 'stream, Error, I = Str<'stream>>
+where
+    I: Input<'stream>,
+{
+    table: &'table InputTable<'kind, 'stream, I>,
+    input: I,
+    /// The units read ahead of the current offset.
+    lookahead: VecDeque<(usize, I::Unit)>,
+    /// The error the input failed with, once the units before it are lexed.
+    failed: Option<YalpError<Error>>,
+    offset: usize,
+    cursor: Cursor,
+    span: Span,
+    /// The source the spans are located in.
+    file: Option<FileId>,
+    /// The stack of modes, the default mode is never popped.
+    modes: Vec<usize>,
+    contextual: bool,
+    /// The tokens expected by the parser, by tag.
+    allowed: Option<Vec<bool>>,
+    /// The keywords expected by the parser, by index.
+    expected_keywords: Option<Vec<bool>>,
+    /// The trivia waiting for the next token.
+    trivia: Vec<InputToken<'stream, I>>,
+    recovery: Recovery<I::Unit>,
+    /// The errors recovered from.
+    errors: Vec<YalpError<Error>>,
+    interner: Interner<SliceOf<'stream, I>>,
+    done: bool,
+    _phantom: PhantomData<Error>,
+}
+
+impl<'table, 'kind, 'stream, Error, I> DfaLexer<'table, 'kind, 'stream, Error, I>
+where
+    I: Input<'stream>,
+{
+    pub fn new(table: &'table InputTable<'kind, 'stream, I>, input: I) -> Self {
+        Self {
+            table,
+            input,
+            lookahead: VecDeque::default(),
+            failed: None,
+            offset: 0,
+            cursor: Cursor::default(),
+            span: Span::default(),
+            file: None,
+            modes: vec![0],
+            contextual: false,
+            allowed: None,
+            expected_keywords: None,
+            trivia: vec![],
+            recovery: Recovery::default(),
+            errors: vec![],
+            interner: Interner::default(),
+            done: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Locate the spans in the file of a [SourceMap](crate::source::SourceMap).
+    pub fn file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// Only match the tokens expected by the parser, when it tells them.
+    ///
+    /// It resolves the clashes between keywords and identifiers, for
+    /// keywords which are only reserved in some positions.
+    pub fn contextual(mut self) -> Self {
+        self.contextual = true;
+        self
+    }
+
+    /// Set the policy applied to the lexing errors.
+    pub fn recovery(mut self, recovery: Recovery<I::Unit>) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    /// The errors recovered from so far.
+    pub fn errors(&self) -> &[YalpError<Error>] {
+        &self.errors
+    }
+
+    pub fn take_errors(&mut self) -> Vec<YalpError<Error>> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Intern the values in the interner, shared with other lexers.
+    pub fn with_interner(mut self, interner: Interner<SliceOf<'stream, I>>) -> Self {
+        self.interner = interner;
+        self
+    }
+
+    /// The identifiers interned so far.
+    pub fn interner(&self) -> &Interner<SliceOf<'stream, I>> {
+        &self.interner
+    }
+
+    pub fn take_interner(&mut self) -> Interner<SliceOf<'stream, I>> {
+        std::mem::take(&mut self.interner)
+    }
+
+    /// Apply the recovery policy to an unexpected unit, which is skipped
+    /// as an [ERROR] token.
+    fn recover(&mut self, err: YalpError<Error>) -> YalpResult<InputToken<'stream, I>, Error> {
+        let sync = match self.recovery {
+            Recovery::Stop => {
+                self.done = true;
+                return Err(err);
+            }
+            Recovery::Skip => None,
+            Recovery::Resync(sync) => Some(sync),
+        };
+
+        self.errors.push(err);
+
+        let mut len = 1;
+
+        if let Some(sync) = sync {
+            while self.peek(len).is_some_and(|unit| !sync(unit)) {
+                len += 1;
+            }
+        }
+
+        Ok(self.error_token(len))
+    }
+
+    /// Skip the next units as an [ERROR] token.
+    fn error_token(&mut self, len: usize) -> InputToken<'stream, I> {
+        let (end, cursor) = self.extent(len);
+        let span = self.span_to(cursor);
+        let token = Token::new(ERROR, self.input.value(self.offset, end), span);
+
+        self.lookahead.drain(..len);
+        self.input.release(end);
+        self.offset = end;
+        self.cursor = cursor;
+        self.span = span;
+
+        Token {
+            trivia: std::mem::take(&mut self.trivia),
+            ..token
+        }
+    }
+
+    /// The span from the current cursor.
+    fn span_to(&self, cursor: Cursor) -> Span {
+        Span::new(self.cursor, cursor).in_file(self.file)
+    }
+
+    /// The end offset and cursor of the next units.
+    fn extent(&self, len: usize) -> (usize, Cursor) {
+        self.lookahead.iter().take(len).fold(
+            (self.offset, self.cursor),
+            |(_, cursor), &(offset, unit)| (offset + unit.len_bytes(), advance(cursor, unit)),
+        )
+    }
+
+    /// Scan the rest of a token with its sub-lexer, returning the length
+    /// of the whole token, or nothing if it is unterminated.
+    fn sub_lex(&mut self, sub: &dyn SubLexer<I::Unit>, len: usize) -> Option<usize> {
+        let opener: Vec<_> = self
+            .lookahead
+            .iter()
+            .take(len)
+            .map(|&(_, unit)| unit)
+            .collect();
+
+        sub.scan(&opener, &mut |i| self.peek(len + i))
+            .map(|rest| len + rest)
+    }
+
+    /// The trivia following the last token, once the stream is exhausted.
+    pub fn trailing_trivia(&self) -> &[InputToken<'stream, I>] {
+        &self.trivia
+    }
+
+    /// The name of the current mode.
+    pub fn mode(&self) -> &'kind str {
+        self.table.modes[self.current_mode()].0
+    }
+
+    fn current_mode(&self) -> usize {
+        self.modes.last().copied().unwrap()
+    }
+
+    fn change_mode(&mut self, change: ModeChange<usize>) {
+        match change {
+            ModeChange::Push(mode) => self.modes.push(mode),
+            ModeChange::Pop if self.modes.len() > 1 => {
+                self.modes.pop();
+            }
+            ModeChange::Pop => {}
+        }
+    }
+
+    /// Returns the i-th unit ahead, reading it from the input if needed.
+    fn peek(&mut self, i: usize) -> Option<I::Unit> {
+        while self.lookahead.len() <= i && self.failed.is_none() {
+            match self.input.next_unit()? {
+                Ok(next) => self.lookahead.push_back(next),
+                Err(err) => self.failed = Some(err),
+            }
+        }
+
+        self.lookahead.get(i).map(|&(_, unit)| unit)
+    }
+
+    /// Run the automaton of the current mode from the current offset,
+    /// and returns the longest match among the allowed tokens, as its
+    /// length in units, end offset, end cursor, and tag.
+    fn longest_match(&mut self, allowed: Option<&[bool]>) -> Option<(usize, usize, Cursor, usize)> {
+        let table = self.table;
+        let dfa = &table.modes[self.current_mode()].1;
+        let mut state = 0;
+        let mut cursor = self.cursor;
+        let mut last = None;
+        let mut i = 0;
+
+        while let Some(unit) = self.peek(i) {
+            match dfa.next(state, unit) {
+                Some(next) => state = next,
+                None => break,
+            }
+
+            cursor = advance(cursor, unit);
+            i += 1;
+
+            let tag = dfa
+                .accepts(state)
+                .iter()
+                .copied()
+                .find(|&tag| allowed.is_none_or(|allowed| allowed[tag]));
+
+            if let Some(tag) = tag {
+                let end = self.lookahead[i - 1].0 + unit.len_bytes();
+                last = Some((i, end, cursor, tag));
+            }
+        }
+
+        last
+    }
+}
+
+impl<'table, 'kind, 'stream, Error, I> traits::Lexer<Error>
+    for DfaLexer<'table, 'kind, 'stream, Error, I>
+where
+    'kind: 'stream,
+    Error: 'static,
+    I: Input<'stream>,
+{
+    type Token = InputToken<'stream, I>;
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn is_contextual(&self) -> bool {
+        self.contextual
+    }
+
+    fn expect(&mut self, terminals: &[&str]) {
+        let table = self.table;
+        let expected_keywords = table
+            .keywords
+            .iter()
+            .map(|keyword| terminals.contains(&keyword))
+            .collect::<Vec<_>>();
+        let keyword = expected_keywords.iter().any(|&expected| expected);
+
+        // Skipped tokens, and trivia, are expected anywhere.
+        self.allowed = Some(
+            table
+                .tokens
+                .iter()
+                .map(|def| {
+                    def.role != TokenRole::Token
+                        || terminals.contains(&def.kind)
+                        || (def.keywords && keyword)
+                })
+                .collect(),
+        );
+        self.expected_keywords = Some(expected_keywords);
+    }
+}
+
+impl<'table, 'kind, 'stream, Error, I> DfaLexer<'table, 'kind, 'stream, Error, I>
+where
+    I: Input<'stream>,
+{
+    /// The kinds of the tokens of the current mode.
+    fn iter_mode_kinds(
+        &self,
+    ) -> impl Iterator<Item = &'kind str> + use<'_, 'table, 'kind, 'stream, Error, I> {
+        let dfa = &self.table.modes[self.current_mode()].1;
+
+        self.table
+            .tokens
+            .iter()
+            .enumerate()
+            .filter(move |(tag, _)| dfa.iter_tags().any(|t| t == *tag))
+            .map(|(_, def)| def.kind)
+    }
+}
+
+impl<'table, 'kind, 'stream, Error, I> Iterator for DfaLexer<'table, 'kind, 'stream, Error, I>
+where
+    'kind: 'stream,
+    Error: 'static,
+    I: Input<'stream>,
+{
+    type Item = YalpResult<InputToken<'stream, I>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.done {
+                return None;
+            }
+
+            // Without any expected match, lex as usual so the parser reports
+            // the unexpected token.
+            let allowed = self.allowed.take();
+            let found = self
+                .longest_match(allowed.as_deref())
+                .or_else(|| self.longest_match(None));
+            self.allowed = allowed;
+
+            let Some((mut len, mut end, mut cursor, tag)) = found else {
+                self.span = self.span_to(self.cursor);
+
+                if let Some(&(_, got)) = self.lookahead.front() {
+                    let err = YalpError::new(
+                        ErrorKind::unexpected_symbol(&got.describe(), self.iter_mode_kinds()),
+                        Some(self.span),
+                    );
+
+                    return Some(self.recover(err));
+                }
+
+                // Either the end of the input, or an input error.
+                self.done = true;
+
+                return self.failed.take().map(|mut err| {
+                    err.span = Some(self.span);
+                    Err(err)
+                });
+            };
+
+            let table = self.table;
+            let def = &table.tokens[tag];
+
+            if let Some(sub) = &def.sub {
+                let Some(whole) = self.sub_lex(sub.as_ref(), len) else {
+                    // The sub-lexer has read the rest of the input.
+                    let rest = self.lookahead.len();
+                    let (_, cursor) = self.extent(rest);
+                    let err = YalpError::new(
+                        ErrorKind::UnterminatedToken(def.kind.to_string()),
+                        Some(self.span_to(cursor)),
+                    );
+
+                    if !self.recovery.is_enabled() {
+                        self.done = true;
+                        self.span = self.span_to(cursor);
+                        return Some(Err(err));
+                    }
+
+                    self.errors.push(err);
+                    return Some(Ok(self.error_token(rest)));
+                };
+
+                len = whole;
+                (end, cursor) = self.extent(len);
+            }
+
+            let span = self.span_to(cursor);
+            let value = self.input.value(self.offset, end);
+
+            self.lookahead.drain(..len);
+            self.input.release(end);
+            self.offset = end;
+            self.cursor = cursor;
+
+            // An unexpected keyword stays an identifier.
+            let keyword = def
+                .keywords
+                .then(|| table.keywords.find(&*value))
+                .flatten()
+                .filter(|keyword| {
+                    self.expected_keywords
+                        .as_ref()
+                        .is_none_or(|expected| expected[keyword.index()])
+                });
+            let kind = keyword
+                .and_then(|keyword| table.keywords.get(keyword))
+                .unwrap_or(def.kind);
+
+            let mut token = Token::new(kind, value, span);
+            token.keyword = keyword;
+
+            if def.intern && keyword.is_none() {
+                token.symbol = Some(self.interner.intern(&token.value));
+            }
+
+            if let Some(change) = def.change {
+                self.change_mode(change);
+            }
+
+            if let Some(callback) = &def.callback {
+                let payload = callback
+                    .get::<Error, SliceOf<'stream, I>>()
+                    .ok_or_else(|| {
+                        YalpError::new(
+                            ErrorKind::UnexpectedValueType(type_name::<
+                                Callback<Error, SliceOf<'stream, I>>,
+                            >()),
+                            None,
+                        )
+                    })
+                    .and_then(|callback| callback(&*token.value));
+
+                match payload {
+                    Ok(payload) => token.payload = Some(payload),
+                    Err(mut err) => {
+                        err.span = Some(span);
+
+                        // The token is kept, without its payload.
+                        if !self.recovery.is_enabled() {
+                            self.span = span;
+                            return Some(Err(err));
+                        }
+
+                        self.errors.push(err);
+                    }
+                }
+            }
+
+            match def.role {
+                TokenRole::Skip => {}
+                TokenRole::Trivia => self.trivia.push(token),
+                TokenRole::Token => {
+                    self.span = span;
+
+                    return Some(Ok(Token {
+                        trivia: std::mem::take(&mut self.trivia),
+                        ..token
+                    }));
+                }
+            }
+        }
+    }
+}
+
+/// The cursor after the unit.
+fn advance(mut cursor: Cursor, unit: impl Unit) -> Cursor {
+    cursor.offset += unit.len_bytes();
+
+    if unit.is_newline() {
+        cursor += NextLine;
+    } else {
+        cursor += NextColumn;
+    }
+
+    cursor
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::{
+        dfa::{ByteSet, CharSet, IntoGraph},
+        source::SourceMap,
+        token::Payload,
+        ErrorKind, NoCustomError, YalpError, YalpResult,
+    };
+
+    use super::{
+        super::{input::Reader, traits::Lexer as _, Recovery, ERROR},
+        LexerBuilder, LexerTable, TokenCallback,
+    };
+
+    #[test]
+    fn test_001_longest_match_and_priority() {
+        let table = LexerBuilder::new()
+            .token("if", "if")
+            .token("ident", CharSet::from('a'..='z').into_graph().plus())
+            .token("ws", CharSet::from(' ').into_graph().plus())
+            .build::<NoCustomError>()
+            .unwrap();
+
+        let tokens = table
+            .lex::<NoCustomError>("if iffy  i")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let got = tokens
+            .iter()
+            .map(|tok| (tok.kind.as_ref(), tok.value.as_ref()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            got,
+            vec![
+                ("if", "if"),
+                ("ws", " "),
+                ("ident", "iffy"),
+                ("ws", "  "),
+                ("ident", "i"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_002_regex_tokens() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("number", "\\d+(\\.\\d+)?")
+            .and_then(|builder| builder.regex("ident", "[a-zA-Z_]\\w*"))
+            .and_then(|builder| builder.regex("ws", "\\s+"))
+            .unwrap()
+            .build::<NoCustomError>()
+            .unwrap();
+
+        let tokens = table
+            .lex::<NoCustomError>("x1 3.14 7")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let got = tokens
+            .iter()
+            .map(|tok| (tok.kind.as_ref(), tok.value.as_ref()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            got,
+            vec![
+                ("ident", "x1"),
+                ("ws", " "),
+                ("number", "3.14"),
+                ("ws", " "),
+                ("number", "7"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_003_unexpected_char() {
+        let table = LexerBuilder::new()
+            .token("n", CharSet::from('0'..='9').into_graph().plus())
+            .build::<NoCustomError>()
+            .unwrap();
+
+        let mut lexer = table.lex::<NoCustomError>("12a3");
+        assert_eq!(lexer.next().unwrap().unwrap().value, "12");
+        assert!(lexer.next().unwrap().is_err());
+        assert!(lexer.next().is_none());
+    }
+
+    #[test]
+    fn test_004_modes() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ident", "[a-z]+")
+            .and_then(|builder| builder.regex("ws", " +"))
+            .and_then(|builder| builder.regex("quote", "\""))
+            .map(|builder| builder.push_mode("string").mode("string"))
+            .and_then(|builder| builder.regex("chars", "[^\"\\\\]+"))
+            .and_then(|builder| builder.regex("escape", "\\\\."))
+            .and_then(|builder| builder.regex("quote", "\""))
+            .map(LexerBuilder::pop_mode)
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let mut lexer = table.lex::<NoCustomError>(r#"a "b \"c" d"#);
+        let mut got = vec![];
+
+        while let Some(tok) = lexer.next() {
+            let tok = tok.unwrap();
+            got.push((tok.kind, tok.value, lexer.mode()));
+        }
+
+        let expected = [
+            ("ident", "a", "default"),
+            ("ws", " ", "default"),
+            ("quote", "\"", "string"),
+            ("chars", "b ", "string"),
+            ("escape", "\\\"", "string"),
+            ("chars", "c", "string"),
+            ("quote", "\"", "default"),
+            ("ws", " ", "default"),
+            ("ident", "d", "default"),
+        ];
+
+        assert_eq!(got.len(), expected.len());
+
+        for ((kind, value, mode), (e_kind, e_value, e_mode)) in got.iter().zip(expected) {
+            assert_eq!(
+                (kind.as_ref(), value.as_ref(), *mode),
+                (e_kind, e_value, e_mode)
+            );
+        }
+    }
+
+    #[test]
+    fn test_005_unknown_mode() {
+        let res = LexerBuilder::new()
+            .token("quote", '"')
+            .push_mode("strnig")
+            .mode("string")
+            .build::<NoCustomError>();
+
+        assert!(matches!(
+            res.err().unwrap().kind(),
+            ErrorKind::UnknownLexerMode(_)
+        ));
+    }
+
+    #[test]
+    fn test_006_skip_and_trivia() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", "\\s+")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("comment", "#[^\\n]*"))
+            .map(LexerBuilder::trivia)
+            .and_then(|builder| builder.regex("ident", "[a-z]+"))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let mut lexer = table.lex::<NoCustomError>("# doc\na b # end");
+        let tokens = lexer.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+        let got = tokens
+            .iter()
+            .map(|tok| {
+                (
+                    tok.value.as_ref(),
+                    tok.trivia
+                        .iter()
+                        .map(|t| t.value.as_ref())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(got, vec![("a", vec!["# doc"]), ("b", vec![])]);
+        assert_eq!(lexer.trailing_trivia()[0].value, "# end");
+    }
+
+    #[test]
+    fn test_007_streamed_input() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", "\\s+")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("word", "\\p{L}+"))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let src = "héllo\nwörld  ∑ok";
+        let input = Reader::new(BufReader::with_capacity(3, src.as_bytes()));
+
+        let streamed = table
+            .lex_input::<NoCustomError, _>(input)
+            .collect::<Vec<_>>();
+        let borrowed = table.lex::<NoCustomError>(src).collect::<Vec<_>>();
+
+        assert_eq!(streamed.len(), 3);
+
+        for (streamed, borrowed) in streamed.into_iter().zip(borrowed) {
+            match (streamed, borrowed) {
+                (Ok(streamed), Ok(borrowed)) => assert_eq!(streamed, borrowed),
+                (Err(streamed), Err(borrowed)) => assert_eq!(streamed.span(), borrowed.span()),
+                _ => panic!("the streamed and borrowed lexers differ"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_008_invalid_utf8_after_token() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("word", "[a-z]+")
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let bytes: &[u8] = &[b'a', b'b', 0xff];
+        let mut lexer = table.lex_input::<NoCustomError, _>(Reader::from_read(bytes));
+
+        assert_eq!(lexer.next().unwrap().unwrap().value, "ab");
+        assert!(matches!(
+            lexer.next().unwrap().unwrap_err().kind(),
+            ErrorKind::InvalidUtf8(2)
+        ));
+        assert!(lexer.next().is_none());
+    }
+
+    #[test]
+    fn test_009_bytes() {
+        let table = LexerBuilder::bytes()
+            .token("get", b"GET")
+            .token("sp", b' ')
+            .skip()
+            .token("num", ByteSet::from(b'0'..=b'9').into_graph().plus())
+            .build::<NoCustomError>()
+            .unwrap();
+
+        let mut lexer = table.lex_bytes::<NoCustomError>(b"GET 42\xff");
+
+        let get = lexer.next().unwrap().unwrap();
+        assert_eq!(
+            (get.kind.as_ref(), get.value.as_ref()),
+            ("get", &b"GET"[..])
+        );
+
+        let num = lexer.next().unwrap().unwrap();
+        assert_eq!((num.kind.as_ref(), num.value.as_ref()), ("num", &b"42"[..]));
+        assert_eq!(num.span.from.column, 4);
+
+        assert!(matches!(
+            lexer.next().unwrap().unwrap_err().kind(),
+            ErrorKind::UnexpectedSymbol { got, .. } if got == "\\xff"
+        ));
+    }
+
+    fn parse_int(value: &str) -> YalpResult<Payload, NoCustomError> {
+        value
+            .parse::<u8>()
+            .map(Payload::new)
+            .map_err(|_| YalpError::new(ErrorKind::Other(NoCustomError), None))
+    }
+
+    #[test]
+    fn test_010_callbacks() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", " +")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("int", "[0-9]+"))
+            .and_then(|builder| builder.callback(parse_int))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let tokens = table.lex::<NoCustomError>("12 300 7").collect::<Vec<_>>();
+
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[0].as_ref().unwrap().payload::<u8>(), Some(&12));
+        assert_eq!(
+            tokens[1].as_ref().unwrap_err().span().unwrap().from.column,
+            3
+        );
+        assert_eq!(tokens[2].as_ref().unwrap().payload::<u8>(), Some(&7));
+
+        // The payloads are shared by the clones, and left out of the comparisons.
+        let token = tokens[0].clone().unwrap();
+        let owned = token.clone().into_owned();
+        assert_eq!(owned.payload::<u8>(), Some(&12));
+        assert_eq!(owned, token);
+
+        // The tokens, and their payloads, can be sent to other threads.
+        let payload = std::thread::spawn(move || owned.payload::<u8>().copied())
+            .join()
+            .unwrap();
+        assert_eq!(payload, Some(12));
+    }
+
+    #[test]
+    fn test_011_recovery() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", " +")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("int", "[0-9]+"))
+            .and_then(|builder| builder.regex(",", ","))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let kinds = |recovery| {
+            let mut lexer = table.lex::<NoCustomError>("1 x?,2 $").recovery(recovery);
+
+            let tokens = lexer
+                .by_ref()
+                .map(|tok| tok.map(|tok| (tok.kind.into_owned(), tok.value.into_owned())))
+                .collect::<Result<Vec<_>, _>>();
+
+            (tokens, lexer.take_errors().len())
+        };
+
+        let (tokens, errors) = kinds(Recovery::resync());
+        assert_eq!(
+            tokens.unwrap(),
+            [
+                ("int", "1"),
+                (ERROR, "x?"),
+                (",", ","),
+                ("int", "2"),
+                (ERROR, "$")
+            ]
+            .map(|(kind, value)| (kind.to_string(), value.to_string()))
+        );
+        assert_eq!(errors, 2);
+
+        let (tokens, errors) = kinds(Recovery::Skip);
+        assert_eq!(tokens.unwrap().len(), 6);
+        assert_eq!(errors, 3);
+
+        let (tokens, errors) = kinds(Recovery::Stop);
+        assert!(tokens.is_err());
+        assert_eq!(errors, 0);
+    }
+
+    #[test]
+    fn test_012_sub_lexers() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", "\\s+")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("ident", "[a-z]+"))
+            .map(|builder| builder.nested_comment("comment", "/*", "*/").trivia())
+            .map(|builder| builder.raw_string("raw").long_bracket("long"))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let tokens = table
+            .lex::<NoCustomError>("a /* x /* y */\n */ b r#\"say \"hi\"\"# [==[ ]] ]==]")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let got = tokens
+            .iter()
+            .map(|tok| (tok.kind.as_ref(), tok.value.as_ref()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            got,
+            vec![
+                ("ident", "a"),
+                ("ident", "b"),
+                ("raw", "r#\"say \"hi\"\"#"),
+                ("long", "[==[ ]] ]==]"),
+            ]
+        );
+
+        let comment = &tokens[1].trivia[0];
+        assert_eq!(comment.value, "/* x /* y */\n */");
+        assert_eq!(comment.span.to.line, comment.span.from.line + 1);
+        assert_eq!(tokens[1].span.from.line, comment.span.to.line);
+    }
+
+    #[test]
+    fn test_013_unterminated_sub_lexer() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ident", "[a-z]+")
+            .map(|builder| builder.nested_comment("comment", "/*", "*/"))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let tokens = table
+            .lex::<NoCustomError>("a/* b /* c */")
+            .collect::<Vec<_>>();
+
+        assert_eq!(tokens.len(), 2);
+        assert!(matches!(
+            tokens[1].as_ref().unwrap_err().kind(),
+            ErrorKind::UnterminatedToken(kind) if kind == "comment"
+        ));
+
+        let tokens = table
+            .lex::<NoCustomError>("a/* b")
+            .recovery(Recovery::Skip)
+            .map(|tok| tok.map(|tok| (tok.kind.into_owned(), tok.value.into_owned())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            tokens,
+            [("ident", "a"), (ERROR, "/* b")]
+                .map(|(kind, value)| (kind.to_string(), value.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_014_keywords_and_interning() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", " +")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("ident", "[a-z]+"))
+            .map(|builder| builder.keywords(["if", "else", "let"]).intern())
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let mut lexer = table.lex::<NoCustomError>("let x if y else x");
+        let tokens = lexer.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+        let kinds = tokens
+            .iter()
+            .map(|tok| tok.kind.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ["let", "ident", "if", "ident", "else", "ident"]);
+
+        assert_eq!(tokens[0].keyword, table.keywords().find("let"));
+        assert_eq!(tokens[0].symbol, None);
+        assert_eq!(tokens[1].keyword, None);
+        assert_eq!(tokens[1].symbol, tokens[5].symbol);
+        assert_ne!(tokens[1].symbol, tokens[3].symbol);
+
+        let interner = lexer.take_interner();
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.resolve(tokens[3].symbol.unwrap()), Some("y"));
+    }
+
+    #[test]
+    fn test_015_from_compiled() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", " +")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("int", "[0-9]+"))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        // The callbacks are not compiled, they are given along the tokens.
+        let compiled = LexerTable::from_compiled(
+            table.iter_tokens().map(|(kind, change, role)| {
+                let callback = (kind == "int").then(|| TokenCallback::new(parse_int));
+                (kind, change, role, callback)
+            }),
+            table
+                .iter_automata()
+                .map(|(name, dfa)| (name, dfa.clone()))
+                .collect(),
+        );
+
+        let tokens = compiled
+            .lex::<NoCustomError>("1 23")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(tokens[0].value, "1");
+        assert_eq!(tokens[1].value, "23");
+        assert_eq!(tokens[1].payload::<u8>(), Some(&23));
+    }
+
+    #[test]
+    fn test_016_source_map() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", "\\s+")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("word", "\\p{L}+"))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let mut sources = SourceMap::new();
+        sources.add("empty", "");
+        let file = sources.add("words", "été\n  hiver");
+        let source = sources.get(file).unwrap();
+
+        let tokens = table
+            .lex::<NoCustomError>(source.source())
+            .file(file)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let hiver = tokens[1].span;
+        assert_eq!(hiver.file, Some(file));
+        assert_eq!(hiver.range(), 8..13);
+        assert_eq!(source.cursor(8), Some(hiver.from));
+        assert_eq!(sources.snippet(&hiver), Some("hiver"));
+        assert_eq!(sources.snippet(&tokens[0].span), Some("été"));
+    }
+
+    #[test]
+    fn test_017_contextual_keywords() {
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("ws", " +")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("ident", "[a-z]+"))
+            .map(|builder| builder.keywords(["if", "else"]))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let mut lexer = table.lex::<NoCustomError>("if if").contextual();
+
+        lexer.expect(&["ident"]);
+        let ident = lexer.next().unwrap().unwrap();
+        assert_eq!((ident.kind.as_ref(), ident.keyword), ("ident", None));
+
+        lexer.expect(&["if", "ident"]);
+        let keyword = lexer.next().unwrap().unwrap();
+        assert_eq!(keyword.kind, "if");
+        assert_eq!(keyword.keyword, table.keywords().find("if"));
+    }
+
+    #[test]
+    fn test_018_callback_errors() {
+        let builder = LexerBuilder::new().callback::<NoCustomError>(parse_int);
+        assert!(matches!(
+            builder.err().unwrap().kind(),
+            ErrorKind::NoTokenDeclared
+        ));
+
+        let table = LexerBuilder::new()
+            .regex::<NoCustomError>("int", "[0-9]+")
+            .and_then(|builder| builder.callback(parse_int))
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        // The lexer raises errors of another type than the callback.
+        let err = table.lex::<()>("12").next().unwrap().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnexpectedValueType(_)));
+        assert_eq!(err.span().unwrap().from.column, 0);
+    }
+}
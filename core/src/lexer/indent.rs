@@ -0,0 +1,348 @@
+//! Indentation-sensitive lexing.
+//!
+//! [Indentation] wraps a lexer, and emits the synthetic [NEWLINE],
+//! [INDENT] and [DEDENT] tokens from the lines and columns of the
+//! tokens' spans, so the wrapped lexer only has to skip whitespaces.
+//!
+//! The columns count every char as one, unless the source is given to
+//! [Indentation::tabs], which expands the tabs of the indentation.
+use std::collections::VecDeque;
+
+use crate::{span::Span, token::Token, ErrorKind, YalpError, YalpResult};
+
+use super::traits;
+
+/// Ends a logical line.
+pub const NEWLINE: &str = "<newline>";
+/// Opens an indented block.
+pub const INDENT: &str = "<indent>";
+/// Closes an indented block.
+pub const DEDENT: &str = "<dedent>";
+
+/// Emits the indentation tokens of the wrapped lexer's stream.
+///
+/// Lines are joined inside brackets, like in Python.
+pub struct Indentation<'stream, 'kind, L, Error> {
+    lexer: L,
+    /// The indentation columns of the opened blocks.
+    levels: Vec<usize>,
+    brackets: Vec<(&'kind str, &'kind str)>,
+    /// The number of opened brackets.
+    depth: usize,
+    /// The line the last token ended on.
+    line: Option<usize>,
+    pending: VecDeque<YalpResult<Token<'stream>, Error>>,
+    span: Span,
+    done: bool,
+    /// The source being lexed, and the width of its tabs.
+    tabs: Option<(&'stream str, usize)>,
+}
+
+impl<'stream, 'kind, L, Error> Indentation<'stream, 'kind, L, Error>
+where
+    L: traits::Lexer<Error, Token = Token<'stream>>,
+{
+    /// Wrap the lexer, with the brackets (), [] and {}.
+    pub fn new(lexer: L) -> Self {
+        Self {
+            lexer,
+            levels: vec![0],
+            brackets: vec![("(", ")"), ("[", "]"), ("{", "}")],
+            depth: 0,
+            line: None,
+            pending: VecDeque::default(),
+            span: Span::default(),
+            done: false,
+            tabs: None,
+        }
+    }
+
+    /// Expand the tabs of the indentation to the next multiple of the width,
+    /// reading the indentation from the source being lexed.
+    ///
+    /// The source must be the one the wrapped lexer reads, as the byte
+    /// offsets of the tokens' spans index into it.
+    pub fn tabs(mut self, source: &'stream str, width: usize) -> Self {
+        self.tabs = Some((source, width.max(1)));
+        self
+    }
+
+    /// Set the brackets, as pairs of opening and closing token kinds.
+    pub fn brackets(mut self, brackets: &[(&'kind str, &'kind str)]) -> Self {
+        self.brackets = brackets.to_vec();
+        self
+    }
+
+    /// Unwrap the lexer, dropping the indentation tokens not yet emitted.
+    pub fn into_inner(self) -> L {
+        self.lexer
+    }
+
+    fn synthetic(&mut self, kind: &'static str, span: Span) {
+        self.pending.push_back(Ok(Token::new(kind, "", span)));
+    }
+
+    /// Queue the token, preceded by the indentation tokens.
+    fn indent(&mut self, token: Token<'stream>) {
+        let at = Span::from(token.span.from).in_file(token.span.file);
+        let starts_line = self.line.is_none_or(|line| token.span.from.line > line);
+
+        if starts_line && self.depth == 0 {
+            if self.line.is_some() {
+                self.synthetic(NEWLINE, at);
+            }
+
+            let column = self.column(&token);
+
+            if column > self.current_level() {
+                self.levels.push(column);
+                self.synthetic(INDENT, at);
+            }
+
+            while column < self.current_level() {
+                self.levels.pop();
+                self.synthetic(DEDENT, at);
+            }
+
+            // The column opens a new level, so the next lines of the
+            // block are not reported again, and the blocks stay balanced.
+            if column != self.current_level() {
+                self.pending.push_back(Err(YalpError::new(
+                    ErrorKind::InconsistentDedent(column),
+                    Some(token.span),
+                )));
+
+                self.levels.push(column);
+                self.synthetic(INDENT, at);
+            }
+        }
+
+        let kind = token.kind.as_ref();
+
+        if self.brackets.iter().any(|(open, _)| *open == kind) {
+            self.depth += 1;
+        } else if self.brackets.iter().any(|(_, close)| *close == kind) {
+            self.depth = self.depth.saturating_sub(1);
+        }
+
+        self.line = Some(token.span.to.line);
+        self.pending.push_back(Ok(token));
+    }
+
+    /// Close the last logical line, and every opened block.
+    fn finish(&mut self) {
+        let at = Span::from(self.span.to).in_file(self.span.file);
+
+        if self.line.is_some() {
+            self.synthetic(NEWLINE, at);
+        }
+
+        while self.levels.len() > 1 {
+            self.levels.pop();
+            self.synthetic(DEDENT, at);
+        }
+    }
+
+    /// The indentation of the token starting a line.
+    ///
+    /// With tabs, the span's offset is a byte offset into the source,
+    /// at a char boundary since the token starts there.
+    fn column(&self, token: &Token<'stream>) -> usize {
+        let offset = token.span.from.offset;
+
+        let Some((indentation, width)) = self.tabs.and_then(|(source, width)| {
+            debug_assert!(
+                source.is_char_boundary(offset),
+                "the offset {offset} is not a char boundary of the source"
+            );
+
+            let line = source.get(..offset)?;
+            let start = line.rfind('\n').map_or(0, |at| at + 1);
+            Some((&line[start..], width))
+        }) else {
+            return token.span.from.column;
+        };
+
+        indentation.chars().fold(0, |column, ch| match ch {
+            '\t' => (column / width + 1) * width,
+            _ => column + 1,
+        })
+    }
+
+    fn current_level(&self) -> usize {
+        self.levels.last().copied().unwrap_or_default()
+    }
+}
+
+impl<'stream, 'kind, L, Error> traits::Lexer<Error> for Indentation<'stream, 'kind, L, Error>
+where
+    L: traits::Lexer<Error, Token = Token<'stream>>,
+{
+    type Token = Token<'stream>;
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn is_contextual(&self) -> bool {
+        self.lexer.is_contextual()
+    }
+
+    fn expect(&mut self, terminals: &[&str]) {
+        self.lexer.expect(terminals)
+    }
+}
+
+impl<'stream, 'kind, L, Error> Iterator for Indentation<'stream, 'kind, L, Error>
+where
+    L: traits::Lexer<Error, Token = Token<'stream>>,
+{
+    type Item = YalpResult<Token<'stream>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending.is_empty() && !self.done {
+            match self.lexer.next() {
+                Some(Ok(token)) => self.indent(token),
+                Some(Err(err)) => self.pending.push_back(Err(err)),
+                None => {
+                    self.done = true;
+                    self.finish();
+                }
+            }
+        }
+
+        self.pending.pop_front().inspect(|item| {
+            if let Ok(token) = item {
+                self.span = token.span;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        lexer::{LexerBuilder, LexerTable},
+        ErrorKind, NoCustomError,
+    };
+
+    use super::{Indentation, DEDENT, INDENT, NEWLINE};
+
+    fn table() -> LexerTable<'static> {
+        LexerBuilder::new()
+            .regex::<NoCustomError>("ws", "\\s+")
+            .map(LexerBuilder::skip)
+            .and_then(|builder| builder.regex("id", "[a-z]+"))
+            .and_then(|builder| builder.regex("(", "\\("))
+            .and_then(|builder| builder.regex(")", "\\)"))
+            .and_then(|builder| builder.regex(",", ","))
+            .and_then(LexerBuilder::build)
+            .unwrap()
+    }
+
+    fn kinds(input: &str) -> Vec<Result<String, ErrorKind<NoCustomError>>> {
+        let table = table();
+
+        Indentation::new(table.lex::<NoCustomError>(input))
+            .map(|tok| {
+                tok.map(|tok| tok.kind.into_owned())
+                    .map_err(|err| err.kind().clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_001_blocks() {
+        let got = kinds("a\n  b\n  c\nd\n");
+        let expected = [
+            "id", NEWLINE, INDENT, "id", NEWLINE, "id", NEWLINE, DEDENT, "id", NEWLINE,
+        ];
+
+        assert_eq!(got.len(), expected.len());
+
+        for (got, expected) in got.into_iter().zip(expected) {
+            assert_eq!(got.unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_002_brackets_join_lines() {
+        let got = kinds("f(a,\n    b)\ng")
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            got,
+            vec!["id", "(", "id", ",", "id", ")", NEWLINE, "id", NEWLINE]
+        );
+    }
+
+    #[test]
+    fn test_003_unclosed_blocks() {
+        let got = kinds("a\n  b\n    c")
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            got,
+            vec!["id", NEWLINE, INDENT, "id", NEWLINE, INDENT, "id", NEWLINE, DEDENT, DEDENT]
+        );
+    }
+
+    #[test]
+    fn test_004_inconsistent_dedent() {
+        let got = kinds("a\n    b\n  c");
+
+        assert!(got
+            .iter()
+            .any(|tok| matches!(tok, Err(ErrorKind::InconsistentDedent(2)))));
+    }
+
+    #[test]
+    fn test_006_stream_after_inconsistent_dedent() {
+        let got = kinds("a\n    b\n  c\n  d\ne\n");
+
+        let errors = got.iter().filter(|tok| tok.is_err()).count();
+        assert_eq!(errors, 1);
+
+        // The column of the faulty line opens a block, closed by the next dedent.
+        let got = got.into_iter().filter_map(Result::ok).collect::<Vec<_>>();
+
+        assert_eq!(
+            got,
+            vec![
+                "id", NEWLINE, INDENT, "id", NEWLINE, DEDENT, INDENT, "id", NEWLINE, "id", NEWLINE,
+                DEDENT, "id", NEWLINE
+            ]
+        );
+    }
+
+    #[test]
+    fn test_005_tabs() {
+        let input = "a\n\tb\n        c\n";
+        let table = table();
+
+        let got = Indentation::new(table.lex::<NoCustomError>(input))
+            .tabs(input, 8)
+            .map(|tok| tok.unwrap().kind)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            got,
+            vec!["id", NEWLINE, INDENT, "id", NEWLINE, "id", NEWLINE, DEDENT]
+        );
+
+        // Without the source, the tab is a single column.
+        let got = kinds(input)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            got,
+            vec!["id", NEWLINE, INDENT, "id", NEWLINE, INDENT, "id", NEWLINE, DEDENT, DEDENT]
+        );
+    }
+}
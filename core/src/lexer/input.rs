@@ -0,0 +1,481 @@
+//! The sources read by the lexers.
+//!
+//! An [Input] yields its units (chars, or bytes) with their byte offsets,
+//! and returns the value of a token from its byte range. In-memory sources
+//! ([Str], [Bytes]) borrow the values from the source, streaming sources
+//! ([Chars], [Reader]) only keep the characters which are still needed.
+use std::{
+    borrow::Cow,
+    fmt::Debug,
+    hash::Hash,
+    io::{BufRead, BufReader, Read},
+};
+
+use crate::{
+    dfa::{Alphabet, ByteSet, CharSet},
+    ErrorKind, YalpError, YalpResult,
+};
+
+/// The unit an input is read by.
+pub trait Unit: Copy + Eq + Debug {
+    /// The slices of units, the token values.
+    type Slice: ?Sized + ToOwned<Owned = Self::Owned> + AsRef<[u8]> + Hash + Eq + 'static;
+    /// The owned slices, the values of streamed tokens.
+    type Owned: Hash + Eq + Clone;
+    /// The sets of units, the token patterns.
+    type Set: Alphabet<Item = Self>;
+
+    /// The length of the unit, in bytes.
+    fn len_bytes(self) -> usize;
+
+    /// Whether the unit ends a line.
+    fn is_newline(self) -> bool;
+
+    /// Describe the unit in error messages.
+    fn describe(self) -> String;
+}
+
+impl Unit for char {
+    type Slice = str;
+    type Owned = String;
+    type Set = CharSet;
+
+    fn len_bytes(self) -> usize {
+        self.len_utf8()
+    }
+
+    fn is_newline(self) -> bool {
+        self == '\n'
+    }
+
+    fn describe(self) -> String {
+        self.to_string()
+    }
+}
+
+impl Unit for u8 {
+    type Slice = [u8];
+    type Owned = Vec<u8>;
+    type Set = ByteSet;
+
+    fn len_bytes(self) -> usize {
+        1
+    }
+
+    fn is_newline(self) -> bool {
+        self == b'\n'
+    }
+
+    fn describe(self) -> String {
+        self.escape_ascii().to_string()
+    }
+}
+
+/// The value of a token read from the input.
+pub type Value<'stream, U> = Cow<'stream, <U as Unit>::Slice>;
+
+pub trait Input<'stream> {
+    type Unit: Unit;
+
+    /// Returns the next unit, and its byte offset.
+    fn next_unit<Error>(&mut self) -> Option<YalpResult<(usize, Self::Unit), Error>>;
+
+    /// Returns the value between the byte offsets.
+    ///
+    /// The range must not start before the last released offset.
+    fn value(&self, from: usize, to: usize) -> Value<'stream, Self::Unit>;
+
+    /// The values before the offset will no longer be requested.
+    fn release(&mut self, _offset: usize) {}
+}
+
+/// An in-memory source, the values are slices of it.
+#[derive(Debug, Clone)]
+pub struct Str<'stream> {
+    src: &'stream str,
+    offset: usize,
+}
+
+impl<'stream> Str<'stream> {
+    pub fn new(src: &'stream str) -> Self {
+        Self { src, offset: 0 }
+    }
+
+    /// Read the bytes as UTF-8.
+    pub fn from_utf8<Error>(bytes: &'stream [u8]) -> YalpResult<Self, Error> {
+        std::str::from_utf8(bytes)
+            .map(Self::new)
+            .map_err(|err| YalpError::new(ErrorKind::InvalidUtf8(err.valid_up_to()), None))
+    }
+
+    pub fn as_str(&self) -> &'stream str {
+        self.src
+    }
+}
+
+impl<'stream> From<&'stream str> for Str<'stream> {
+    fn from(src: &'stream str) -> Self {
+        Self::new(src)
+    }
+}
+
+impl<'stream> Input<'stream> for Str<'stream> {
+    type Unit = char;
+
+    fn next_unit<Error>(&mut self) -> Option<YalpResult<(usize, char), Error>> {
+        let ch = self.src[self.offset..].chars().next()?;
+        let offset = self.offset;
+        self.offset += ch.len_utf8();
+        Some(Ok((offset, ch)))
+    }
+
+    fn value(&self, from: usize, to: usize) -> Value<'stream, char> {
+        Cow::Borrowed(&self.src[from..to])
+    }
+}
+
+/// An in-memory binary source, the values are slices of it.
+#[derive(Debug, Clone)]
+pub struct Bytes<'stream> {
+    src: &'stream [u8],
+    offset: usize,
+}
+
+impl<'stream> Bytes<'stream> {
+    pub fn new(src: &'stream [u8]) -> Self {
+        Self { src, offset: 0 }
+    }
+}
+
+impl<'stream> From<&'stream [u8]> for Bytes<'stream> {
+    fn from(src: &'stream [u8]) -> Self {
+        Self::new(src)
+    }
+}
+
+impl<'stream> Input<'stream> for Bytes<'stream> {
+    type Unit = u8;
+
+    fn next_unit<Error>(&mut self) -> Option<YalpResult<(usize, u8), Error>> {
+        let byte = *self.src.get(self.offset)?;
+        self.offset += 1;
+        Some(Ok((self.offset - 1, byte)))
+    }
+
+    fn value(&self, from: usize, to: usize) -> Value<'stream, u8> {
+        Cow::Borrowed(&self.src[from..to])
+    }
+}
+
+/// A source of characters, the values are owned.
+///
+/// The characters are buffered until they are released.
+#[derive(Debug, Clone)]
+pub struct Chars<I> {
+    iter: I,
+    /// The characters read since the last released offset.
+    buffer: String,
+    /// The byte offset of the buffer's start.
+    base: usize,
+}
+
+impl<I> Chars<I>
+where
+    I: Iterator<Item = char>,
+{
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            buffer: String::default(),
+            base: 0,
+        }
+    }
+}
+
+impl<'stream, I> Input<'stream> for Chars<I>
+where
+    I: Iterator<Item = char>,
+{
+    type Unit = char;
+
+    fn next_unit<Error>(&mut self) -> Option<YalpResult<(usize, char), Error>> {
+        let ch = self.iter.next()?;
+        let offset = self.base + self.buffer.len();
+        self.buffer.push(ch);
+        Some(Ok((offset, ch)))
+    }
+
+    fn value(&self, from: usize, to: usize) -> Value<'stream, char> {
+        Cow::Owned(self.buffer[from - self.base..to - self.base].to_owned())
+    }
+
+    fn release(&mut self, offset: usize) {
+        if offset > self.base {
+            self.buffer.drain(..offset - self.base);
+            self.base = offset;
+        }
+    }
+}
+
+/// A source reading UTF-8 from a [BufRead], the values are owned.
+///
+/// The input is decoded as it is read, and only the characters which
+/// are not released are kept in memory.
+#[derive(Debug)]
+pub struct Reader<R> {
+    reader: R,
+    /// The decoded characters since the last released offset.
+    window: String,
+    /// The byte offset of the window's start.
+    base: usize,
+    /// The position of the next character in the window.
+    read: usize,
+    /// The bytes of a sequence split between two reads.
+    partial: Vec<u8>,
+    /// The offset of an invalid sequence, once the previous characters are read.
+    invalid: Option<usize>,
+}
+
+impl<R> Reader<R>
+where
+    R: BufRead,
+{
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            window: String::default(),
+            base: 0,
+            read: 0,
+            partial: vec![],
+            invalid: None,
+        }
+    }
+
+    /// The number of bytes held in memory.
+    pub fn window_len(&self) -> usize {
+        self.window.len()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Decode the next chunk of the reader into the window.
+    ///
+    /// Returns false at the end of the reader.
+    fn refill<Error>(&mut self) -> YalpResult<bool, Error> {
+        let chunk = loop {
+            match self.reader.fill_buf() {
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                chunk => break chunk,
+            }
+        }
+        .map_err(|err| YalpError::new(ErrorKind::Io(err.to_string()), None))?;
+
+        let end = self.base + self.window.len();
+
+        if chunk.is_empty() {
+            if !self.partial.is_empty() {
+                // The stream ends in the middle of a sequence.
+                self.partial.clear();
+                self.invalid = Some(end);
+                return Ok(true);
+            }
+
+            return Ok(false);
+        }
+
+        let mut bytes = std::mem::take(&mut self.partial);
+        bytes.extend_from_slice(chunk);
+        let len = chunk.len();
+        self.reader.consume(len);
+
+        match std::str::from_utf8(&bytes) {
+            Ok(decoded) => self.window.push_str(decoded),
+            Err(err) => {
+                let (valid, rest) = bytes.split_at(err.valid_up_to());
+                self.window
+                    .push_str(std::str::from_utf8(valid).unwrap_or_default());
+
+                match err.error_len() {
+                    Some(_) => self.invalid = Some(end + valid.len()),
+                    None => self.partial = rest.to_vec(),
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+impl<R> Reader<BufReader<R>>
+where
+    R: Read,
+{
+    /// Buffer the reader.
+    pub fn from_read(reader: R) -> Self {
+        Self::new(BufReader::new(reader))
+    }
+}
+
+impl<'stream, R> Input<'stream> for Reader<R>
+where
+    R: BufRead,
+{
+    type Unit = char;
+
+    fn next_unit<Error>(&mut self) -> Option<YalpResult<(usize, char), Error>> {
+        while self.read >= self.window.len() {
+            if let Some(offset) = self.invalid {
+                return Some(Err(YalpError::new(ErrorKind::InvalidUtf8(offset), None)));
+            }
+
+            match self.refill() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        let ch = self.window[self.read..].chars().next()?;
+        let offset = self.base + self.read;
+        self.read += ch.len_utf8();
+
+        Some(Ok((offset, ch)))
+    }
+
+    fn value(&self, from: usize, to: usize) -> Value<'stream, char> {
+        Cow::Owned(self.window[from - self.base..to - self.base].to_owned())
+    }
+
+    fn release(&mut self, offset: usize) {
+        let len = offset.saturating_sub(self.base).min(self.read);
+        self.window.drain(..len);
+        self.read -= len;
+        self.base += len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::{ErrorKind, NoCustomError};
+
+    use std::io::{BufReader, Read};
+
+    use super::{Bytes, Chars, Input, Reader, Str};
+
+    #[test]
+    fn test_str_borrows() {
+        let mut input = Str::new("é+1");
+
+        assert_eq!(
+            input.next_unit::<NoCustomError>().unwrap().unwrap(),
+            (0, 'é')
+        );
+        assert_eq!(
+            input.next_unit::<NoCustomError>().unwrap().unwrap(),
+            (2, '+')
+        );
+        assert!(matches!(input.value(0, 3), Cow::Borrowed("é+")));
+    }
+
+    #[test]
+    fn test_chars_release() {
+        let mut input = Chars::new("ab cd".chars());
+
+        while input.next_unit::<NoCustomError>().is_some() {}
+        input.release(3);
+
+        assert_eq!(Input::<'static>::value(&input, 3, 5), "cd");
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        assert!(Str::from_utf8::<NoCustomError>(&[b'a', 0xff]).is_err());
+    }
+
+    #[test]
+    fn test_reader_decodes_across_refills() {
+        // Two bytes per read split every multi-byte sequence.
+        let src = "aé€𝄞b";
+        let mut input = Reader::new(BufReader::with_capacity(2, src.as_bytes()));
+        let mut chars = vec![];
+
+        while let Some(item) = input.next_unit::<NoCustomError>() {
+            chars.push(item.unwrap());
+        }
+
+        assert_eq!(chars, src.char_indices().collect::<Vec<_>>());
+        assert_eq!(Input::<'static>::value(&input, 1, 6), "é€");
+
+        input.release(6);
+        assert_eq!(input.window_len(), src.len() - 6);
+    }
+
+    #[test]
+    fn test_reader_invalid_utf8() {
+        let bytes: &[u8] = &[b'a', b'b', 0xc3, b'c'];
+        let mut input = Reader::new(BufReader::with_capacity(3, bytes));
+
+        assert_eq!(
+            input.next_unit::<NoCustomError>().unwrap().unwrap(),
+            (0, 'a')
+        );
+        assert_eq!(
+            input.next_unit::<NoCustomError>().unwrap().unwrap(),
+            (1, 'b')
+        );
+
+        let err = input.next_unit::<NoCustomError>().unwrap().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidUtf8(2)));
+    }
+
+    #[test]
+    fn test_reader_truncated_sequence() {
+        let bytes: &[u8] = &[b'a', 0xe2, 0x82];
+        let mut input = Reader::from_read(bytes);
+
+        assert!(input.next_unit::<NoCustomError>().unwrap().is_ok());
+        assert!(input.next_unit::<NoCustomError>().unwrap().is_err());
+    }
+
+    /// Interrupted before every read.
+    struct Interrupting<'a>(&'a [u8], bool);
+
+    impl Read for Interrupting<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.1 = !self.1;
+
+            if self.1 {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn test_reader_retries_interrupted_reads() {
+        let mut input = Reader::from_read(Interrupting(b"ab", false));
+        let chars = std::iter::from_fn(|| input.next_unit::<NoCustomError>())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(chars, [(0, 'a'), (1, 'b')]);
+    }
+
+    #[test]
+    fn test_bytes_borrows() {
+        let mut input = Bytes::new(b"\x00\xff");
+
+        assert_eq!(input.next_unit::<NoCustomError>().unwrap().unwrap(), (0, 0));
+        assert_eq!(
+            input.next_unit::<NoCustomError>().unwrap().unwrap(),
+            (1, 0xff)
+        );
+        assert!(input.next_unit::<NoCustomError>().is_none());
+        assert!(matches!(input.value(0, 2), Cow::Borrowed(b"\x00\xff")));
+    }
+}
@@ -0,0 +1,111 @@
+//! Identifier interning.
+//!
+//! Interned identifiers are compared, and hashed, as compact
+//! [Symbol] handles rather than strings.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+/// An interned value, its index in the interner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Interns slices of the input, `str` for text and `[u8]` for binary
+/// inputs.
+pub struct Interner<Slice = str>
+where
+    Slice: ?Sized + ToOwned,
+{
+    symbols: HashMap<Slice::Owned, Symbol>,
+    values: Vec<Slice::Owned>,
+}
+
+impl<Slice> Default for Interner<Slice>
+where
+    Slice: ?Sized + ToOwned,
+{
+    fn default() -> Self {
+        Self {
+            symbols: HashMap::default(),
+            values: vec![],
+        }
+    }
+}
+
+impl<Slice> Interner<Slice>
+where
+    Slice: ?Sized + ToOwned + Hash + Eq,
+    Slice::Owned: Hash + Eq + Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The symbol of the value, interned if it is new.
+    pub fn intern(&mut self, value: &Slice) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(value) {
+            return symbol;
+        }
+
+        let symbol = Symbol(self.values.len() as u32);
+        self.values.push(value.to_owned());
+        self.symbols.insert(value.to_owned(), symbol);
+        symbol
+    }
+
+    /// The symbol of the value, if it has been interned.
+    pub fn get(&self, value: &Slice) -> Option<Symbol> {
+        self.symbols.get(value).copied()
+    }
+
+    /// The value of the symbol.
+    pub fn resolve(&self, symbol: Symbol) -> Option<&Slice> {
+        self.values.get(symbol.index()).map(Borrow::borrow)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl<Slice> std::fmt::Debug for Interner<Slice>
+where
+    Slice: ?Sized + ToOwned,
+    Slice::Owned: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(&self.values).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interner;
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::<str>::new();
+
+        let foo = interner.intern("foo");
+        let bar = interner.intern("bar");
+
+        assert_ne!(foo, bar);
+        assert_eq!(interner.intern("foo"), foo);
+        assert_eq!(interner.get("bar"), Some(bar));
+        assert_eq!(interner.get("baz"), None);
+        assert_eq!(interner.resolve(bar), Some("bar"));
+        assert_eq!(interner.len(), 2);
+
+        let mut bytes = Interner::<[u8]>::new();
+        let symbol = bytes.intern(b"\x00\x01");
+        assert_eq!(bytes.resolve(symbol), Some(&b"\x00\x01"[..]));
+    }
+}
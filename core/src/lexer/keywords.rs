@@ -0,0 +1,194 @@
+//! Keyword tables.
+//!
+//! Declaring every keyword as a pattern bloats the automaton. Instead,
+//! an identifier token is checked against the keyword table after it
+//! is matched, and becomes the keyword's token.
+//!
+//! The table is a perfect hash (hash and displace): the keys are
+//! spread in buckets, and each bucket gets a seed which sends all its
+//! keys to distinct free slots. A lookup hashes the key twice, and
+//! compares it with a single candidate.
+
+/// A keyword, its index in the keyword table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Keyword(u32);
+
+impl Keyword {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A set of keywords, with a perfect hash lookup.
+#[derive(Debug, Clone, Default)]
+pub struct Keywords<'kind> {
+    keywords: Vec<&'kind str>,
+    /// The seed of each bucket.
+    seeds: Vec<u64>,
+    /// The keyword in each slot.
+    slots: Vec<Option<u32>>,
+}
+
+impl<'kind> Keywords<'kind> {
+    /// Build the table, duplicated keywords are only kept once.
+    pub fn new(keywords: impl IntoIterator<Item = &'kind str>) -> Self {
+        let mut unique: Vec<&'kind str> = vec![];
+
+        for keyword in keywords {
+            if !unique.contains(&keyword) {
+                unique.push(keyword);
+            }
+        }
+
+        let buckets = unique.len().div_ceil(4).max(1);
+        let mut content = vec![vec![]; buckets];
+
+        for (index, keyword) in unique.iter().enumerate() {
+            content[hash(keyword.as_bytes(), 0) as usize % buckets].push(index);
+        }
+
+        // At least a fifth of the slots are left free, and the table grows when
+        // a bucket cannot be placed.
+        let mut len = (unique.len() * 5 / 4).next_power_of_two();
+
+        let (seeds, slots) = loop {
+            match place(&unique, &content, len) {
+                Some(placed) => break placed,
+                None => len *= 2,
+            }
+        };
+
+        Self {
+            keywords: unique,
+            seeds,
+            slots,
+        }
+    }
+
+    /// The keyword spelled as the value, if any.
+    pub fn find(&self, value: impl AsRef<[u8]>) -> Option<Keyword> {
+        if self.keywords.is_empty() {
+            return None;
+        }
+
+        let value = value.as_ref();
+        let seed = self.seeds[hash(value, 0) as usize % self.seeds.len()];
+        let index = self.slots[hash(value, seed) as usize % self.slots.len()]?;
+
+        (self.keywords[index as usize].as_bytes() == value).then_some(Keyword(index))
+    }
+
+    /// The spelling of the keyword.
+    pub fn get(&self, keyword: Keyword) -> Option<&'kind str> {
+        self.keywords.get(keyword.index()).copied()
+    }
+
+    pub fn contains(&self, value: impl AsRef<[u8]>) -> bool {
+        self.find(value).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.keywords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &'kind str> + '_ {
+        self.keywords.iter().copied()
+    }
+}
+
+/// The most seeds tried for a bucket, before growing the table.
+const MAX_SEED: u64 = 1 << 12;
+
+/// Give each bucket a seed sending its keys to distinct free slots,
+/// among len slots.
+fn place(
+    keywords: &[&str],
+    content: &[Vec<usize>],
+    len: usize,
+) -> Option<(Vec<u64>, Vec<Option<u32>>)> {
+    let mut slots = vec![None; len];
+    let mut seeds = vec![0; content.len()];
+
+    // The largest buckets are placed first, while most slots are free.
+    let mut order = (0..content.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&bucket| std::cmp::Reverse(content[bucket].len()));
+
+    for bucket in order {
+        let keys = &content[bucket];
+
+        if keys.is_empty() {
+            continue;
+        }
+
+        let (seed, places) = (1..=MAX_SEED).find_map(|seed| {
+            let places = keys
+                .iter()
+                .map(|&index| hash(keywords[index].as_bytes(), seed) as usize % len)
+                .collect::<Vec<_>>();
+
+            let free = places
+                .iter()
+                .enumerate()
+                .all(|(i, &slot)| slots[slot].is_none() && !places[..i].contains(&slot));
+
+            free.then_some((seed, places))
+        })?;
+
+        seeds[bucket] = seed;
+
+        for (&index, slot) in keys.iter().zip(places) {
+            slots[slot] = Some(index as u32);
+        }
+    }
+
+    Some((seeds, slots))
+}
+
+/// FNV-1a, seeded, with a final mix so that the low bits depend on
+/// the whole hash.
+fn hash(bytes: &[u8], seed: u64) -> u64 {
+    let hash = bytes
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325 ^ seed, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+
+    let hash = (hash ^ (hash >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^ (hash >> 33)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Keywords;
+
+    #[test]
+    fn test_keywords() {
+        let spelled = (0..100).map(|i| format!("kw{i}")).collect::<Vec<_>>();
+        let keywords = Keywords::new(spelled.iter().map(String::as_str));
+
+        assert_eq!(keywords.len(), 100);
+        // A fifth of the slots, at least, are free.
+        assert!(keywords.slots.len() * 4 >= keywords.len() * 5);
+
+        for spelling in &spelled {
+            let keyword = keywords.find(spelling).unwrap();
+            assert_eq!(keywords.get(keyword), Some(spelling.as_str()));
+        }
+
+        assert!(!keywords.contains("kw100"));
+        assert!(!keywords.contains(""));
+        assert!(keywords.contains(b"kw7"));
+    }
+
+    #[test]
+    fn test_duplicates_and_empty() {
+        let keywords = Keywords::new(["if", "else", "if"]);
+
+        assert_eq!(keywords.iter().collect::<Vec<_>>(), ["if", "else"]);
+        assert!(!Keywords::new([]).contains("if"));
+    }
+}
@@ -1,8 +1,35 @@
-use std::marker::PhantomData;
-
-use crate::{token::Token, YalpResult};
-
-use self::traits::Lexer as _;
+use std::{fmt::Debug, marker::PhantomData};
+
+use crate::{
+    source::FileId,
+    span::{Cursor, NextColumn, NextLine},
+    token::{Payload, Token},
+    ErrorKind, NoCustomError, YalpError, YalpResult,
+};
+
+use self::input::Unit;
+
+pub use crate::span::Span;
+
+pub mod ast;
+pub mod builder;
+pub mod indent;
+pub mod input;
+pub mod interner;
+pub mod keywords;
+pub mod recovery;
+pub mod regex;
+pub mod sub;
+
+pub use builder::{
+    CompiledToken, DfaLexer, LexerBuilder, LexerTable, ModeChange, TokenCallback, TokenRole,
+    DEFAULT_MODE,
+};
+pub use indent::{Indentation, DEDENT, INDENT, NEWLINE};
+pub use interner::{Interner, Symbol};
+pub use keywords::{Keyword, Keywords};
+pub use recovery::{Recovery, ERROR};
+pub use sub::{Delimited, Nested, SubLexer};
 
 pub mod traits {
     use crate::{token::traits::Token, YalpResult};
@@ -14,25 +41,116 @@ pub mod traits {
         type Token: Token;
 
         fn span(&self) -> Span;
+
+        /// Whether the lexer depends on the terminals expected by the parser.
+        fn is_contextual(&self) -> bool {
+            false
+        }
+
+        /// Restrict the next token to the terminals expected by the parser.
+        ///
+        /// It is called before fetching each token, if the lexer is contextual.
+        fn expect(&mut self, _terminals: &[&str]) {}
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Action<'kind> {
+/// Computes the payload of a token from its value.
+///
+/// The errors it returns are raised by the lexer, at the token's span.
+pub type Callback<Error, Slice = str> = fn(&Slice) -> YalpResult<Payload, Error>;
+
+/// Builds the error raised by an [Action::Error], from the current value.
+pub type ErrorBuilder<Error, Slice = str> = fn(&Slice) -> ErrorKind<Error>;
+
+pub enum Action<'kind, Error = NoCustomError, U = char>
+where
+    U: Unit,
+{
     /// Reconsume the current character
     Reconsume,
     Consume,
     Write,
     Push(&'kind str),
     Merge(&'kind str, usize),
+    /// Enter the mode (a state index), saving the goto state.
+    PushMode(usize),
+    /// Return to the last saved state.
+    PopMode,
+    /// Compute the payload of the TOS fragment.
+    Call(Callback<Error, U::Slice>),
+    /// Raise an error, at the span of the consumed units.
+    Error(ErrorBuilder<Error, U::Slice>),
+}
+
+impl<'kind, Error, U> Clone for Action<'kind, Error, U>
+where
+    U: Unit,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'kind, Error, U> Copy for Action<'kind, Error, U> where U: Unit {}
+
+impl<'kind, Error, U> Debug for Action<'kind, Error, U>
+where
+    U: Unit,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Reconsume => f.write_str("Reconsume"),
+            Action::Consume => f.write_str("Consume"),
+            Action::Write => f.write_str("Write"),
+            Action::Push(kind) => f.debug_tuple("Push").field(kind).finish(),
+            Action::Merge(kind, n) => f.debug_tuple("Merge").field(kind).field(n).finish(),
+            Action::PushMode(mode) => f.debug_tuple("PushMode").field(mode).finish(),
+            Action::PopMode => f.write_str("PopMode"),
+            Action::Call(callback) => f
+                .debug_tuple("Call")
+                .field(&(*callback as *const ()))
+                .finish(),
+            Action::Error(builder) => f
+                .debug_tuple("Error")
+                .field(&(*builder as *const ()))
+                .finish(),
+        }
+    }
 }
-#[derive(Debug, Default)]
-pub struct ActionSequence<'kind> {
-    actions: Vec<Action<'kind>>,
+
+pub struct ActionSequence<'kind, Error = NoCustomError, U = char>
+where
+    U: Unit,
+{
+    actions: Vec<Action<'kind, Error, U>>,
     goto: usize,
 }
 
-impl<'kind> ActionSequence<'kind> {
+impl<'kind, Error, U> Debug for ActionSequence<'kind, Error, U>
+where
+    U: Unit,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActionSequence")
+            .field("actions", &self.actions)
+            .field("goto", &self.goto)
+            .finish()
+    }
+}
+
+impl<'kind, Error, U> Default for ActionSequence<'kind, Error, U>
+where
+    U: Unit,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<'kind, Error, U> ActionSequence<'kind, Error, U>
+where
+    U: Unit,
+{
     pub fn new(goto: usize) -> Self {
         Self {
             actions: vec![],
@@ -40,7 +158,7 @@ impl<'kind> ActionSequence<'kind> {
         }
     }
 
-    pub fn act(mut self, action: Action<'kind>) -> Self {
+    pub fn act(mut self, action: Action<'kind, Error, U>) -> Self {
         self.actions.push(action);
         self
     }
@@ -64,143 +182,449 @@ impl<'kind> ActionSequence<'kind> {
     pub fn merge(self, kind: &'kind str, n: usize) -> Self {
         self.act(Action::Merge(kind, n))
     }
+
+    pub fn push_mode(self, mode: usize) -> Self {
+        self.act(Action::PushMode(mode))
+    }
+
+    pub fn pop_mode(self) -> Self {
+        self.act(Action::PopMode)
+    }
+
+    pub fn call(self, callback: Callback<Error, U::Slice>) -> Self {
+        self.act(Action::Call(callback))
+    }
+
+    pub fn error(self, error: ErrorBuilder<Error, U::Slice>) -> Self {
+        self.act(Action::Error(error))
+    }
 }
 
-impl<'kind> IntoIterator for ActionSequence<'kind> {
-    type Item = Action<'kind>;
+impl<'kind, Error, U> IntoIterator for ActionSequence<'kind, Error, U>
+where
+    U: Unit,
+{
+    type Item = Action<'kind, Error, U>;
 
-    type IntoIter = <Vec<Action<'kind>> as IntoIterator>::IntoIter;
+    type IntoIter = <Vec<Action<'kind, Error, U>> as IntoIterator>::IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
         self.actions.into_iter()
     }
 }
 
-pub type State<'kind, Error> = fn(char) -> YalpResult<ActionSequence<'kind>, Error>;
+/// A state of a hand-written lexer, reading chars or bytes.
+pub type State<'kind, Error, U = char> =
+    fn(U) -> YalpResult<ActionSequence<'kind, Error, U>, Error>;
+
+/// The slices of units read from an input.
+pub type SliceOf<'stream, I> = <<I as input::Input<'stream>>::Unit as Unit>::Slice;
+
+/// The tokens read from an input.
+pub type InputToken<'stream, I> = Token<'stream, SliceOf<'stream, I>>;
+
+/// A position in the input.
+#[derive(Debug, Default, Clone, Copy)]
+struct Position {
+    /// The byte offset, as returned by the input.
+    offset: usize,
+    cursor: Cursor,
+}
+
+impl Position {
+    /// The position after the unit.
+    fn advance(mut self, unit: impl Unit) -> Self {
+        self.offset += unit.len_bytes();
+        self.cursor.offset = self.offset;
+
+        if unit.is_newline() {
+            self.cursor += NextLine;
+        } else {
+            self.cursor += NextColumn;
+        }
+
+        self
+    }
+}
+
+/// A token, and the positions it spans.
+struct Fragment<Token> {
+    from: Position,
+    to: Position,
+    token: Token,
+}
 
-pub struct Lexer<'kind, 'state, Stream, Error>
+/// A lexer driven by hand-written states.
+///
+/// The token values are read from the [Input](input::Input), they are
+/// borrowed from in-memory sources.
+pub struct Lexer<'kind, 'state, 'stream, Input, Error>
 where
-    Stream: Iterator<Item = char>,
+    Input: input::Input<'stream>,
 {
     state: usize,
-    states: &'state [State<'kind, Error>],
+    states: &'state [State<'kind, Error, Input::Unit>],
+    /// The states to return to when a mode is popped, and the modes'
+    /// entry states.
+    modes: Vec<(usize, usize)>,
+    recovery: Recovery<Input::Unit>,
+    /// The errors recovered from.
+    errors: Vec<YalpError<Error>>,
+    /// The span of the last written token.
     span: Span,
-    reconsume: Option<char>,
-    /// The current token's buffer
-    buffer: String,
+    /// The source the spans are located in.
+    file: Option<FileId>,
+    /// The position of the current unit.
+    current: Position,
+    /// The position after the last read unit.
+    position: Position,
+    reconsume: Option<(Position, Input::Unit)>,
+    /// The positions of the current token's consumed units.
+    consumed: Option<(Position, Position)>,
     /// Fragmented tokens are intermediate results for complex tokenization
-    fragments: Vec<Token<'kind>>,
-    stream: Stream,
+    fragments: Vec<Fragment<InputToken<'stream, Input>>>,
+    input: Input,
+    /// The input failed, and is not read anymore.
+    failed: bool,
     _phantom: PhantomData<(&'kind (), Error)>,
 }
 
-impl<'kind, 'state, Stream, Error> traits::Lexer<Error> for Lexer<'kind, 'state, Stream, Error>
+impl<'kind, 'state, 'stream, Input, Error> traits::Lexer<Error>
+    for Lexer<'kind, 'state, 'stream, Input, Error>
 where
-    Stream: Iterator<Item = char>,
+    'kind: 'stream,
+    Input: input::Input<'stream>,
 {
-    type Token = Token<'kind>;
+    type Token = InputToken<'stream, Input>;
 
     fn span(&self) -> Span {
         self.span
     }
 }
 
-impl<'kind, 'state, Stream, Error> Lexer<'kind, 'state, Stream, Error>
+impl<'kind, 'state, 'stream, Input, Error> Lexer<'kind, 'state, 'stream, Input, Error>
 where
-    Stream: Iterator<Item = char>,
+    'kind: 'stream,
+    Input: input::Input<'stream>,
 {
-    pub fn new(states: &'state [State<'kind, Error>], stream: Stream) -> Self {
+    pub fn new(states: &'state [State<'kind, Error, Input::Unit>], input: Input) -> Self {
         Self {
             state: 0,
             states,
-            stream,
-            buffer: String::default(),
+            modes: vec![],
+            recovery: Recovery::default(),
+            errors: vec![],
+            input,
             reconsume: None,
             span: Span::default(),
+            file: None,
+            current: Position::default(),
+            position: Position::default(),
+            consumed: None,
             fragments: vec![],
+            failed: false,
             _phantom: PhantomData,
         }
     }
 
     /// Push the current buffer as a fragment
     fn push(&mut self, kind: &'kind str) {
-        let token = Token::new(kind, self.take(), self.span(), vec![]);
+        let (from, to) = self.take();
+        let token = Token::new(
+            kind,
+            self.input.value(from.offset, to.offset),
+            self.span_between(from, to),
+        );
+        self.fragments.push(Fragment { from, to, token });
     }
 
-    /// Merge the n last fragments on the stack
+    /// Merge the n last fragments on the stack, and the current buffer.
     fn merge(&mut self, kind: &'kind str, n: usize) {
-        let consume = self.fragments.len().saturating_sub(n);
+        let consumed = self.consumed.take();
+        let first = self.fragments.len().saturating_sub(n);
+        let merged = self.fragments.drain(first..).collect::<Vec<_>>();
+
+        let from = merged
+            .first()
+            .map(|fragment| fragment.from)
+            .or(consumed.map(|(from, _)| from))
+            .unwrap_or(self.current);
+
+        let to = consumed
+            .map(|(_, to)| to)
+            .or(merged.last().map(|fragment| fragment.to))
+            .unwrap_or(from);
+
         let token = Token::new(
             kind,
-            self.take(),
-            self.span(),
-            self.fragments.drain(consume..).collect(),
+            self.input.value(from.offset, to.offset),
+            self.span_between(from, to),
         );
+        self.fragments.push(Fragment { from, to, token });
     }
 
     /// Write the TOS fragment in the output stream.
-    fn write(&mut self) -> Token<'kind> {
-        self.fragments.pop().unwrap()
+    fn write(&mut self) -> Option<InputToken<'stream, Input>> {
+        let token = self.fragments.pop()?.token;
+        self.span = token.span;
+
+        // Only the pending fragments, and the current unit, can still be read.
+        let retained = self
+            .fragments
+            .first()
+            .map(|fragment| fragment.from)
+            .or(self.consumed.map(|(from, _)| from))
+            .unwrap_or(self.current);
+
+        self.input.release(retained.offset);
+
+        Some(token)
     }
 
-    fn next_char(&mut self) -> Option<char> {
-        if self.reconsume.is_some() {
-            let char = self.reconsume.unwrap();
-            self.reconsume = None;
-            return Some(char);
+    fn next_unit(&mut self) -> Option<YalpResult<Input::Unit, Error>> {
+        if let Some((position, unit)) = self.reconsume.take() {
+            self.current = position;
+            return Some(Ok(unit));
         }
 
-        self.stream.next().inspect(|&ch| {
-            if ch == '\n' {
-                self.span += NextLine;
-            } else {
-                self.span += NextColumn;
+        if self.failed {
+            return None;
+        }
+
+        let (offset, unit) = match self.input.next_unit()? {
+            Ok(next) => next,
+            Err(mut err) => {
+                self.failed = true;
+                err.span = Some(self.span_between(self.position, self.position));
+                return Some(Err(err));
             }
-        })
+        };
+
+        self.current = Position {
+            offset,
+            cursor: Cursor {
+                offset,
+                ..self.position.cursor
+            },
+        };
+        self.position = self.current.advance(unit);
+
+        Some(Ok(unit))
+    }
+
+    /// Locate the spans in the file of a [SourceMap](crate::source::SourceMap).
+    pub fn file(mut self, file: FileId) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    /// Set the policy applied to the lexing errors.
+    pub fn recovery(mut self, recovery: Recovery<Input::Unit>) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    /// The errors recovered from so far.
+    pub fn errors(&self) -> &[YalpError<Error>] {
+        &self.errors
     }
 
-    pub fn reconsume(&mut self, ch: char) {
-        self.reconsume = Some(ch);
+    pub fn take_errors(&mut self) -> Vec<YalpError<Error>> {
+        std::mem::take(&mut self.errors)
     }
 
-    pub fn consume(&mut self, ch: char) {
-        self.buffer.push(ch)
+    /// Enter the mode, the current state is restored when it is popped.
+    pub fn push_mode(&mut self, mode: usize) {
+        self.modes.push((self.state, mode));
+        self.state = mode;
     }
 
-    fn take(&mut self) -> String {
-        std::mem::take(&mut self.buffer)
+    /// Return to the state saved by the last pushed mode.
+    pub fn pop_mode(&mut self) {
+        if let Some((state, _)) = self.modes.pop() {
+            self.state = state;
+        }
+    }
+
+    /// The entry state of the current mode.
+    fn root_state(&self) -> usize {
+        self.modes.last().map_or(0, |(_, mode)| *mode)
+    }
+
+    /// Apply the recovery policy to the error raised on the unit.
+    ///
+    /// The bad input, from the current token's first consumed unit to
+    /// the current unit, is written as an [ERROR] token.
+    fn recover(
+        &mut self,
+        err: YalpError<Error>,
+        unit: Input::Unit,
+    ) -> Option<YalpResult<InputToken<'stream, Input>, Error>> {
+        let sync = match self.recovery {
+            Recovery::Stop => return Some(Err(err)),
+            Recovery::Skip => None,
+            Recovery::Resync(sync) => Some(sync),
+        };
+
+        self.errors.push(err);
+
+        let from = self.consumed.take().map_or(self.current, |(from, _)| from);
+        let mut to = self.current.advance(unit);
+        self.reconsume = None;
+
+        if let Some(sync) = sync {
+            while let Some(next) = self.next_unit() {
+                match next {
+                    Ok(unit) if sync(unit) => {
+                        self.reconsume(unit);
+                        break;
+                    }
+                    Ok(unit) => to = self.current.advance(unit),
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+        }
+
+        self.state = self.root_state();
+
+        let token = Token::new(
+            ERROR,
+            self.input.value(from.offset, to.offset),
+            self.span_between(from, to),
+        );
+        self.fragments.push(Fragment { from, to, token });
+        self.write().map(Ok)
+    }
+
+    pub fn reconsume(&mut self, unit: Input::Unit) {
+        self.reconsume = Some((self.current, unit));
+    }
+
+    /// Add the unit to the current token.
+    ///
+    /// The token's value spans from its first to its last consumed
+    /// units.
+    pub fn consume(&mut self, unit: Input::Unit) {
+        let from = self.consumed.map_or(self.current, |(from, _)| from);
+        self.consumed = Some((from, self.current.advance(unit)));
+    }
+
+    /// Compute the payload of the TOS fragment.
+    fn call(
+        &mut self,
+        callback: Callback<Error, SliceOf<'stream, Input>>,
+    ) -> YalpResult<(), Error> {
+        let Some(fragment) = self.fragments.last_mut() else {
+            return Ok(());
+        };
+
+        let span = fragment.token.span;
+        let payload = callback(&*fragment.token.value).map_err(|mut err| {
+            err.span = Some(span);
+            err
+        })?;
+
+        fragment.token.payload = Some(payload);
+        Ok(())
+    }
+
+    /// The error raised at the consumed units, or at the current one.
+    fn raise(&mut self, error: ErrorBuilder<Error, SliceOf<'stream, Input>>) -> YalpError<Error> {
+        let (from, to) = self.consumed.unwrap_or((self.current, self.position));
+        let value = self.input.value(from.offset, to.offset);
+
+        YalpError::new(error(&*value), Some(self.span_between(from, to)))
+    }
+
+    fn span_between(&self, from: Position, to: Position) -> Span {
+        Span::new(from.cursor, to.cursor).in_file(self.file)
+    }
+
+    /// Take the positions of the current token.
+    fn take(&mut self) -> (Position, Position) {
+        self.consumed.take().unwrap_or((self.current, self.current))
     }
 }
 
-impl<'kind, 'state, Stream, Error> Iterator for Lexer<'kind, 'state, Stream, Error>
+impl<'kind, 'state, 'stream, Error> Lexer<'kind, 'state, 'stream, input::Str<'stream>, Error>
 where
-    Stream: Iterator<Item = char>,
+    'kind: 'stream,
 {
-    type Item = YalpResult<Token<'kind>, Error>;
+    /// Lex the string, the token values are slices of it.
+    pub fn from_source(states: &'state [State<'kind, Error>], src: &'stream str) -> Self {
+        Self::new(states, input::Str::new(src))
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let state = self.states[self.state];
+impl<'kind, 'state, 'stream, Error> Lexer<'kind, 'state, 'stream, input::Bytes<'stream>, Error>
+where
+    'kind: 'stream,
+{
+    /// Lex the bytes, the token values are slices of them.
+    pub fn from_bytes(states: &'state [State<'kind, Error, u8>], src: &'stream [u8]) -> Self {
+        Self::new(states, input::Bytes::new(src))
+    }
+}
 
-        while let Some(ch) = self.next_char() {
-            let action_result = state(ch).map_err(|mut err| {
-                err.span = Some(self.span());
-                err
-            });
+impl<'kind, 'state, 'stream, Stream, Error>
+    Lexer<'kind, 'state, 'stream, input::Chars<Stream>, Error>
+where
+    'kind: 'stream,
+    Stream: Iterator<Item = char>,
+{
+    /// Lex the characters, the token values are owned.
+    pub fn from_chars(states: &'state [State<'kind, Error>], stream: Stream) -> Self {
+        Self::new(states, input::Chars::new(stream))
+    }
+}
 
-            if action_result.is_err() {
-                return Some(Err(action_result.unwrap_err()));
-            }
+impl<'kind, 'state, 'stream, Input, Error> Iterator for Lexer<'kind, 'state, 'stream, Input, Error>
+where
+    'kind: 'stream,
+    Input: input::Input<'stream>,
+{
+    type Item = YalpResult<InputToken<'stream, Input>, Error>;
 
-            let seq = action_result.unwrap_or_else(|_| unreachable!());
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(next) = self.next_unit() {
+            let unit = match next {
+                Ok(unit) => unit,
+                Err(err) => return Some(Err(err)),
+            };
+
+            let state = self.states[self.state];
+            let seq = match state(unit) {
+                Ok(seq) => seq,
+                Err(mut err) => {
+                    err.span = Some(self.span_between(self.current, self.current.advance(unit)));
+                    return self.recover(err, unit);
+                }
+            };
             self.state = seq.goto;
 
             for action in seq {
                 match action {
-                    Action::Reconsume => self.reconsume(ch),
-                    Action::Consume => self.consume(ch),
-                    Action::Write => return self.fragments.pop().map(|f| Ok(f)),
+                    Action::Reconsume => self.reconsume(unit),
+                    Action::Consume => self.consume(unit),
+                    Action::Write => return self.write().map(Ok),
                     Action::Push(kind) => self.push(kind),
                     Action::Merge(kind, n) => self.merge(kind, n),
+                    Action::PushMode(mode) => self.push_mode(mode),
+                    Action::PopMode => self.pop_mode(),
+                    Action::Call(callback) => {
+                        if let Err(err) = self.call(callback) {
+                            // The token is kept, without its payload.
+                            if !self.recovery.is_enabled() {
+                                return Some(Err(err));
+                            }
+
+                            self.errors.push(err);
+                        }
+                    }
+                    Action::Error(error) => {
+                        let err = self.raise(error);
+                        return self.recover(err, unit);
+                    }
                 }
             }
         }
@@ -213,7 +637,10 @@ where
 pub mod fixtures {
     use crate::{ActionSequence, ErrorKind, NoCustomError, YalpError, YalpResult};
 
-    use super::{Action, Lexer, State};
+    use super::{
+        input::{Chars, Input, Str},
+        Lexer, State,
+    };
 
     fn lr0_root_state(ch: char) -> YalpResult<ActionSequence<'static>, NoCustomError> {
         match ch {
@@ -234,11 +661,26 @@ pub mod fixtures {
         lr0_root_state,
     ];
 
-    pub fn lexer_fixture_lr0<I>(iter: I) -> Lexer<'static, 'static, I, NoCustomError>
+    pub fn lexer_fixture_lr0<I>(
+        iter: I,
+    ) -> Lexer<'static, 'static, 'static, Chars<I>, NoCustomError>
     where
         I: Iterator<Item = char>,
     {
-        Lexer::new(LR0_LEXER_STATES, iter)
+        Lexer::from_chars(LR0_LEXER_STATES, iter)
+    }
+
+    pub fn lexer_fixture_lr0_str(src: &str) -> Lexer<'static, 'static, '_, Str<'_>, NoCustomError> {
+        Lexer::from_source(LR0_LEXER_STATES, src)
+    }
+
+    pub fn lexer_fixture_lr0_input<'stream, I>(
+        input: I,
+    ) -> Lexer<'static, 'static, 'stream, I, NoCustomError>
+    where
+        I: Input<'stream, Unit = char>,
+    {
+        Lexer::new(LR0_LEXER_STATES, input)
     }
 
     fn lr1_root_state(ch: char) -> YalpResult<ActionSequence<'static>, NoCustomError> {
@@ -261,32 +703,234 @@ pub mod fixtures {
         lr1_root_state,
     ];
 
-    pub fn lexer_fixture_lr1<I>(iter: I) -> Lexer<'static, 'static, I, NoCustomError>
+    pub fn lexer_fixture_lr1<I>(
+        iter: I,
+    ) -> Lexer<'static, 'static, 'static, Chars<I>, NoCustomError>
     where
         I: Iterator<Item = char>,
     {
-        Lexer::new(LR1_LEXER_STATES, iter)
+        Lexer::from_chars(LR1_LEXER_STATES, iter)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{lexer::Span, token::Token};
-
-    use super::fixtures::lexer_fixture_lr0;
+    use std::borrow::Cow;
+
+    use crate::{
+        lexer::Span,
+        span::Cursor,
+        token::{Payload, Token},
+        ActionSequence, ErrorKind, NoCustomError, YalpError, YalpResult,
+    };
+
+    use super::{
+        fixtures::{lexer_fixture_lr0, lexer_fixture_lr0_input, lexer_fixture_lr0_str},
+        input::{Reader, Unit as _},
+        Lexer, Recovery, State, ERROR,
+    };
+
+    fn at(column: usize) -> Span {
+        Span::new(
+            Cursor::new(1, column, column),
+            Cursor::new(1, column + 1, column + 1),
+        )
+    }
 
     #[test]
     fn test_lexer() {
         let lexer = lexer_fixture_lr0("1 + 1 * 0".chars());
         let tokens = lexer.collect::<Result<Vec<_>, _>>().unwrap();
         let expected_tokens = vec![
-            Token::new("1", "1", Span::new(1, 1), vec![]),
-            Token::new("+", "+", Span::new(1, 3), vec![]),
-            Token::new("1", "1", Span::new(1, 5), vec![]),
-            Token::new("*", "*", Span::new(1, 7), vec![]),
-            Token::new("0", "0", Span::new(1, 9), vec![]),
+            Token::new("1", "1", at(0)),
+            Token::new("+", "+", at(2)),
+            Token::new("1", "1", at(4)),
+            Token::new("*", "*", at(6)),
+            Token::new("0", "0", at(8)),
         ];
 
         assert_eq!(tokens, expected_tokens);
     }
+
+    #[test]
+    fn test_lexer_borrows_from_str() {
+        let src = String::from("1 + 1 * 0");
+        let tokens = lexer_fixture_lr0_str(&src)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(tokens.len(), 5);
+        assert!(tokens
+            .iter()
+            .all(|tok| matches!(tok.value, Cow::Borrowed(_))));
+        assert_eq!(tokens[4].value, "0");
+        assert_eq!(tokens[4].span, at(8));
+    }
+
+    #[test]
+    fn test_lexer_reads_stream() {
+        let src = "1 + 1 * 0";
+        let input = Reader::new(std::io::BufReader::with_capacity(1, src.as_bytes()));
+
+        let streamed = lexer_fixture_lr0_input(input)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let borrowed = lexer_fixture_lr0_str(src)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(streamed, borrowed);
+    }
+
+    /// Fails on every read.
+    struct Broken;
+
+    impl std::io::Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    #[test]
+    fn test_lexer_stops_on_input_errors() {
+        let mut lexer = lexer_fixture_lr0_input(Reader::from_read(Broken));
+
+        assert!(matches!(lexer.next(), Some(Err(_))));
+        assert!(lexer.next().is_none());
+    }
+
+    fn digits_state(
+        byte: u8,
+    ) -> YalpResult<ActionSequence<'static, NoCustomError, u8>, NoCustomError> {
+        match byte {
+            b'0'..=b'9' => Ok(ActionSequence::new(1).consume()),
+            b',' => Ok(ActionSequence::new(0).consume().push(",").write()),
+            _ => Err(YalpError::new(
+                ErrorKind::unexpected_symbol(&byte.describe(), vec!["0-9", ","]),
+                None,
+            )),
+        }
+    }
+
+    fn number_state(
+        byte: u8,
+    ) -> YalpResult<ActionSequence<'static, NoCustomError, u8>, NoCustomError> {
+        match byte {
+            b'0'..=b'9' => Ok(ActionSequence::new(1).consume()),
+            _ => Ok(ActionSequence::new(0).reconsume().push("num").write()),
+        }
+    }
+
+    static BYTE_STATES: &[State<NoCustomError, u8>] = &[digits_state, number_state];
+
+    #[test]
+    fn test_lexer_reads_bytes() {
+        let tokens = Lexer::from_bytes(BYTE_STATES, b"12,345,\xff").collect::<Vec<_>>();
+
+        let values = tokens[..4]
+            .iter()
+            .map(|tok| {
+                let tok = tok.as_ref().unwrap();
+                (tok.kind.as_ref(), tok.value.as_ref())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            values,
+            vec![
+                ("num", &b"12"[..]),
+                (",", &b","[..]),
+                ("num", &b"345"[..]),
+                (",", &b","[..]),
+            ]
+        );
+
+        let err = tokens[4].as_ref().unwrap_err();
+        assert_eq!(err.span().unwrap().from.column, 7);
+    }
+
+    fn parse_int(value: &str) -> YalpResult<Payload, NoCustomError> {
+        value
+            .parse::<i64>()
+            .map(Payload::new)
+            .map_err(|_| YalpError::new(ErrorKind::Other(NoCustomError), None))
+    }
+
+    fn int_root_state(ch: char) -> YalpResult<ActionSequence<'static>, NoCustomError> {
+        match ch {
+            '0'..='9' => Ok(ActionSequence::new(1).consume()),
+            ' ' => Ok(ActionSequence::new(0)),
+            _ => Ok(ActionSequence::new(0)
+                .consume()
+                .error(|value| ErrorKind::unexpected_symbol(value, ["0-9", " "]))),
+        }
+    }
+
+    fn int_state(ch: char) -> YalpResult<ActionSequence<'static>, NoCustomError> {
+        match ch {
+            '0'..='9' => Ok(ActionSequence::new(1).consume()),
+            _ => Ok(ActionSequence::new(0)
+                .reconsume()
+                .push("int")
+                .call(parse_int)
+                .write()),
+        }
+    }
+
+    static INT_STATES: &[State<NoCustomError>] = &[int_root_state, int_state];
+
+    #[test]
+    fn test_lexer_callbacks_and_errors() {
+        let mut lexer = Lexer::from_source(INT_STATES, "12 7!");
+
+        let int = lexer.next().unwrap().unwrap();
+        assert_eq!(int.payload::<i64>(), Some(&12));
+
+        let int = lexer.next().unwrap().unwrap();
+        assert_eq!(int.payload::<i64>(), Some(&7));
+        assert_eq!(int.payload::<u8>(), None);
+
+        let err = lexer.next().unwrap().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnexpectedSymbol { got, .. } if got == "!"));
+        assert_eq!(err.span(), Some(at(4)));
+    }
+
+    #[test]
+    fn test_lexer_recovery() {
+        let tokens = |recovery| {
+            Lexer::from_source(INT_STATES, "1 a!b 2 c")
+                .recovery(recovery)
+                .map(|tok| tok.map(|tok| (tok.kind.into_owned(), tok.value.into_owned())))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(
+            tokens(Recovery::Skip).unwrap(),
+            vec![
+                ("int".to_string(), "1".to_string()),
+                (ERROR.to_string(), "a".to_string()),
+                (ERROR.to_string(), "!".to_string()),
+                (ERROR.to_string(), "b".to_string()),
+                ("int".to_string(), "2".to_string()),
+                (ERROR.to_string(), "c".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            tokens(Recovery::resync()).unwrap(),
+            vec![
+                ("int".to_string(), "1".to_string()),
+                (ERROR.to_string(), "a!b".to_string()),
+                ("int".to_string(), "2".to_string()),
+                (ERROR.to_string(), "c".to_string()),
+            ]
+        );
+
+        assert!(tokens(Recovery::Stop).is_err());
+
+        let mut lexer = Lexer::from_source(INT_STATES, "a!").recovery(Recovery::resync());
+        assert_eq!(lexer.by_ref().count(), 1);
+        assert_eq!(lexer.errors().len(), 1);
+        assert_eq!(lexer.errors()[0].span(), Some(at(0)));
+    }
 }
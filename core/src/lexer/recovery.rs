@@ -0,0 +1,43 @@
+//! Error recovery in the lexers.
+//!
+//! By default a lexer error ends the token stream. With a recovery
+//! policy, the lexer emits an [ERROR] token spanning the bad input
+//! instead, collects the error, and resumes, so the parser can report
+//! (or recover from) the error token itself.
+
+/// The kind of the tokens spanning bad input.
+pub const ERROR: &str = "<error>";
+
+/// What a lexer does after an error.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Recovery<U = char> {
+    /// Return the error, the stream cannot go any further.
+    #[default]
+    Stop,
+    /// Skip the offending unit.
+    Skip,
+    /// Skip the units until the first one matching the predicate.
+    Resync(fn(U) -> bool),
+}
+
+impl<U> Recovery<U> {
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, Self::Stop)
+    }
+}
+
+impl Recovery<char> {
+    /// Resynchronise at the next whitespace or delimiter.
+    pub fn resync() -> Self {
+        Self::Resync(|ch| ch.is_whitespace() || DELIMITERS.contains(ch))
+    }
+}
+
+impl Recovery<u8> {
+    /// Resynchronise at the next ASCII whitespace or delimiter.
+    pub fn resync_bytes() -> Self {
+        Self::Resync(|byte| byte.is_ascii_whitespace() || DELIMITERS.as_bytes().contains(&byte))
+    }
+}
+
+const DELIMITERS: &str = "()[]{},;";
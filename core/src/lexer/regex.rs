@@ -0,0 +1,485 @@
+//! Parser of the regular expressions describing tokens.
+//!
+//! The supported syntax is:
+//! - literals, and escaped metacharacters `\.`, `\*`, ...
+//! - the control escapes `\n`, `\r`, `\t`, `\0`,
+//! - the unicode escapes `\u{1F600}`, and `\u00E9`,
+//! - the classes `[a-z_]`, `[^0-9]`, and `.` (any char but `\n`),
+//! - the shorthand classes `\d`, `\w`, `\s`, and their negations `\D`, `\W`, `\S`,
+//! - the unicode classes `\p{Lu}`, `\p{Script=Greek}`, `\pL`, and their negations `\P{..}`,
+//! - the groups `(a|b)`, and the named groups `(?<name>a|b)`,
+//! - the quantifiers `*`, `+`, `?`, `{n}`, `{n,}`, `{,m}` and `{n,m}`,
+//!   whose bounds are at most [MAX_REPETITION].
+use crate::{
+    dfa::CharSet,
+    span::{Cursor, Span},
+    ErrorKind, YalpError, YalpResult,
+};
+
+use super::ast::{Either, Expr, Group, Leaf, Quantified, Quantifier, Sequence};
+
+/// The largest bound of a repetition, as each repetition
+/// is unrolled into as many copies of its expression.
+pub const MAX_REPETITION: usize = 1000;
+
+/// Parse the regular expression.
+///
+/// The span of an error locates the faulty part of the pattern,
+/// with columns counted in chars.
+pub fn parse<Error>(pattern: &str) -> YalpResult<Expr, Error> {
+    let mut parser = RegexParser {
+        chars: pattern.chars().collect(),
+        pos: 0,
+    };
+
+    let expr = parser.either()?;
+
+    match parser.peek() {
+        None => Ok(expr),
+        Some(_) => Err(parser.error(parser.pos, "unmatched ')'")),
+    }
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        self.peek().inspect(|_| self.pos += 1)
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        let eaten = self.peek() == Some(ch);
+        if eaten {
+            self.pos += 1;
+        }
+        eaten
+    }
+
+    fn error<Error>(&self, from: usize, message: &str) -> YalpError<Error> {
+        self.fail(from, ErrorKind::InvalidRegex(message.to_string()))
+    }
+
+    /// Locate the error from the given column to the current one.
+    fn fail<Error>(&self, from: usize, kind: ErrorKind<Error>) -> YalpError<Error> {
+        let cursor = |column: usize| {
+            let offset = self.chars[..column.min(self.chars.len())]
+                .iter()
+                .map(|ch| ch.len_utf8())
+                .sum();
+
+            Cursor::new(1, column, offset)
+        };
+
+        YalpError::new(
+            kind,
+            Some(Span::new(cursor(from), cursor(self.pos.max(from + 1)))),
+        )
+    }
+
+    fn expect<Error>(&mut self, ch: char, from: usize, message: &str) -> YalpResult<(), Error> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(self.error(from, message))
+        }
+    }
+
+    /// A1 | A2 | ... | An
+    fn either<Error>(&mut self) -> YalpResult<Expr, Error> {
+        let mut alternatives = vec![self.sequence()?];
+
+        while self.eat('|') {
+            alternatives.push(self.sequence()?);
+        }
+
+        Ok(match alternatives.len() {
+            1 => alternatives.pop().unwrap(),
+            _ => Expr::Either(Either(alternatives)),
+        })
+    }
+
+    /// A1..An
+    fn sequence<Error>(&mut self) -> YalpResult<Expr, Error> {
+        let mut items = vec![];
+
+        while !matches!(self.peek(), None | Some('|') | Some(')')) {
+            items.push(self.quantified()?);
+        }
+
+        Ok(match items.len() {
+            1 => items.pop().unwrap(),
+            _ => Expr::Sequence(Sequence(items)),
+        })
+    }
+
+    /// A{n,m} or A+, or A?, or A*
+    fn quantified<Error>(&mut self) -> YalpResult<Expr, Error> {
+        let mut expr = self.atom()?;
+
+        loop {
+            let from = self.pos;
+            let quantifier = match self.peek() {
+                Some('*' | '+' | '?') => match self.next() {
+                    Some('*') => Quantifier::Wild,
+                    Some('+') => Quantifier::RangeFrom(1),
+                    _ => Quantifier::Optional,
+                },
+                Some('{') => {
+                    self.pos += 1;
+                    self.bounds(from)?
+                }
+                _ => return Ok(expr),
+            };
+
+            expr = Expr::Quantified(Quantified(Box::new(expr), quantifier));
+        }
+    }
+
+    /// {n}, {n,}, {,m} or {n,m}, the opening brace being consumed.
+    fn bounds<Error>(&mut self, from: usize) -> YalpResult<Quantifier, Error> {
+        let min = self.number()?;
+
+        let quantifier = if self.eat(',') {
+            match (min, self.number()?) {
+                (Some(min), None) => Quantifier::RangeFrom(min),
+                (None, Some(max)) => Quantifier::RangeTo(max),
+                (Some(min), Some(max)) if min <= max => Quantifier::Range(min, max),
+                (Some(_), Some(_)) => {
+                    return Err(self.error(from, "the lower bound exceeds the upper bound"))
+                }
+                (None, None) => return Err(self.error(from, "missing repetition bounds")),
+            }
+        } else {
+            match min {
+                Some(n) => Quantifier::Range(n, n),
+                None => return Err(self.error(from, "missing repetition bounds")),
+            }
+        };
+
+        self.expect('}', from, "unclosed repetition")?;
+        Ok(quantifier)
+    }
+
+    /// A repetition bound, if any, up to [MAX_REPETITION].
+    fn number<Error>(&mut self) -> YalpResult<Option<usize>, Error> {
+        let from = self.pos;
+
+        while self.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        if from == self.pos {
+            return Ok(None);
+        }
+
+        let digits = self.chars[from..self.pos].iter().collect::<String>();
+
+        match digits.parse::<usize>() {
+            Ok(bound) if bound <= MAX_REPETITION => Ok(Some(bound)),
+            _ => Err(self.fail(
+                from,
+                ErrorKind::RepetitionOverflow {
+                    bound: digits,
+                    limit: MAX_REPETITION,
+                },
+            )),
+        }
+    }
+
+    fn atom<Error>(&mut self) -> YalpResult<Expr, Error> {
+        let from = self.pos;
+
+        match self.next() {
+            Some('(') => {
+                let name = if self.eat('?') {
+                    self.expect('<', from, "expecting a group name")?;
+                    let name = self.until('>');
+                    self.expect('>', from, "unclosed group name")?;
+                    Some(name)
+                } else {
+                    None
+                };
+
+                let expr = self.either()?;
+                self.expect(')', from, "unclosed group")?;
+                Ok(Expr::Group(Group(Box::new(expr), name)))
+            }
+            Some('[') => self.class(from).map(leaf),
+            Some('.') => Ok(leaf(!CharSet::eq('\n'))),
+            Some('\\') => self.escape(from).map(leaf),
+            Some('*' | '+' | '?' | '{') => Err(self.error(from, "nothing to repeat")),
+            Some(ch) => Ok(leaf(CharSet::eq(ch))),
+            None => Err(self.error(from, "unexpected end of pattern")),
+        }
+    }
+
+    fn until(&mut self, end: char) -> String {
+        let from = self.pos;
+
+        while self.peek().is_some_and(|ch| ch != end) {
+            self.pos += 1;
+        }
+
+        self.chars[from..self.pos].iter().collect()
+    }
+
+    /// [a-z_], or [^0-9], the opening bracket being consumed.
+    fn class<Error>(&mut self, from: usize) -> YalpResult<CharSet, Error> {
+        let negated = self.eat('^');
+        let mut set = None;
+        let mut first = true;
+
+        loop {
+            let item_from = self.pos;
+
+            let item = match self.next() {
+                None => return Err(self.error(from, "unclosed class")),
+                Some(']') if !first => break,
+                Some('\\') => self.escape(item_from)?,
+                Some(lo)
+                    if self.peek() == Some('-') && self.chars.get(self.pos + 1) != Some(&']') =>
+                {
+                    self.pos += 1;
+
+                    let hi = match self.next() {
+                        Some('\\') => self.escaped_char(self.pos - 1)?,
+                        Some(hi) => hi,
+                        None => return Err(self.error(from, "unclosed class")),
+                    };
+
+                    if lo > hi {
+                        return Err(self.error(item_from, "invalid class range"));
+                    }
+
+                    CharSet::from(lo..=hi)
+                }
+                Some(ch) => CharSet::eq(ch),
+            };
+
+            first = false;
+            set = Some(match set {
+                None => item,
+                Some(set) => set | item,
+            });
+        }
+
+        let set = set.unwrap();
+        Ok(if negated { !set } else { set })
+    }
+
+    /// An escape sequence, the backslash being consumed.
+    fn escape<Error>(&mut self, from: usize) -> YalpResult<CharSet, Error> {
+        let digit = || CharSet::from('0'..='9');
+        let word = || CharSet::from('a'..='z') | CharSet::from('A'..='Z') | digit() | '_'.into();
+        let space = || {
+            [' ', '\t', '\n', '\r', '\u{B}', '\u{C}']
+                .into_iter()
+                .map(CharSet::eq)
+                .reduce(|a, b| a | b)
+                .unwrap()
+        };
+
+        if let Some(negation @ ('p' | 'P')) = self.peek() {
+            self.pos += 1;
+            let set = self.property(from)?;
+            return Ok(if negation == 'P' { !set } else { set });
+        }
+
+        Ok(match self.peek() {
+            Some('d') => digit(),
+            Some('D') => !digit(),
+            Some('w') => word(),
+            Some('W') => !word(),
+            Some('s') => space(),
+            Some('S') => !space(),
+            _ => return self.escaped_char(from).map(CharSet::eq),
+        })
+        .inspect(|_| self.pos += 1)
+    }
+
+    /// {Lu}, {Script=Greek}, or L, the \p being consumed.
+    fn property<Error>(&mut self, from: usize) -> YalpResult<CharSet, Error> {
+        let name = if self.eat('{') {
+            let name = self.until('}');
+            self.expect('}', from, "unclosed unicode class")?;
+            name
+        } else {
+            self.next().map(String::from).unwrap_or_default()
+        };
+
+        CharSet::unicode_property(&name).ok_or_else(|| self.error(from, "unknown unicode class"))
+    }
+
+    /// A single escaped char, the backslash being consumed.
+    fn escaped_char<Error>(&mut self, from: usize) -> YalpResult<char, Error> {
+        match self.next() {
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('0') => Ok('\0'),
+            Some('u') => self.unicode(from),
+            Some(ch) if ch.is_ascii_punctuation() => Ok(ch),
+            Some(_) => Err(self.error(from, "unknown escape sequence")),
+            None => Err(self.error(from, "unexpected end of pattern")),
+        }
+    }
+
+    /// \u{1F600}, or \u00E9, the \u being consumed.
+    fn unicode<Error>(&mut self, from: usize) -> YalpResult<char, Error> {
+        let digits = if self.eat('{') {
+            let digits = self.until('}');
+            self.expect('}', from, "unclosed unicode escape")?;
+            digits
+        } else {
+            let end = (self.pos + 4).min(self.chars.len());
+            let digits = self.chars[self.pos..end].iter().collect::<String>();
+            self.pos = end;
+            digits
+        };
+
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .filter(|_| !digits.is_empty() && digits.len() <= 6)
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(from, "invalid unicode escape"))
+    }
+}
+
+fn leaf(set: CharSet) -> Expr {
+    Expr::Leaf(Leaf(set))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        dfa::{CharSet, Dfa, Graph, IntoGraph},
+        ErrorKind, NoCustomError,
+    };
+
+    use super::{parse, MAX_REPETITION};
+
+    fn matches(pattern: &str, input: &str) -> bool {
+        let graph: Graph<CharSet, ()> = parse::<NoCustomError>(pattern).unwrap().into_graph();
+        let dfa = Dfa::from_graphs(&[(0, graph)]);
+
+        input
+            .chars()
+            .try_fold(0, |state, ch| dfa.next(state, &ch))
+            .and_then(|state| dfa.accept(state))
+            .is_some()
+    }
+
+    #[test]
+    fn test_001_regex_matches() {
+        let cases: &[(&str, &[&str], &[&str])] = &[
+            ("abc", &["abc"], &["ab", "abcd"]),
+            ("a|bc", &["a", "bc"], &["b", "abc"]),
+            ("[a-c_]+", &["a", "cab_"], &["", "d"]),
+            ("[^0-9]", &["a", "-"], &["5"]),
+            ("[a-]", &["a", "-"], &["b"]),
+            (".", &["a", "é"], &["\n", ""]),
+            ("\\d+\\.\\d*", &["1.", "12.5"], &["1", ".5"]),
+            ("\\w\\s\\W", &["a -"], &["a b", "  -"]),
+            ("a(b|c)*d", &["ad", "abcbd"], &["abc"]),
+            ("(?<num>a)?b", &["b", "ab"], &["aab"]),
+            ("a{2}", &["aa"], &["a", "aaa"]),
+            ("a{2,}", &["aa", "aaaa"], &["a"]),
+            ("a{,2}", &["", "a", "aa"], &["aaa"]),
+            ("a{1,3}", &["a", "aaa"], &["", "aaaa"]),
+            ("\\u{1F600}\\u00e9", &["\u{1F600}é"], &["é"]),
+            ("x{2}+", &["xx", "xxxx"], &["xxx"]),
+            ("\\p{Lu}\\p{Ll}*", &["Été", "Ω"], &["été", "1"]),
+            ("\\pL\\P{L}", &["a1", "λ-"], &["ab"]),
+            ("[\\p{Greek}\\d]+", &["λ1", "42"], &["a"]),
+        ];
+
+        for (pattern, accepted, rejected) in cases {
+            for input in accepted.iter() {
+                assert!(
+                    matches(pattern, input),
+                    "{} should match {:?}",
+                    pattern,
+                    input
+                );
+            }
+
+            for input in rejected.iter() {
+                assert!(
+                    !matches(pattern, input),
+                    "{} should not match {:?}",
+                    pattern,
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_002_regex_errors() {
+        let cases = [
+            ("(ab", 0),
+            ("ab)", 2),
+            ("[a-z", 0),
+            ("a{3,1}", 1),
+            ("*a", 0),
+            ("a\\q", 1),
+            ("[z-a]", 1),
+            ("\\u{110000}", 0),
+            ("a\\p{Klingon}", 1),
+        ];
+
+        for (pattern, column) in cases {
+            let err = parse::<NoCustomError>(pattern).err().unwrap();
+
+            assert!(
+                matches!(err.kind(), ErrorKind::InvalidRegex(_)),
+                "{}",
+                pattern
+            );
+            assert_eq!(err.span.unwrap().from.column, column, "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn test_003_regex_repetition_overflow() {
+        let cases = [
+            ("a{4000000000}", 2, 12),
+            ("a{4000000000000000000000}", 2, 24),
+            ("a{100000}", 2, 8),
+            ("a{2,1001}", 4, 8),
+        ];
+
+        for (pattern, from, to) in cases {
+            let err = parse::<NoCustomError>(pattern).err().unwrap();
+
+            assert!(
+                matches!(
+                    err.kind(),
+                    ErrorKind::RepetitionOverflow {
+                        limit: MAX_REPETITION,
+                        ..
+                    }
+                ),
+                "{}",
+                pattern
+            );
+
+            let span = err.span.unwrap();
+            assert_eq!(
+                (span.from.column, span.to.column),
+                (from, to),
+                "{}",
+                pattern
+            );
+        }
+
+        assert!(parse::<NoCustomError>("a{1000}").is_ok());
+    }
+}
@@ -0,0 +1,155 @@
+//! Sub-lexers, for the tokens which are not regular.
+//!
+//! Nested comments, or raw strings whose closing delimiter depends on
+//! the opening one, cannot be matched by a DFA. The pattern of such a
+//! token only matches its opener, and a [SubLexer] scans the rest of it.
+
+/// Scans the rest of a token, after its opener.
+pub trait SubLexer<U> {
+    /// Returns the number of units following the opener which belong to
+    /// the token, or None if it is not terminated.
+    ///
+    /// `peek(i)` returns the i-th unit after the opener.
+    fn scan(&self, opener: &[U], peek: &mut dyn FnMut(usize) -> Option<U>) -> Option<usize>;
+}
+
+impl<U, F> SubLexer<U> for F
+where
+    F: Fn(&[U], &mut dyn FnMut(usize) -> Option<U>) -> Option<usize>,
+{
+    fn scan(&self, opener: &[U], peek: &mut dyn FnMut(usize) -> Option<U>) -> Option<usize> {
+        self(opener, peek)
+    }
+}
+
+/// Whether the units starting at i are the sequence.
+fn starts_with<U: PartialEq>(
+    seq: &[U],
+    i: usize,
+    peek: &mut dyn FnMut(usize) -> Option<U>,
+) -> bool {
+    seq.iter()
+        .enumerate()
+        .all(|(j, unit)| peek(i + j).is_some_and(|next| next == *unit))
+}
+
+/// Nested constructs, such as `/* /* */ */` comments.
+///
+/// The opener is the first level, and every inner opening sequence
+/// must be closed.
+#[derive(Debug, Clone)]
+pub struct Nested<U> {
+    open: Vec<U>,
+    close: Vec<U>,
+}
+
+impl<U> Nested<U> {
+    pub fn new(open: impl IntoIterator<Item = U>, close: impl IntoIterator<Item = U>) -> Self {
+        Self {
+            open: open.into_iter().collect(),
+            close: close.into_iter().collect(),
+        }
+    }
+}
+
+impl<U: PartialEq> SubLexer<U> for Nested<U> {
+    fn scan(&self, _opener: &[U], peek: &mut dyn FnMut(usize) -> Option<U>) -> Option<usize> {
+        let mut depth = 1;
+        let mut i = 0;
+
+        while depth > 0 {
+            if starts_with(&self.close, i, peek) {
+                depth -= 1;
+                i += self.close.len();
+            } else if starts_with(&self.open, i, peek) {
+                depth += 1;
+                i += self.open.len();
+            } else {
+                peek(i)?;
+                i += 1;
+            }
+        }
+
+        Some(i)
+    }
+}
+
+/// Constructs closed by a sequence derived from their opener, such
+/// as Rust raw strings `r#"..."#` or Lua long brackets `[==[...]==]`.
+#[derive(Debug, Clone, Copy)]
+pub struct Delimited<U> {
+    closer: fn(&[U]) -> Vec<U>,
+}
+
+impl<U> Delimited<U> {
+    /// The closing sequence is computed from the opener.
+    pub fn new(closer: fn(&[U]) -> Vec<U>) -> Self {
+        Self { closer }
+    }
+}
+
+impl Delimited<char> {
+    /// Rust raw strings, the opener `r#"` is closed by `"#`.
+    pub fn raw_string() -> Self {
+        Self::new(|opener| {
+            std::iter::once('"')
+                .chain(opener.iter().copied().filter(|ch| *ch == '#'))
+                .collect()
+        })
+    }
+
+    /// Lua long brackets, the opener `[=[` is closed by `]=]`.
+    pub fn long_bracket() -> Self {
+        Self::new(|opener| {
+            std::iter::once(']')
+                .chain(opener.iter().copied().filter(|ch| *ch == '='))
+                .chain(std::iter::once(']'))
+                .collect()
+        })
+    }
+}
+
+impl<U: PartialEq> SubLexer<U> for Delimited<U> {
+    fn scan(&self, opener: &[U], peek: &mut dyn FnMut(usize) -> Option<U>) -> Option<usize> {
+        let closer = (self.closer)(opener);
+        let mut i = 0;
+
+        while !starts_with(&closer, i, peek) {
+            peek(i)?;
+            i += 1;
+        }
+
+        Some(i + closer.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Delimited, Nested, SubLexer};
+
+    fn scan(sub: &dyn SubLexer<char>, opener: &str, rest: &str) -> Option<usize> {
+        let opener = opener.chars().collect::<Vec<_>>();
+        let rest = rest.chars().collect::<Vec<_>>();
+
+        sub.scan(&opener, &mut |i| rest.get(i).copied())
+    }
+
+    #[test]
+    fn test_nested() {
+        let nested = Nested::new("/*".chars(), "*/".chars());
+
+        assert_eq!(scan(&nested, "/*", " a /* b */ c */ d"), Some(15));
+        assert_eq!(scan(&nested, "/*", "*/"), Some(2));
+        assert_eq!(scan(&nested, "/*", " /* */"), None);
+    }
+
+    #[test]
+    fn test_delimited() {
+        assert_eq!(
+            scan(&Delimited::raw_string(), "r##\"", "a \"# b\"##c"),
+            Some(9)
+        );
+        assert_eq!(scan(&Delimited::raw_string(), "r\"", "abc"), None);
+        assert_eq!(scan(&Delimited::long_bracket(), "[=[", "a]]b]=]"), Some(7));
+    }
+}
@@ -1,11 +1,15 @@
 pub mod ast;
-//pub mod dfa;:
+pub mod dfa;
+pub mod diagnostic;
+pub mod earley;
 pub mod error;
 pub mod item;
 pub mod lexer;
 pub mod lr;
 pub mod parser;
 pub mod prelude;
+pub mod reducer;
+pub mod source;
 pub mod span;
 pub mod syntax;
 pub mod token;
@@ -13,47 +17,98 @@ pub use lexer::*;
 
 pub(crate) use item::*;
 
+pub use diagnostic::{Diagnostic, Renderer};
 pub use lexer::Span;
+pub use source::{FileId, SourceMap};
+pub use syntax::{EOS, START};
 
 mod array;
 
 pub use error::{ErrorKind, NoCustomError, YalpError};
+pub use reducer::{ReducerSet, RuleReducer};
 
 pub type YalpResult<T, E> = Result<T, YalpError<E>>;
 
+#[cfg(test)]
 macro_rules! rule {
-    ($lhs:literal ::= $($rhs:literal)*) => {
-        StaticRule::new(StaticSymbol::new($lhs),
-            &[
-                $(StaticSymbol::new($rhs),)*
-            ]
-        )
-    };
+    ($lhs:literal ::= $($rhs:literal)*) => {{
+        const RHS: &[StaticSymbol] = &[
+            $(StaticSymbol::new($rhs),)*
+        ];
+        StaticRule::new(StaticSymbol::new($lhs), RHS)
+    }};
+
+}
 
+#[cfg(test)]
+macro_rules! syntax {
+    ($($rule:expr),* $(,)?) => {{
+        const RULES: &[StaticRule] = &[
+            $($rule,)*
+        ];
+        StaticSyntax::new(RULES)
+    }};
 }
 
 #[cfg(test)]
 pub mod fixtures {
 
-    use crate::syntax::{StaticRule, StaticSymbol, StaticSyntax};
+    use crate::{
+        reducer::RuleReducer,
+        syntax::{StaticRule, StaticSymbol, StaticSyntax},
+        token::Token,
+        NoCustomError,
+    };
 
-    pub const FIXTURE_LR1_GRAMMAR: StaticSyntax = StaticSyntax::new(&[
+    pub static FIXTURE_LR1_GRAMMAR: StaticSyntax = syntax![
         rule!("START" ::= "E"),
         rule!("E" ::= "(" "E" ")"),
         rule!("E" ::= "T"),
         rule!("T" ::= "n"),
         rule!("T" ::= "+" "T"),
         rule!("T" ::= "+" "+" "n"),
-    ]);
+    ];
 
-    pub const FIXTURE_LR0_GRAMMAR: StaticSyntax = StaticSyntax::new(&[
+    pub static FIXTURE_LR0_GRAMMAR: StaticSyntax = syntax![
         rule!("START" ::= "E"),
         rule!("E" ::= "E" "*" "B"),
         rule!("E" ::= "E" "+" "B"),
         rule!("E" ::= "B"),
         rule!("B" ::= "0"),
         rule!("B" ::= "1"),
-    ]);
+    ];
+
+    /// E -> E + E is ambiguous.
+    pub static FIXTURE_AMBIGUOUS_GRAMMAR: StaticSyntax =
+        syntax![rule!("E" ::= "E" "+" "E"), rule!("E" ::= "n")];
+
+    /// E -> E E has a Catalan number of derivations of n^k.
+    pub static FIXTURE_CATALAN_GRAMMAR: StaticSyntax =
+        syntax![rule!("E" ::= "E" "E"), rule!("E" ::= "n")];
+
+    /// "let" is only a keyword at the start of the statement.
+    pub static FIXTURE_LET_GRAMMAR: StaticSyntax =
+        syntax![rule!("START" ::= "S"), rule!("S" ::= "let" "id" "=" "id")];
+
+    pub type FixtureToken = Token<'static>;
+
+    /// Evaluates the expressions of the LR(0) grammar.
+    pub fn fixture_lr0_reducers() -> Vec<RuleReducer<'static, NoCustomError>> {
+        vec![
+            // START -> E
+            RuleReducer::new(|_, (e,): (i32,)| Ok(e)),
+            // E -> E * B
+            RuleReducer::new(|_, (e, _, b): (i32, FixtureToken, i32)| Ok(e * b)),
+            // E -> E + B
+            RuleReducer::new(|_, (e, _, b): (i32, FixtureToken, i32)| Ok(e + b)),
+            // E -> B
+            RuleReducer::new(|_, (b,): (i32,)| Ok(b)),
+            // B -> 0
+            RuleReducer::new(|_, (_,): (FixtureToken,)| Ok(0)),
+            // B -> 1
+            RuleReducer::new(|_, (_,): (FixtureToken,)| Ok(1)),
+        ]
+    }
 
     #[test]
     fn test_grammars() {
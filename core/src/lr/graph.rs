@@ -15,7 +15,9 @@ impl<'syntax, 'gen, const K: usize> Graph<'syntax, 'gen, K> {
     pub fn new(rules: &'gen PrepSyntax<'syntax>) -> Self {
         Self {
             rules,
-            sets: vec![rules.start_item_set()],
+            sets: (0..rules.entries.len())
+                .map(|entry| rules.start_item_set(entry))
+                .collect(),
             edges: vec![],
         }
     }
@@ -53,7 +55,8 @@ impl<'syntax, 'gen, const K: usize> Graph<'syntax, 'gen, K> {
     }
 
     pub fn build<Error>(&mut self) -> YalpResult<(), Error> {
-        let mut stack = VecDeque::from_iter([0]);
+        // One start state per entry point.
+        let mut stack = VecDeque::from_iter(0..self.rules.entries.len());
         let rules = self.rules;
 
         while let Some(set_id) = stack.pop_front() {
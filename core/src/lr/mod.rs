@@ -1,29 +1,32 @@
 use std::marker::PhantomData;
 
 use crate::{
-    lexer::traits::Lexer,
-    parser::traits::{Ast, Parser},
-    ItemSetId,
+    lexer::traits::Lexer, parser::traits::Parser, syntax::RuleId, token::traits::Token, ItemSetId,
+    EOS,
 };
 use crate::{ErrorKind, YalpError, YalpResult};
 
 mod action;
 mod graph;
+mod observer;
+mod state;
 mod table;
 mod transition;
+mod typed;
 
-use action::*;
+pub use action::*;
 use graph::*;
+pub use observer::*;
+pub use state::*;
 pub use table::*;
 use transition::*;
-
-pub use self::traits::LrTable;
+pub use typed::*;
 
 pub type StateId = ItemSetId;
 
 pub struct LrParser<'table, Ast, Table>
 where
-    Table: LrTable,
+    Table: traits::LrTable,
     Ast: crate::prelude::Ast,
 {
     table: &'table Table,
@@ -32,7 +35,7 @@ where
 
 impl<'table, Ast, Table> LrParser<'table, Ast, Table>
 where
-    Table: LrTable,
+    Table: traits::LrTable,
     Ast: crate::prelude::Ast,
 {
     pub fn new(table: &'table Table) -> Self {
@@ -41,13 +44,227 @@ where
             pht: PhantomData,
         }
     }
+
+    /// Returns the terminals which may legally follow the given prefix of terminals.
+    ///
+    /// Default reductions are taken into account, so only the terminals
+    /// which can be shifted, or accepted, are returned.
+    pub fn expected_terminals<Error, I, S>(&self, prefix: I) -> YalpResult<Vec<&'table str>, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut state = ParserState::new();
+
+        for symbol in prefix {
+            state.feed(self.table, symbol.as_ref())?;
+        }
+
+        Ok(state.expected(self.table).collect())
+    }
+
+    /// Parse the stream, and report every step to the observer.
+    pub fn parse_with<L, O, Error>(&self, lexer: &mut L, observer: &mut O) -> YalpResult<Ast, Error>
+    where
+        Error: Clone,
+        L: Lexer<Error>,
+        O: Observer,
+        Ast: From<L::Token>,
+    {
+        self.run(ParserState::new(), lexer, observer)
+            .inspect_err(|err| observer.error(err))
+    }
+
+    /// Parse the stream from the given entry point.
+    ///
+    /// The entry must have been declared when building the table.
+    pub fn parse_entry<L, Error>(&self, entry: &str, lexer: &mut L) -> YalpResult<Ast, Error>
+    where
+        Error: Clone,
+        L: Lexer<Error>,
+        Ast: From<L::Token>,
+    {
+        self.parse_entry_with(entry, lexer, &mut ())
+    }
+
+    /// Parse the stream from the given entry point, and report every step to the observer.
+    pub fn parse_entry_with<L, O, Error>(
+        &self,
+        entry: &str,
+        lexer: &mut L,
+        observer: &mut O,
+    ) -> YalpResult<Ast, Error>
+    where
+        Error: Clone,
+        L: Lexer<Error>,
+        O: Observer,
+        Ast: From<L::Token>,
+    {
+        ParserState::from_entry(self.table, entry)
+            .and_then(|states| self.run(states, lexer, observer))
+            .inspect_err(|err| observer.error(err))
+    }
+
+    fn run<L, O, Error>(
+        &self,
+        states: ParserState,
+        lexer: &mut L,
+        observer: &mut O,
+    ) -> YalpResult<Ast, Error>
+    where
+        Error: Clone,
+        L: Lexer<Error>,
+        O: Observer,
+        Ast: From<L::Token>,
+    {
+        drive(
+            self.table,
+            states,
+            lexer,
+            observer,
+            Ast::from,
+            |_, reduction, rhs| Ok(Ast::reduce(&reduction.lhs, rhs)),
+            |_, mut values| Ok(values.next_back().unwrap()),
+        )
+    }
+}
+
+/// Fetch the next token, passing the expected terminals to contextual lexers.
+fn next_token<Table, L, Error>(
+    table: &Table,
+    states: &ParserState,
+    lexer: &mut L,
+) -> Option<YalpResult<L::Token, Error>>
+where
+    Table: traits::LrTable,
+    L: Lexer<Error>,
+{
+    if lexer.is_contextual() {
+        let expected = states.expected(table).collect::<Vec<_>>();
+        lexer.expect(&expected);
+    }
+
+    lexer.next()
+}
+
+/// Run the LR automaton over the lexer's stream.
+///
+/// Shifted tokens are turned into values, and values are
+/// combined by the reducer for each rule reduction.
+/// On accept, the values of the entry point's rule are passed,
+/// with the entry's start state, to the acceptor.
+pub(crate) fn drive<Table, L, O, Value, Error>(
+    table: &Table,
+    mut states: ParserState,
+    lexer: &mut L,
+    observer: &mut O,
+    mut shift: impl FnMut(L::Token) -> Value,
+    mut reduce: impl FnMut(RuleId, &Reduction, std::vec::Drain<'_, Value>) -> YalpResult<Value, Error>,
+    accept: impl FnOnce(StateId, std::vec::Drain<'_, Value>) -> YalpResult<Value, Error>,
+) -> YalpResult<Value, Error>
+where
+    Table: traits::LrTable,
+    Error: Clone,
+    L: Lexer<Error>,
+    O: Observer,
+{
+    let mut stack: Vec<Value> = Vec::default();
+    let mut cursor = next_token(table, &states, lexer);
+
+    observer.start(states.current());
+
+    loop {
+        let state = states.current();
+
+        let (symbol, tok) = match &cursor {
+            None => (EOS, None),
+            Some(Ok(tok)) => (tok.symbol_id(), Some(tok)),
+            Some(Err(err)) => return Err(err.clone()),
+        };
+
+        // Errors are located at the lookahead, or where the lexer stopped.
+        let span = tok.and_then(Token::span).unwrap_or_else(|| lexer.span());
+        let locate = |mut err: YalpError<Error>| {
+            err.span = err.span.or(Some(span));
+            err
+        };
+
+        let action = table.action(state, symbol).ok_or_else(|| {
+            YalpError::new(
+                ErrorKind::unexpected_symbol(
+                    table.describe(symbol),
+                    states.expected(table).map(|sym| table.describe(sym)),
+                ),
+                Some(span),
+            )
+        })?;
+
+        match action {
+            // Push the new terminal on top of the stack
+            // Shift to tne given state.
+            Action::Shift(next_state_id) => {
+                observer.shift(*next_state_id, symbol);
+
+                if let Some(tok) = tok {
+                    stack.push(shift(tok.clone()));
+                    states.push(*next_state_id);
+                    cursor = next_token(table, &states, lexer);
+                } else {
+                    states.push(*next_state_id);
+                }
+            }
+
+            // Reduce by the given rule
+            // Consume LHS's length number of symbols
+            Action::Reduce(rule_id) => {
+                let reduction = table
+                    .reduction(*rule_id)
+                    .ok_or_else(|| YalpError::new(ErrorKind::UnknownRule(*rule_id), Some(span)))?;
+                let consume = reduction.arity;
+
+                let value = reduce(
+                    *rule_id,
+                    reduction,
+                    stack.drain(stack.len().saturating_sub(consume)..),
+                )
+                .map_err(locate)?;
+
+                states.pop(consume);
+                observer.reduce(*rule_id, consume);
+
+                let goto = table
+                    .goto(states.current(), &reduction.lhs)
+                    .ok_or_else(|| {
+                        YalpError::new(
+                            ErrorKind::unexpected_symbol(
+                                &reduction.lhs,
+                                table.iter_non_terminals(states.current()),
+                            ),
+                            Some(span),
+                        )
+                    })?;
+
+                states.push(goto);
+                observer.goto(&reduction.lhs, goto);
+
+                stack.push(value);
+            }
+            Action::Accept => {
+                observer.accept();
+
+                // The bottom state is the start state of the entry point.
+                let entry = states.iter().next().copied().unwrap_or_default();
+                return accept(entry, stack.drain(..)).map_err(locate);
+            }
+        }
+    }
 }
 
 impl<'table, Table, Ast, Error> Parser<Error> for LrParser<'table, Ast, Table>
 where
     Error: Clone,
     Ast: crate::prelude::Ast,
-    Table: LrTable,
+    Table: traits::LrTable,
 {
     type Ast = Ast;
 
@@ -55,147 +272,49 @@ where
     where
         Self::Ast: From<L::Token>,
     {
-        let mut states: Vec<StateId> = vec![0];
-        let mut stack: Vec<Ast> = Vec::default();
-        let mut cursor = lexer.next();
-
-        loop {
-            let mut state = states.last().copied().unwrap();
-
-            let (symbol, tok) = match &cursor {
-                None => (self.rules.eos(), None),
-                Some(Ok(tok)) => (self.rules.try_get_symbol_by_id(tok.symbol_id())?, Some(tok)),
-                Some(Err(err)) => return Err(err.clone()),
-            };
-
-            let action = self.table.action(state, &symbol).ok_or_else(|| {
-                YalpError::new(
-                    ErrorKind::unexpected_symbol(
-                        symbol.id,
-                        self.table.iter_terminals(state).map(|s| s.id.to_string()),
-                    ),
-                    None,
-                )
-            })?;
-
-            match action {
-                // Push the new terminal on top of the stack
-                // Shift to tne given state.
-                Action::Shift(next_state_id) => {
-                    if !symbol.is_eos() {
-                        stack.push(tok.cloned().unwrap().into());
-                        cursor = lexer.next();
-                    }
-                    states.push(*next_state_id);
-                }
-
-                // Reduce by the given rule
-                // Consume LHS's length number of symbols
-                Action::Reduce(rule_id) => {
-                    let rule = self.rules.borrow_rule(*rule_id);
-                    let consume = rule.rhs.len();
-
-                    let ast = {
-                        let drained = stack.drain(stack.len().saturating_sub(consume)..);
-                        drained
-                            .as_slice()
-                            .iter()
-                            .zip(rule.rhs.iter())
-                            .try_for_each(|(node, expected_symbol)| {
-                                if node.symbol_id() != expected_symbol.id {
-                                    Err(YalpError::new(
-                                        ErrorKind::unexpected_symbol(
-                                            &node.symbol_id().to_string(),
-                                            vec![expected_symbol.id],
-                                        ),
-                                        None,
-                                    ))
-                                } else {
-                                    Ok(())
-                                }
-                            })?;
-
-                        states.truncate(states.len().saturating_sub(consume));
-                        state = states.last().copied().unwrap();
-
-                        let goto = self.table.goto(state, &rule.lhs).ok_or_else(|| {
-                            YalpError::new(
-                                ErrorKind::unexpected_symbol(
-                                    &rule.lhs.id,
-                                    self.table
-                                        .iter_non_terminals(state)
-                                        .map(|s| s.id.to_string()),
-                                ),
-                                None,
-                            )
-                        })?;
-
-                        states.push(goto);
-
-                        let reducer = self.reducers.get(*rule_id).unwrap();
-                        reducer.reduce(rule, drained.into())
-                    }?;
-
-                    if ast.symbol_id() != rule.lhs.id {
-                        return Err(YalpError::new(
-                            ErrorKind::unexpected_symbol(ast.symbol_id(), vec![rule.lhs.id]),
-                            None,
-                        ));
-                    }
-
-                    stack.push(ast);
-                }
-                Action::Accept => {
-                    return Ok(stack.pop().unwrap());
-                }
-            }
-        }
+        self.parse_with(lexer, &mut ())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        fixtures::{FIXTURE_LR0_GRAMMAR, FIXTURE_LR1_GRAMMAR},
-        lexer::fixtures::{lexer_fixture_lr0, lexer_fixture_lr1},
-        NoCustomError,
+        ast::AstNode,
+        fixtures::{
+            fixture_lr0_reducers, FixtureToken, FIXTURE_LET_GRAMMAR, FIXTURE_LR0_GRAMMAR,
+            FIXTURE_LR1_GRAMMAR,
+        },
+        lexer::fixtures::{lexer_fixture_lr0, lexer_fixture_lr0_str, lexer_fixture_lr1},
+        lexer::LexerBuilder,
+        parser::traits::Parser as _,
+        reducer::{ReducerSet, RuleReducer},
+        NoCustomError, EOS,
     };
 
-    use super::{LrParser, LrTable};
+    use super::{LrParser, LrTable, PrettyPrinter, TraceEvent, TraceRecorder, TypedLrParser};
 
     #[test]
     pub fn test_lr0_grammar_table_building() {
-        let table = LrTable::build::<0, _, NoCustomError>(&FIXTURE_LR0_GRAMMAR)
-            .expect("cannot build table");
+        let table =
+            LrTable::build::<0, NoCustomError>(&FIXTURE_LR0_GRAMMAR).expect("cannot build table");
         println!("{}", table);
     }
 
     #[test]
     pub fn test_lr1_grammar_table_building() {
-        let table = LrTable::build::<1, _, NoCustomError>(&FIXTURE_LR1_GRAMMAR)
-            .expect("cannot build table");
+        let table =
+            LrTable::build::<1, NoCustomError>(&FIXTURE_LR1_GRAMMAR).expect("cannot build table");
         println!("{}", table);
     }
 
     #[test]
     pub fn test_lr0_parser() {
-        let table = LrTable::build::<0, _, NoCustomError>(&FIXTURE_LR0_GRAMMAR)
-            .expect("cannot build table");
+        let table =
+            LrTable::build::<0, NoCustomError>(&FIXTURE_LR0_GRAMMAR).expect("cannot build table");
 
         let mut lexer = lexer_fixture_lr0("1 + 1 * 0 * 1 * 1".chars());
 
-        let parser = LrParser::new(
-            &FIXTURE_LR0_GRAMMAR,
-            &table,
-            &[
-                AstNodeReducer,
-                AstNodeReducer,
-                AstNodeReducer,
-                AstNodeReducer,
-                AstNodeReducer,
-                AstNodeReducer,
-            ],
-        );
+        let parser = LrParser::<AstNode, _>::new(&table);
 
         let ast = parser.parse(&mut lexer).unwrap();
         println!("{:#?}", ast);
@@ -203,24 +322,257 @@ mod tests {
 
     #[test]
     pub fn test_lr1_parser() {
-        let table = LrTable::build::<1, _, NoCustomError>(&FIXTURE_LR1_GRAMMAR)
-            .expect("cannot build table");
+        let table =
+            LrTable::build::<1, NoCustomError>(&FIXTURE_LR1_GRAMMAR).expect("cannot build table");
 
-        let mut lexer = lexer_fixture_lr1("n + n".chars());
-        let parser = LrParser::new(
-            &FIXTURE_LR1_GRAMMAR,
-            &table,
-            &[
-                AstNodeReducer,
-                AstNodeReducer,
-                AstNodeReducer,
-                AstNodeReducer,
-                AstNodeReducer,
-                AstNodeReducer,
-            ],
-        );
+        let mut lexer = lexer_fixture_lr1("(+ n)".chars());
+        let parser = LrParser::<AstNode, _>::new(&table);
 
         let ast = parser.parse(&mut lexer).unwrap();
         println!("{:#?}", ast);
     }
+
+    #[test]
+    pub fn test_lr1_expected_terminals() {
+        let table =
+            LrTable::build::<1, NoCustomError>(&FIXTURE_LR1_GRAMMAR).expect("cannot build table");
+
+        let parser = LrParser::<AstNode, _>::new(&table);
+
+        let mut expected = parser
+            .expected_terminals::<NoCustomError, _, _>(["("])
+            .unwrap();
+        expected.sort();
+        assert_eq!(expected, vec!["(", "+", "n"]);
+
+        // T -> n •, E -> T • are default reductions before ")"
+        let expected = parser
+            .expected_terminals::<NoCustomError, _, _>(["(", "n"])
+            .unwrap();
+        assert_eq!(expected, vec![")"]);
+
+        let expected = parser
+            .expected_terminals::<NoCustomError, _, _>(["n"])
+            .unwrap();
+        assert_eq!(expected, vec![EOS]);
+
+        assert!(parser
+            .expected_terminals::<NoCustomError, _, _>(["n", "n"])
+            .is_err());
+    }
+
+    #[test]
+    pub fn test_lr0_parser_trace() {
+        let table =
+            LrTable::build::<0, NoCustomError>(&FIXTURE_LR0_GRAMMAR).expect("cannot build table");
+
+        let mut lexer = lexer_fixture_lr0("1 + 1 * 0".chars());
+        let parser = LrParser::<AstNode, _>::new(&table);
+
+        let mut trace = TraceRecorder::default();
+        parser.parse_with(&mut lexer, &mut trace).unwrap();
+
+        let shifted = trace
+            .iter()
+            .filter_map(|event| match event {
+                TraceEvent::Shift { symbol, .. } => Some(symbol.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(shifted, vec!["1", "+", "1", "*", "0"]);
+        assert_eq!(trace.iter().last(), Some(&TraceEvent::Accept));
+    }
+
+    #[test]
+    pub fn test_lr1_parser_entry() {
+        let table = LrTable::build_with_entries::<1, NoCustomError>(&FIXTURE_LR1_GRAMMAR, &["T"])
+            .expect("cannot build table");
+
+        let mut lexer = lexer_fixture_lr1("+ n".chars());
+        let parser = LrParser::<AstNode, _>::new(&table);
+
+        let ast = parser
+            .parse_entry::<_, NoCustomError>("T", &mut lexer)
+            .unwrap();
+        assert_eq!(ast.kind, "T");
+
+        let mut lexer = lexer_fixture_lr1("+ n".chars());
+        assert!(parser
+            .parse_entry::<_, NoCustomError>("B", &mut lexer)
+            .is_err());
+    }
+
+    #[test]
+    pub fn test_lr1_parser_entry_trace() {
+        let table = LrTable::build_with_entries::<1, NoCustomError>(&FIXTURE_LR1_GRAMMAR, &["T"])
+            .expect("cannot build table");
+
+        let parser = LrParser::<AstNode, _>::new(&table);
+
+        let mut lexer = lexer_fixture_lr1("+ n".chars());
+        let mut trace = TraceRecorder::default();
+        parser
+            .parse_entry_with::<_, _, NoCustomError>("T", &mut lexer, &mut trace)
+            .unwrap();
+
+        assert_eq!(trace.iter().next(), Some(&TraceEvent::Start { state: 1 }));
+
+        // The state stack starts from the entry's start state.
+        let mut lexer = lexer_fixture_lr1("+ n".chars());
+        let mut printer = PrettyPrinter::new(vec![]);
+        parser
+            .parse_entry_with::<_, _, NoCustomError>("T", &mut lexer, &mut printer)
+            .unwrap();
+
+        let output = String::from_utf8(printer.into_inner()).unwrap();
+        assert!(output
+            .lines()
+            .all(|line| line.split('|').nth(1).unwrap().trim().starts_with('1')));
+    }
+
+    #[test]
+    pub fn test_lr0_typed_parser() {
+        let table =
+            LrTable::build::<0, NoCustomError>(&FIXTURE_LR0_GRAMMAR).expect("cannot build table");
+
+        let reducers =
+            ReducerSet::new::<FixtureToken>(&FIXTURE_LR0_GRAMMAR, fixture_lr0_reducers())
+                .expect("cannot check reducers");
+
+        let parser = TypedLrParser::new(&table, &reducers).unwrap();
+
+        let mut lexer = lexer_fixture_lr0("1 + 1 + 1".chars());
+        let value: i32 = parser.parse(&mut lexer).unwrap();
+        assert_eq!(value, 3);
+
+        let mut lexer = lexer_fixture_lr0("1 + 1 * 0".chars());
+        let value: i32 = parser.parse(&mut lexer).unwrap();
+        assert_eq!(value, 0);
+    }
+
+    #[test]
+    pub fn test_lr0_typed_parser_root_reducer() {
+        let table =
+            LrTable::build::<0, NoCustomError>(&FIXTURE_LR0_GRAMMAR).expect("cannot build table");
+
+        // START -> E formats the value.
+        let mut reducers = fixture_lr0_reducers();
+        reducers[0] = RuleReducer::new(|_, (e,): (i32,)| Ok(format!("= {e}")));
+
+        let reducers = ReducerSet::new::<FixtureToken>(&FIXTURE_LR0_GRAMMAR, reducers)
+            .expect("cannot check reducers");
+
+        let parser = TypedLrParser::new(&table, &reducers).unwrap();
+
+        // The tokens borrow their values from the source.
+        let source = String::from("1 + 1");
+        let mut lexer = lexer_fixture_lr0_str(&source);
+        let value: String = parser.parse(&mut lexer).unwrap();
+        assert_eq!(value, "= 2");
+    }
+
+    #[test]
+    pub fn test_lr0_typed_parser_entry() {
+        let table = LrTable::build_with_entries::<0, NoCustomError>(&FIXTURE_LR0_GRAMMAR, &["B"])
+            .expect("cannot build table");
+
+        // START -> E formats the value, the entries return their symbol's value.
+        let mut reducers = fixture_lr0_reducers();
+        reducers[0] = RuleReducer::new(|_, (e,): (i32,)| Ok(format!("= {e}")));
+
+        let reducers = ReducerSet::new::<FixtureToken>(&FIXTURE_LR0_GRAMMAR, reducers)
+            .expect("cannot check reducers");
+
+        let parser = TypedLrParser::new(&table, &reducers).unwrap();
+
+        let mut lexer = lexer_fixture_lr0("1 + 1".chars());
+        let value: String = parser.parse_entry("START", &mut lexer).unwrap();
+        assert_eq!(value, "= 2");
+
+        let mut lexer = lexer_fixture_lr0("1".chars());
+        let value: i32 = parser.parse_entry("B", &mut lexer).unwrap();
+        assert_eq!(value, 1);
+
+        // The errors are reported to the observer, whatever the entry.
+        let mut lexer = lexer_fixture_lr0("1 +".chars());
+        let mut trace = TraceRecorder::default();
+        assert!(parser
+            .parse_entry_with::<i32, _, _>("B", &mut lexer, &mut trace)
+            .is_err());
+        assert_eq!(trace.iter().last(), Some(&TraceEvent::Error));
+
+        let mut lexer = lexer_fixture_lr0("1".chars());
+        let mut trace = TraceRecorder::default();
+        assert!(parser
+            .parse_entry_with::<i32, _, _>("E", &mut lexer, &mut trace)
+            .is_err());
+        assert_eq!(trace.iter().last(), Some(&TraceEvent::Error));
+    }
+
+    #[test]
+    pub fn test_typed_parser_checks_table() {
+        let table =
+            LrTable::build::<1, NoCustomError>(&FIXTURE_LR1_GRAMMAR).expect("cannot build table");
+
+        let reducers =
+            ReducerSet::new::<FixtureToken>(&FIXTURE_LR0_GRAMMAR, fixture_lr0_reducers())
+                .expect("cannot check reducers");
+
+        assert!(TypedLrParser::new(&table, &reducers).is_err());
+    }
+
+    #[test]
+    pub fn test_lr1_contextual_lexing() {
+        let table =
+            LrTable::build::<1, NoCustomError>(&FIXTURE_LET_GRAMMAR).expect("cannot build table");
+
+        let lexer_table = LexerBuilder::new()
+            .token("let", "let")
+            .token("=", '=')
+            .regex::<NoCustomError>("id", "[a-z]+")
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let parser = LrParser::<AstNode, _>::new(&table);
+
+        // "letlet" is the longest match, as an identifier.
+        let mut lexer = lexer_table.lex::<NoCustomError>("letlet=x");
+        assert!(parser.parse(&mut lexer).is_err());
+
+        // The parser expects an identifier after "let".
+        let mut lexer = lexer_table.lex::<NoCustomError>("letlet=x").contextual();
+        assert!(parser.parse(&mut lexer).is_ok());
+    }
+
+    #[test]
+    pub fn test_lr1_error_span_and_names() {
+        let syntax = FIXTURE_LET_GRAMMAR.clone().with_name("id", "identifier");
+        let table = LrTable::build::<1, NoCustomError>(&syntax).expect("cannot build table");
+
+        let lexer_table = LexerBuilder::new()
+            .token("let", "let")
+            .token("=", '=')
+            .regex::<NoCustomError>("id", "[a-z]+")
+            .and_then(LexerBuilder::build)
+            .unwrap();
+
+        let parser = LrParser::<AstNode, _>::new(&table);
+
+        let mut lexer = lexer_table.lex::<NoCustomError>("let=x");
+        let err = parser.parse(&mut lexer).unwrap_err();
+
+        assert_eq!(err.to_string(), "unexpected symbol =, expecting identifier");
+        assert_eq!(err.span().map(|span| span.range()), Some(3..4));
+
+        // The end of the stream is located where the lexer stopped.
+        let mut lexer = lexer_table.lex::<NoCustomError>("let");
+        let err = parser.parse(&mut lexer).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "unexpected symbol end of input, expecting identifier"
+        );
+        assert!(err.span().is_some());
+    }
 }
@@ -0,0 +1,163 @@
+use std::io::Write;
+
+use itertools::Itertools as _;
+
+use crate::{syntax::RuleId, YalpError};
+
+use super::StateId;
+
+/// Hooks called by the LR parser at each step of a parse.
+///
+/// Every callback defaults to a no-op, and the unit type is the
+/// observer used by [Parser::parse](crate::prelude::Parser::parse),
+/// so tracing compiles down to nothing when unused.
+pub trait Observer {
+    /// The parse starts from the given state, the entry point's start state.
+    fn start(&mut self, _state: StateId) {}
+
+    /// The terminal has been shifted, and the parser moved to the given state.
+    ///
+    /// The end of the stream is shifted as [EOS](crate::EOS).
+    fn shift(&mut self, _state: StateId, _symbol: &str) {}
+
+    /// The rule has been reduced, popping n symbols from the stacks.
+    fn reduce(&mut self, _rule: RuleId, _popped: usize) {}
+
+    /// The parser moved to the given state after a reduction to the symbol.
+    fn goto(&mut self, _symbol: &str, _state: StateId) {}
+
+    /// The input has been accepted.
+    fn accept(&mut self) {}
+
+    /// The parse failed.
+    fn error<E>(&mut self, _error: &YalpError<E>) {}
+}
+
+impl Observer for () {}
+
+/// Pretty-prints the state stack, and the symbol stack, at each step.
+pub struct PrettyPrinter<W: Write> {
+    out: W,
+    states: Vec<StateId>,
+    symbols: Vec<String>,
+}
+
+impl PrettyPrinter<std::io::Stdout> {
+    pub fn stdout() -> Self {
+        Self::new(std::io::stdout())
+    }
+}
+
+impl<W: Write> PrettyPrinter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            states: vec![],
+            symbols: vec![],
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn print(&mut self, step: std::fmt::Arguments) {
+        // Tracing must never interfere with the parse itself.
+        let _ = writeln!(
+            self.out,
+            "{:<16} | {:<24} | {}",
+            step.to_string(),
+            self.states.iter().join(" "),
+            self.symbols.iter().join(" ")
+        );
+    }
+}
+
+impl<W: Write> Observer for PrettyPrinter<W> {
+    fn start(&mut self, state: StateId) {
+        self.states = vec![state];
+        self.symbols.clear();
+        self.print(format_args!("start"));
+    }
+
+    fn shift(&mut self, state: StateId, symbol: &str) {
+        self.states.push(state);
+        self.symbols.push(symbol.to_string());
+        self.print(format_args!("s{}", state));
+    }
+
+    fn reduce(&mut self, rule: RuleId, popped: usize) {
+        self.states
+            .truncate(self.states.len().saturating_sub(popped).max(1));
+        self.symbols
+            .truncate(self.symbols.len().saturating_sub(popped));
+        self.print(format_args!("r{}", rule));
+    }
+
+    fn goto(&mut self, symbol: &str, state: StateId) {
+        self.states.push(state);
+        self.symbols.push(symbol.to_string());
+        self.print(format_args!("goto {}", state));
+    }
+
+    fn accept(&mut self) {
+        self.print(format_args!("acc"));
+    }
+
+    fn error<E>(&mut self, _error: &YalpError<E>) {
+        self.print(format_args!("error"));
+    }
+}
+
+/// A step of a parse, as recorded by [TraceRecorder].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    Start { state: StateId },
+    Shift { state: StateId, symbol: String },
+    Reduce { rule: RuleId, popped: usize },
+    Goto { symbol: String, state: StateId },
+    Accept,
+    Error,
+}
+
+/// Records the trace of a parse, mostly for testing.
+#[derive(Debug, Default)]
+pub struct TraceRecorder(pub Vec<TraceEvent>);
+
+impl TraceRecorder {
+    pub fn iter(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.0.iter()
+    }
+}
+
+impl Observer for TraceRecorder {
+    fn start(&mut self, state: StateId) {
+        self.0.push(TraceEvent::Start { state })
+    }
+
+    fn shift(&mut self, state: StateId, symbol: &str) {
+        self.0.push(TraceEvent::Shift {
+            state,
+            symbol: symbol.to_string(),
+        })
+    }
+
+    fn reduce(&mut self, rule: RuleId, popped: usize) {
+        self.0.push(TraceEvent::Reduce { rule, popped })
+    }
+
+    fn goto(&mut self, symbol: &str, state: StateId) {
+        self.0.push(TraceEvent::Goto {
+            symbol: symbol.to_string(),
+            state,
+        })
+    }
+
+    fn accept(&mut self) {
+        self.0.push(TraceEvent::Accept)
+    }
+
+    fn error<E>(&mut self, _error: &YalpError<E>) {
+        self.0.push(TraceEvent::Error)
+    }
+}
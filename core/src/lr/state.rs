@@ -0,0 +1,112 @@
+use crate::{ErrorKind, YalpError, YalpResult};
+
+use super::{traits::LrTable, Action, StateId};
+
+/// The stack of states of a LR parser.
+///
+/// It can be paused after any terminal, and queried for the
+/// terminals which may legally come next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserState(Vec<StateId>);
+
+impl Default for ParserState {
+    fn default() -> Self {
+        Self(vec![0])
+    }
+}
+
+impl ParserState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from the given entry point's state.
+    pub fn from_entry<Table: LrTable, Error>(
+        table: &Table,
+        entry: &str,
+    ) -> YalpResult<Self, Error> {
+        table
+            .entry(entry)
+            .map(|state| Self(vec![state]))
+            .ok_or_else(|| YalpError::new(ErrorKind::unknown_symbol(entry), None))
+    }
+
+    /// The state on top of the stack.
+    pub fn current(&self) -> StateId {
+        self.0.last().copied().unwrap()
+    }
+
+    pub fn push(&mut self, state: StateId) {
+        self.0.push(state)
+    }
+
+    /// Pop n states, the initial state is never popped.
+    pub fn pop(&mut self, n: usize) {
+        self.0.truncate(self.0.len().saturating_sub(n).max(1));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StateId> {
+        self.0.iter()
+    }
+
+    /// Perform every reduction triggered by the lookahead symbol,
+    /// and returns the first non-reducing action.
+    ///
+    /// Returns None if the symbol is rejected.
+    fn reduce<Table: LrTable>(&mut self, table: &Table, symbol: &str) -> Option<Action> {
+        loop {
+            match table.action(self.current(), symbol).copied()? {
+                Action::Reduce(rule_id) => {
+                    let reduction = table.reduction(rule_id)?;
+                    self.pop(reduction.arity);
+                    let goto = table.goto(self.current(), &reduction.lhs)?;
+                    self.push(goto);
+                }
+                action => return Some(action),
+            }
+        }
+    }
+
+    /// Check if the terminal can be shifted, or accepted, from this state.
+    ///
+    /// Default reductions are followed, so a terminal which would only
+    /// trigger reductions before being rejected is not accepted.
+    pub fn accepts<Table: LrTable>(&self, table: &Table, symbol: &str) -> bool {
+        self.clone().reduce(table, symbol).is_some()
+    }
+
+    /// Iterate over the terminals which may legally come next.
+    pub fn expected<'state, 'table: 'state, Table: LrTable>(
+        &'state self,
+        table: &'table Table,
+    ) -> impl Iterator<Item = &'table str> + 'state {
+        table
+            .iter_terminals(self.current())
+            .filter(move |sym| self.accepts(table, sym))
+    }
+
+    /// Feed a terminal to the state, without building any AST.
+    pub fn feed<Table: LrTable, Error>(
+        &mut self,
+        table: &Table,
+        symbol: &str,
+    ) -> YalpResult<(), Error> {
+        let mut next = self.clone();
+
+        match next.reduce(table, symbol) {
+            Some(Action::Shift(to)) => {
+                next.push(to);
+                *self = next;
+                Ok(())
+            }
+            Some(Action::Accept) => {
+                *self = next;
+                Ok(())
+            }
+            _ => Err(YalpError::new(
+                ErrorKind::unexpected_symbol(symbol, self.expected(table)),
+                None,
+            )),
+        }
+    }
+}
@@ -1,23 +1,64 @@
-use pb_bnf::symbol::Symbol;
 use prettytable::Table as PtTable;
-use std::{collections::HashMap, u16};
+use std::collections::HashMap;
 
 use crate::{
-    syntax::{PrepSyntax, SymbolSet, Syntax},
+    syntax::{PrepSymbol, PrepSyntax, RuleId, SymbolSet, Syntax, EOS},
     ErrorKind, ItemSetId, YalpError, YalpResult,
 };
 
 use super::{Action, Graph, Transition};
 
 pub mod traits {
-    use crate::lr::Action;
+    use crate::{lr::Action, syntax::RuleId};
+
+    use super::Reduction;
 
     pub trait LrTable {
         fn action<'table>(&'table self, state: usize, symbol: &str) -> Option<&'table Action>;
         fn goto(&self, state: usize, symbol: &str) -> Option<usize>;
+
+        /// Returns the reduction to perform for the given rule.
+        fn reduction(&self, rule: RuleId) -> Option<&Reduction>;
+
+        /// Returns the start state of the entry point.
+        fn entry(&self, symbol: &str) -> Option<usize>;
+
+        /// Returns the augmented rule of the entry point, by its start state.
+        fn entry_rule(&self, entry: usize) -> Option<RuleId>;
+
+        /// Iterate over the terminals having an action in the given state.
+        fn iter_terminals<'table>(
+            &'table self,
+            state: usize,
+        ) -> impl Iterator<Item = &'table str> + 'table;
+
+        /// Iterate over the non-terminals having a goto in the given state.
+        fn iter_non_terminals<'table>(
+            &'table self,
+            state: usize,
+        ) -> impl Iterator<Item = &'table str> + 'table;
+
+        /// Returns the name of the symbol in error messages, if it has one.
+        fn display_name(&self, _symbol: &str) -> Option<&str> {
+            None
+        }
+
+        /// Returns the name of the symbol in error messages, or its identifier.
+        fn describe<'a>(&'a self, symbol: &'a str) -> &'a str {
+            self.display_name(symbol).unwrap_or(symbol)
+        }
     }
 }
 
+/// What a rule reduction does to the parser's stacks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reduction {
+    /// The non-terminal produced by the rule.
+    pub lhs: String,
+    /// The number of symbols consumed from the stacks.
+    pub arity: usize,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct Row {
     actions: HashMap<SymbolId, Action>,
@@ -51,14 +92,13 @@ impl Row {
 impl Row {
     fn from_transition_lr1<'syntax, const K: usize, Error>(
         transition: Transition<'syntax, '_, '_, K>,
-        symbols: &SymbolSet<'syntax>,
         map: &SymbolMap,
     ) -> YalpResult<Self, Error> {
-        let mut actions = HashMap::<Symbol<'syntax>, Action>::default();
-        let mut goto = HashMap::<Symbol<'syntax>, ItemSetId>::default();
+        let mut actions = HashMap::<SymbolId, Action>::default();
+        let mut goto = HashMap::<SymbolId, ItemSetId>::default();
 
         if transition.from.has_item_reaching_eos() {
-            actions.insert(symbols.eos(), Action::Accept);
+            actions.insert(map.get_symbol_id(PrepSymbol::EOS)?, Action::Accept);
         }
 
         for (sym, action) in transition
@@ -67,46 +107,47 @@ impl Row {
             .filter(|(sym, _)| sym.is_terminal())
             .map(|(sym, set)| (*sym, Action::Shift(set.id)))
         {
+            let id = map.get_symbol_id(sym)?;
+
             // Shift/reduce conflict
-            if actions.contains_key(&sym) && matches!(actions[&sym], Action::Reduce(_)) {
+            if actions.contains_key(&id) && matches!(actions[&id], Action::Reduce(_)) {
                 return Err(YalpError::new(
                     ErrorKind::ShiftReduceConflict {
                         state: transition.from.id,
-                        symbol: sym.to_owned(),
-                        conflict: [action, actions[&sym]],
+                        symbol: sym.to_string(),
+                        conflict: [action, actions[&id]],
                     },
                     None,
                 ));
             }
 
-            actions.insert(sym, action);
+            actions.insert(id, action);
         }
 
-        goto.extend(
-            transition
-                .edges
-                .iter()
-                .filter(|(sym, _)| !sym.is_terminal())
-                .map(|(sym, set)| (*sym, set.id)),
-        );
+        for (sym, set) in transition
+            .edges
+            .iter()
+            .filter(|(sym, _)| !sym.is_terminal())
+        {
+            goto.insert(map.get_symbol_id(*sym)?, set.id);
+        }
 
-        actions.extend(
-            transition
-                .from
-                .iter_exhausted_items()
-                .map(|item| (item.lookaheads[0], Action::Reduce(item.rule.id))),
-        );
+        for item in transition.from.iter_exhausted_items() {
+            actions.insert(
+                map.get_symbol_id(item.lookaheads[0])?,
+                Action::Reduce(item.rule.id),
+            );
+        }
 
         Ok(Self::new(actions, goto))
     }
 
     fn from_transition_lr0<'syntax, const K: usize, Error>(
         transition: Transition<'syntax, '_, '_, K>,
-        symbols: &SymbolSet<'syntax>,
         map: &SymbolMap,
     ) -> YalpResult<Self, Error> {
-        let mut actions = HashMap::<Symbol<'syntax>, Action>::default();
-        let mut goto = HashMap::<Symbol<'syntax>, ItemSetId>::default();
+        let mut actions = HashMap::<SymbolId, Action>::default();
+        let mut goto = HashMap::<SymbolId, ItemSetId>::default();
 
         for (sym, action) in transition
             .edges
@@ -115,59 +156,62 @@ impl Row {
             .filter(|(sym, _)| !sym.is_eos())
             .map(|(sym, set)| (*sym, Action::Shift(set.id)))
         {
+            let id = map.get_symbol_id(sym)?;
+
             // Shift/reduce conflict
-            if actions.contains_key(&sym) && matches!(actions[&sym], Action::Reduce(_)) {
+            if actions.contains_key(&id) && matches!(actions[&id], Action::Reduce(_)) {
                 return Err(YalpError::new(
                     ErrorKind::ShiftReduceConflict {
                         state: transition.from.id,
-                        symbol: sym.to_owned(),
-                        conflict: [action, actions[&sym]],
+                        symbol: sym.to_string(),
+                        conflict: [action, actions[&id]],
                     },
                     None,
                 ));
             }
 
-            actions.insert(sym, action);
+            actions.insert(id, action);
         }
 
-        goto.extend(
-            transition
-                .edges
-                .iter()
-                .filter(|(sym, _)| !sym.is_terminal())
-                .map(|(sym, set)| (*sym, set.id)),
-        );
+        for (sym, set) in transition
+            .edges
+            .iter()
+            .filter(|(sym, _)| !sym.is_terminal())
+        {
+            goto.insert(map.get_symbol_id(*sym)?, set.id);
+        }
 
         if transition.from.has_item_reaching_eos() {
-            actions.insert(symbols.eos(), Action::Accept);
+            actions.insert(map.get_symbol_id(PrepSymbol::EOS)?, Action::Accept);
         }
 
         if transition.from.has_exhausted_items() {
             let rule_id = transition.from.get_exhausted_rule();
             actions.extend(
-                symbols
-                    .iter_terminals()
-                    .map(|sym| (sym, Action::Reduce(rule_id))),
+                map.iter_terminals()
+                    .filter_map(|sym| map.get_internal_id(sym))
+                    .map(|id| (id, Action::Reduce(rule_id))),
             );
         }
 
         Ok(Self::new(actions, goto))
     }
+
     pub fn from_transition<'syntax, const K: usize, Error>(
         transition: Transition<'syntax, '_, '_, K>,
-        symbols: &SymbolSet<'syntax>,
         map: &SymbolMap,
     ) -> YalpResult<Self, Error> {
         if K == 0 {
-            Self::from_transition_lr0(transition, symbols, map)
+            Self::from_transition_lr0(transition, map)
         } else if K == 1 {
-            Self::from_transition_lr1(transition, symbols, map)
+            Self::from_transition_lr1(transition, map)
         } else {
             Err(YalpError::new(ErrorKind::UnsupportedAlgorithm, None))
         }
     }
 }
 
+#[derive(PartialEq)]
 struct SymbolMap {
     terminals: Vec<String>,
     non_terminals: Vec<String>,
@@ -175,11 +219,15 @@ struct SymbolMap {
 
 type SymbolId = u16;
 
-impl From<SymbolSet<'_>> for SymbolMap {
-    fn from(value: SymbolSet<'_>) -> Self {
+impl From<&SymbolSet<'_>> for SymbolMap {
+    fn from(value: &SymbolSet<'_>) -> Self {
         Self::new(
-            value.terminals.iter().map(|sym| sym.deref()),
-            value.non_terminals.iter().map(|sym| sym.deref()),
+            value
+                .terminals
+                .iter()
+                .map(|sym| sym.to_string())
+                .chain([EOS.to_string()]),
+            value.non_terminals.iter().map(|sym| sym.to_string()),
         )
     }
 }
@@ -201,11 +249,30 @@ impl SymbolMap {
             .map(|sym| sym.as_str())
     }
 
+    pub fn iter_terminals(&self) -> impl Iterator<Item = &str> {
+        self.terminals.iter().map(|sym| sym.as_str())
+    }
+
+    pub fn iter_non_terminals(&self) -> impl Iterator<Item = &str> {
+        self.non_terminals.iter().map(|sym| sym.as_str())
+    }
+
     pub fn get_internal_id(&self, symbol_id: &str) -> Option<SymbolId> {
         self.iter()
             .enumerate()
-            .find(|(_, sym)| sym == symbol_id)
-            .map(|(iid, _)| iid)
+            .find(|(_, sym)| *sym == symbol_id)
+            .map(|(iid, _)| iid as SymbolId)
+    }
+
+    /// Translate a symbol of the syntax into its internal id.
+    pub fn get_symbol_id<Error>(&self, symbol: PrepSymbol<'_>) -> YalpResult<SymbolId, Error> {
+        let symbol_id = match symbol {
+            PrepSymbol::Terminal(sym) | PrepSymbol::NonTerminal(sym) => sym.to_string(),
+            PrepSymbol::EOS => EOS.to_string(),
+        };
+
+        self.get_internal_id(&symbol_id)
+            .ok_or_else(|| YalpError::new(ErrorKind::unknown_symbol(&symbol_id), None))
     }
 }
 
@@ -215,6 +282,12 @@ pub struct LrTable {
     symbols: SymbolMap,
     /// The table rows
     rows: Vec<Row>,
+    /// The reductions, indexed by rule
+    reductions: Vec<Reduction>,
+    /// The entry points' symbol and augmented rule, indexed by their start state
+    entries: Vec<(String, RuleId)>,
+    /// The names of the terminals in error messages
+    names: HashMap<String, String>,
 }
 
 impl std::fmt::Debug for LrTable {
@@ -224,7 +297,7 @@ impl std::fmt::Debug for LrTable {
     }
 }
 
-impl<'syntax> std::fmt::Display for LrTable {
+impl std::fmt::Display for LrTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut table = PtTable::new();
 
@@ -234,26 +307,23 @@ impl<'syntax> std::fmt::Display for LrTable {
                 .chain(
                     self.symbols
                         .iter_terminals()
-                        .chain(self.symbols.iter_non_terminals())
-                        .map(|sym| sym.id),
+                        .chain(self.symbols.iter_non_terminals()),
                 )
                 .collect(),
         );
 
-        for (id, row) in self.iter().enumerate() {
+        for id in 0..self.rows.len() {
             table.add_row(
                 [id.to_string()]
                     .into_iter()
                     .chain(self.symbols.iter_terminals().map(|sym| {
-                        row.actions
-                            .get(&sym)
+                        traits::LrTable::action(self, id, sym)
                             .map(ToString::to_string)
                             .unwrap_or_default()
                     }))
                     .chain(self.symbols.iter_non_terminals().map(|sym| {
-                        row.goto
-                            .get(&sym)
-                            .map(ToString::to_string)
+                        traits::LrTable::goto(self, id, sym)
+                            .map(|to| to.to_string())
                             .unwrap_or_default()
                     }))
                     .collect(),
@@ -266,41 +336,106 @@ impl<'syntax> std::fmt::Display for LrTable {
 
 impl traits::LrTable for LrTable {
     fn action<'table>(&'table self, state: usize, symbol: &str) -> Option<&'table Action> {
-        self.rows.get(state).and_then(|row| row.action(symbol))
+        let symbol_id = self.symbols.get_internal_id(symbol)?;
+        self.rows.get(state).and_then(|row| row.action(&symbol_id))
     }
 
     fn goto(&self, state: usize, symbol: &str) -> Option<usize> {
-        self.rows.get(state).and_then(|row| row.goto(symbol))
+        let symbol_id = self.symbols.get_internal_id(symbol)?;
+        self.rows.get(state).and_then(|row| row.goto(&symbol_id))
     }
-}
 
-impl LrTable {
-    fn iter(&self) -> impl Iterator<Item = &Row> {
-        self.rows.iter()
+    fn reduction(&self, rule: RuleId) -> Option<&Reduction> {
+        self.reductions.get(rule)
+    }
+
+    fn entry(&self, symbol: &str) -> Option<usize> {
+        self.entries.iter().position(|(entry, _)| entry == symbol)
+    }
+
+    fn entry_rule(&self, entry: usize) -> Option<RuleId> {
+        self.entries.get(entry).map(|&(_, rule)| rule)
+    }
+
+    fn iter_terminals<'table>(
+        &'table self,
+        state: usize,
+    ) -> impl Iterator<Item = &'table str> + 'table {
+        self.symbols
+            .iter_terminals()
+            .filter(move |sym| self.action(state, sym).is_some())
     }
 
+    fn iter_non_terminals<'table>(
+        &'table self,
+        state: usize,
+    ) -> impl Iterator<Item = &'table str> + 'table {
+        self.symbols
+            .iter_non_terminals()
+            .filter(move |sym| self.goto(state, sym).is_some())
+    }
+
+    fn display_name(&self, symbol: &str) -> Option<&str> {
+        self.names.get(symbol).map(String::as_str)
+    }
+}
+
+impl LrTable {
     fn from_graph<'syntax, 'gen, const K: usize, Error>(
         graph: &Graph<'syntax, 'gen, K>,
         syntax: &'gen PrepSyntax<'syntax>,
+        names: HashMap<String, String>,
     ) -> YalpResult<Self, Error> {
-        let symbols = Symbol::from(syntax.symbols);
+        let symbols = SymbolMap::from(&syntax.symbols);
 
         Ok(Self {
-            symbols,
             rows: graph
                 .iter_transitions()
-                .map(|t| Row::from_transition(t, &syntax.symbols, &symbols))
+                .map(|t| Row::from_transition(t, &symbols))
                 .collect::<YalpResult<Vec<_>, Error>>()?,
+            reductions: syntax
+                .rules
+                .iter()
+                .map(|rule| Reduction {
+                    lhs: rule.lhs.to_string(),
+                    arity: rule.rhs.iter().filter(|sym| !sym.is_eos()).count(),
+                })
+                .collect(),
+            entries: syntax
+                .iter_entries()
+                .zip(syntax.entries.iter().copied())
+                .map(|(sym, rule)| (sym.to_string(), rule))
+                .collect(),
+            symbols,
+            names,
         })
     }
 
     /// Build a LR Table parser from a grammar.
-    pub fn build<const K: usize, G, Error>(syntax: &Syntax) -> YalpResult<Self, Error> {
-        let rules = PrepSyntax::from(syntax);
+    pub fn build<const K: usize, Error>(syntax: &Syntax) -> YalpResult<Self, Error> {
+        Self::build_with_entries::<K, Error>(syntax, &[])
+    }
+
+    /// Build a LR Table parser from a grammar, with additional entry points.
+    ///
+    /// The root rule (#0) remains the default entry point, each
+    /// additional entry gets its own start state.
+    pub fn build_with_entries<const K: usize, Error>(
+        syntax: &Syntax,
+        entries: &[&str],
+    ) -> YalpResult<Self, Error> {
+        let rules = PrepSyntax::with_entries(syntax, entries)?;
 
         let mut graph = Graph::<K>::new(&rules);
         graph.build()?;
 
-        LrTable::from_graph(&graph, &rules)
+        // The end of the stream is named, unless the syntax names it.
+        let names = [(EOS, "end of input")]
+            .into_iter()
+            .chain(syntax.iter_names())
+            .map(|(symbol, name)| (symbol.to_string(), name.to_string()))
+            .collect();
+
+        LrTable::from_graph(&graph, &rules, names)
     }
 }
@@ -0,0 +1,127 @@
+use crate::{
+    lexer::traits::Lexer,
+    reducer::{ReducerSet, Value},
+    token::traits::IntoOwnedToken,
+    ErrorKind, YalpError, YalpResult,
+};
+
+use super::{drive, traits::LrTable, Observer, ParserState};
+
+/// A LR parser producing typed semantic values.
+///
+/// The values are computed by the rule reducers, and stored on a
+/// type-erased stack. Tokens are detached from the stream before
+/// being stored, so reducers take owned tokens.
+///
+/// The augmented rule of the entry point is reduced when the input is
+/// accepted: the root rule by its reducer, whereas the rules added for
+/// the other entry points return the value of their symbol.
+pub struct TypedLrParser<'table, 'reducers, 'syntax, Table, Error>
+where
+    Table: LrTable,
+{
+    table: &'table Table,
+    reducers: &'reducers ReducerSet<'syntax, Error>,
+}
+
+impl<'table, 'reducers, 'syntax, Table, Error>
+    TypedLrParser<'table, 'reducers, 'syntax, Table, Error>
+where
+    Table: LrTable,
+    Error: Clone,
+{
+    /// Check the reducers against the table's rules.
+    pub fn new(
+        table: &'table Table,
+        reducers: &'reducers ReducerSet<'syntax, Error>,
+    ) -> YalpResult<Self, Error> {
+        reducers.check(table)?;
+        Ok(Self { table, reducers })
+    }
+
+    pub fn parse<T, L>(&self, lexer: &mut L) -> YalpResult<T, Error>
+    where
+        T: 'static,
+        L: Lexer<Error>,
+        L::Token: IntoOwnedToken,
+    {
+        self.parse_with(lexer, &mut ())
+    }
+
+    /// Parse the stream, and report every step to the observer.
+    pub fn parse_with<T, L, O>(&self, lexer: &mut L, observer: &mut O) -> YalpResult<T, Error>
+    where
+        T: 'static,
+        L: Lexer<Error>,
+        L::Token: IntoOwnedToken,
+        O: Observer,
+    {
+        self.run(ParserState::new(), lexer, observer)
+            .inspect_err(|err| observer.error(err))
+    }
+
+    /// Parse the stream from the given entry point.
+    pub fn parse_entry<T, L>(&self, entry: &str, lexer: &mut L) -> YalpResult<T, Error>
+    where
+        T: 'static,
+        L: Lexer<Error>,
+        L::Token: IntoOwnedToken,
+    {
+        self.parse_entry_with(entry, lexer, &mut ())
+    }
+
+    /// Parse the stream from the given entry point, and report every step to the observer.
+    pub fn parse_entry_with<T, L, O>(
+        &self,
+        entry: &str,
+        lexer: &mut L,
+        observer: &mut O,
+    ) -> YalpResult<T, Error>
+    where
+        T: 'static,
+        L: Lexer<Error>,
+        L::Token: IntoOwnedToken,
+        O: Observer,
+    {
+        ParserState::from_entry(self.table, entry)
+            .and_then(|states| self.run(states, lexer, observer))
+            .inspect_err(|err| observer.error(err))
+    }
+
+    fn run<T, L, O>(
+        &self,
+        states: ParserState,
+        lexer: &mut L,
+        observer: &mut O,
+    ) -> YalpResult<T, Error>
+    where
+        T: 'static,
+        L: Lexer<Error>,
+        L::Token: IntoOwnedToken,
+        O: Observer,
+    {
+        let value = drive(
+            self.table,
+            states,
+            lexer,
+            observer,
+            |tok| Box::new(tok.into_owned()) as Value,
+            |rule_id, _, rhs| self.reducers.reduce(rule_id, rhs),
+            |entry, values| {
+                let rule_id = self
+                    .table
+                    .entry_rule(entry)
+                    .ok_or_else(|| YalpError::new(ErrorKind::UnknownRule(entry), None))?;
+
+                self.reducers.accept(rule_id, values)
+            },
+        )?;
+
+        value.downcast::<T>().map(|value| *value).map_err(|_| {
+            YalpError::new(
+                ErrorKind::UnexpectedValueType(std::any::type_name::<T>()),
+                None,
+            )
+        })
+    }
+}
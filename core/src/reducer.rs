@@ -0,0 +1,326 @@
+//! Typed semantic values for LR parsers.
+//!
+//! Each rule gets a reducer, a closure taking the values of the rule's
+//! right-hand side as a tuple, and returning the value of its left-hand side.
+//!
+//! Values are stored on a type-erased stack, the consistency of the
+//! reducers with the syntax is checked once when building the [ReducerSet],
+//! and with the parser's table when building the parser.
+use std::any::{type_name, Any, TypeId};
+
+use crate::{
+    lr::traits::LrTable,
+    syntax::{Rule, RuleId, Syntax},
+    ErrorKind, YalpError, YalpResult,
+};
+
+/// A type-erased semantic value.
+pub type Value = Box<dyn Any>;
+
+/// The type of a semantic value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueType {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl ValueType {
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl std::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// The arguments of a reducer, a tuple of the rule's rhs values.
+pub trait ReducerArgs: Sized + 'static {
+    /// The value type of each symbol of the rhs.
+    fn types() -> Vec<ValueType>;
+
+    /// Downcast the values into the tuple.
+    ///
+    /// # Panics
+    /// Panics if the values do not match the types, which cannot happen
+    /// once the reducers have been checked against the syntax.
+    fn from_values(values: impl Iterator<Item = Value>) -> Self;
+}
+
+macro_rules! impl_reducer_args {
+    ($($ty:ident),*) => {
+        impl<$($ty: 'static),*> ReducerArgs for ($($ty,)*) {
+            fn types() -> Vec<ValueType> {
+                vec![$(ValueType::of::<$ty>()),*]
+            }
+
+            #[allow(unused_mut, unused_variables, clippy::unused_unit)]
+            fn from_values(mut values: impl Iterator<Item = Value>) -> Self {
+                ($(
+                    *values
+                        .next()
+                        .and_then(|value| value.downcast::<$ty>().ok())
+                        .expect("unchecked reducer"),
+                )*)
+            }
+        }
+    };
+}
+
+impl_reducer_args!();
+impl_reducer_args!(A);
+impl_reducer_args!(A, B);
+impl_reducer_args!(A, B, C);
+impl_reducer_args!(A, B, C, D);
+impl_reducer_args!(A, B, C, D, E);
+impl_reducer_args!(A, B, C, D, E, F);
+impl_reducer_args!(A, B, C, D, E, F, G);
+impl_reducer_args!(A, B, C, D, E, F, G, H);
+
+type ErasedReducer<'syntax, Error> =
+    Box<dyn Fn(&Rule<'syntax>, &mut dyn Iterator<Item = Value>) -> YalpResult<Value, Error>>;
+
+/// The reducer of a rule.
+///
+/// # Example
+/// ```ignore
+/// // E -> E + B
+/// RuleReducer::new(|_, (lhs, _, rhs): (i32, Token, i32)| Ok(lhs + rhs))
+/// ```
+pub struct RuleReducer<'syntax, Error> {
+    output: ValueType,
+    args: Vec<ValueType>,
+    reduce: ErasedReducer<'syntax, Error>,
+}
+
+impl<'syntax, Error> RuleReducer<'syntax, Error> {
+    pub fn new<Args, T, F>(reduce: F) -> Self
+    where
+        Args: ReducerArgs,
+        T: 'static,
+        F: Fn(&Rule<'syntax>, Args) -> YalpResult<T, Error> + 'static,
+    {
+        Self {
+            output: ValueType::of::<T>(),
+            args: Args::types(),
+            reduce: Box::new(move |rule, values| {
+                reduce(rule, Args::from_values(values)).map(|value| Box::new(value) as Value)
+            }),
+        }
+    }
+}
+
+/// The reducers of every rule of a syntax, checked against it.
+pub struct ReducerSet<'syntax, Error> {
+    syntax: &'syntax Syntax<'syntax>,
+    reducers: Vec<RuleReducer<'syntax, Error>>,
+}
+
+impl<'syntax, Error> ReducerSet<'syntax, Error> {
+    /// Check the reducers against the syntax.
+    ///
+    /// Terminals carry the token's type, and each non-terminal carries
+    /// the output type of its rules, which must be the same for all of them.
+    ///
+    /// The token type is the owned token, see
+    /// [IntoOwnedToken](crate::token::traits::IntoOwnedToken).
+    pub fn new<Token: 'static>(
+        syntax: &'syntax Syntax<'syntax>,
+        reducers: Vec<RuleReducer<'syntax, Error>>,
+    ) -> YalpResult<Self, Error> {
+        let rules = syntax.as_ref();
+
+        if rules.len() != reducers.len() {
+            return Err(YalpError::new(
+                ErrorKind::MissingReducer(reducers.len().min(rules.len())),
+                None,
+            ));
+        }
+
+        let value_type = |symbol: &str| -> Option<ValueType> {
+            if syntax.iter_rules_by_symbol(symbol).next().is_none() {
+                return Some(ValueType::of::<Token>());
+            }
+
+            rules
+                .iter()
+                .zip(reducers.iter())
+                .find(|(rule, _)| rule.lhs.is(symbol))
+                .map(|(_, reducer)| reducer.output)
+        };
+
+        for (rule_id, (rule, reducer)) in rules.iter().zip(reducers.iter()).enumerate() {
+            let rhs = rule.rhs.as_ref();
+
+            if rhs.len() != reducer.args.len() {
+                return Err(YalpError::new(
+                    ErrorKind::ReducerArityMismatch {
+                        rule: rule_id,
+                        expected: rhs.len(),
+                        got: reducer.args.len(),
+                    },
+                    None,
+                ));
+            }
+
+            let symbols = std::iter::once((&rule.lhs, reducer.output))
+                .chain(rhs.iter().zip(reducer.args.iter().copied()));
+
+            for (symbol, got) in symbols {
+                let expected = value_type(symbol.as_ref()).unwrap();
+
+                if expected != got {
+                    return Err(YalpError::new(
+                        ErrorKind::ReducerTypeMismatch {
+                            rule: rule_id,
+                            symbol: symbol.to_string(),
+                            expected: expected.name(),
+                            got: got.name(),
+                        },
+                        None,
+                    ));
+                }
+            }
+        }
+
+        Ok(Self { syntax, reducers })
+    }
+
+    /// Check that the table was built from the reducers' syntax: each
+    /// rule must reduce to the same symbol, with the same arity.
+    pub fn check<Table: LrTable>(&self, table: &Table) -> YalpResult<(), Error> {
+        for (rule_id, (rule, reducer)) in self
+            .syntax
+            .as_ref()
+            .iter()
+            .zip(self.reducers.iter())
+            .enumerate()
+        {
+            let reduction = table
+                .reduction(rule_id)
+                .ok_or_else(|| YalpError::new(ErrorKind::UnknownRule(rule_id), None))?;
+
+            if !rule.lhs.is(&reduction.lhs) {
+                return Err(YalpError::new(
+                    ErrorKind::unknown_symbol(&reduction.lhs),
+                    None,
+                ));
+            }
+
+            if reduction.arity != reducer.args.len() {
+                return Err(YalpError::new(
+                    ErrorKind::ReducerArityMismatch {
+                        rule: rule_id,
+                        expected: reduction.arity,
+                        got: reducer.args.len(),
+                    },
+                    None,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reduce the values by the given rule.
+    pub fn reduce(
+        &self,
+        rule_id: RuleId,
+        mut values: impl Iterator<Item = Value>,
+    ) -> YalpResult<Value, Error> {
+        let rule = self
+            .syntax
+            .as_ref()
+            .get(rule_id)
+            .ok_or_else(|| YalpError::new(ErrorKind::UnknownRule(rule_id), None))?;
+
+        let reducer = self
+            .reducers
+            .get(rule_id)
+            .ok_or_else(|| YalpError::new(ErrorKind::MissingReducer(rule_id), None))?;
+
+        (reducer.reduce)(rule, &mut values)
+    }
+
+    /// Reduce the values of an entry point's augmented rule.
+    ///
+    /// The rules of the syntax are reduced by their reducer, whereas the
+    /// rules added by the table for the other entry points pass the value
+    /// of their symbol.
+    pub fn accept(
+        &self,
+        rule_id: RuleId,
+        mut values: impl Iterator<Item = Value>,
+    ) -> YalpResult<Value, Error> {
+        if rule_id < self.syntax.as_ref().len() {
+            return self.reduce(rule_id, values);
+        }
+
+        values
+            .next()
+            .ok_or_else(|| YalpError::new(ErrorKind::UnknownRule(rule_id), None))
+    }
+
+    /// The value type produced by the root rule.
+    pub fn output(&self) -> Option<ValueType> {
+        self.reducers.first().map(|reducer| reducer.output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fixtures::{fixture_lr0_reducers, FixtureToken, FIXTURE_LR0_GRAMMAR},
+        ErrorKind,
+    };
+
+    use super::{ReducerSet, RuleReducer};
+
+    #[test]
+    fn test_001_reducers_type_check() {
+        assert!(
+            ReducerSet::new::<FixtureToken>(&FIXTURE_LR0_GRAMMAR, fixture_lr0_reducers()).is_ok()
+        );
+
+        // B -> 1 produces a bool, whereas B -> 0 produces an i32.
+        let mut reducers = fixture_lr0_reducers();
+        reducers[5] = RuleReducer::new(|_, (_,): (FixtureToken,)| Ok(true));
+
+        let err = ReducerSet::new::<FixtureToken>(&FIXTURE_LR0_GRAMMAR, reducers)
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::ReducerTypeMismatch { rule: 5, .. }
+        ));
+    }
+
+    #[test]
+    fn test_002_reducers_arity_check() {
+        let mut reducers = fixture_lr0_reducers();
+        reducers[1] = RuleReducer::new(|_, (e,): (i32,)| Ok(e));
+
+        let err = ReducerSet::new::<FixtureToken>(&FIXTURE_LR0_GRAMMAR, reducers)
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::ReducerArityMismatch {
+                rule: 1,
+                expected: 3,
+                got: 1
+            }
+        ));
+    }
+}
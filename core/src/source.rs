@@ -0,0 +1,220 @@
+//! Sources, and the resolution of spans in them.
+//!
+//! A [SourceMap] owns the sources of a project, and hands out the
+//! [FileId]s their spans are located by. Lines are counted from 1, and
+//! columns in chars from 0, as in [Cursor]; the UTF-16 columns are the
+//! ones of the language server protocol.
+use crate::span::{Cursor, Span};
+
+/// A source registered in a [SourceMap].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(u32);
+
+impl FileId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A named source, and the offsets of its lines.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    name: String,
+    source: String,
+    /// The byte offset of each line.
+    lines: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        let source = source.into();
+        let lines = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+
+        Self {
+            name: name.into(),
+            source,
+            lines,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The text of the line, without its line ending.
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let start = *self.lines.get(line.checked_sub(1)?)?;
+        let end = self
+            .lines
+            .get(line)
+            .map_or(self.source.len(), |next| next - 1);
+
+        Some(self.source[start..end].trim_end_matches('\r'))
+    }
+
+    /// The cursor at the byte offset, if it is a char boundary of the source.
+    pub fn cursor(&self, offset: usize) -> Option<Cursor> {
+        if !self.source.is_char_boundary(offset) {
+            return None;
+        }
+
+        let line = self.lines.partition_point(|start| *start <= offset);
+        let column = self.source[self.lines[line - 1]..offset].chars().count();
+
+        Some(Cursor::new(line, column, offset))
+    }
+
+    /// The byte offset of the line and column, which can be the end of
+    /// the line.
+    pub fn offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.lines.get(line.checked_sub(1)?)?;
+        let text = self.line(line)?;
+
+        text.char_indices()
+            .map(|(offset, _)| offset)
+            .chain(std::iter::once(text.len()))
+            .nth(column)
+            .map(|offset| start + offset)
+    }
+
+    /// The column of the cursor, in UTF-16 code units.
+    pub fn utf16_column(&self, cursor: Cursor) -> Option<usize> {
+        let start = *self.lines.get(cursor.line.checked_sub(1)?)?;
+
+        Some(
+            self.source
+                .get(start..cursor.offset)?
+                .chars()
+                .map(char::len_utf16)
+                .sum(),
+        )
+    }
+
+    /// The cursor at the line and UTF-16 column, if it is not inside
+    /// a surrogate pair.
+    pub fn utf16_cursor(&self, line: usize, column: usize) -> Option<Cursor> {
+        let start = *self.lines.get(line.checked_sub(1)?)?;
+        let text = self.line(line)?;
+        let mut units = 0;
+
+        for (offset, ch) in text.char_indices().chain([(text.len(), '\n')]) {
+            if units >= column {
+                return (units == column).then(|| self.cursor(start + offset))?;
+            }
+
+            units += ch.len_utf16();
+        }
+
+        None
+    }
+
+    /// The text of the span.
+    pub fn snippet(&self, span: &Span) -> Option<&str> {
+        self.source.get(span.range())
+    }
+}
+
+/// The sources of a project.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a source.
+    pub fn add(&mut self, name: impl Into<String>, source: impl Into<String>) -> FileId {
+        self.files.push(SourceFile::new(name, source));
+        FileId(self.files.len() as u32 - 1)
+    }
+
+    pub fn get(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file.index())
+    }
+
+    /// The file of the name, if it has been registered.
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.iter()
+            .find(|(_, source)| source.name() == name)
+            .map(|(file, _)| file)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(index, source)| (FileId(index as u32), source))
+    }
+
+    /// The file the span is located in.
+    pub fn file(&self, span: &Span) -> Option<&SourceFile> {
+        self.get(span.file?)
+    }
+
+    /// The text of the span.
+    pub fn snippet(&self, span: &Span) -> Option<&str> {
+        self.file(span)?.snippet(span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::span::{Cursor, Span};
+
+    use super::{SourceFile, SourceMap};
+
+    #[test]
+    fn test_lines_and_cursors() {
+        let file = SourceFile::new("main", "let x;\r\nlet é = 1;\n");
+
+        assert_eq!(file.line_count(), 3);
+        assert_eq!(file.line(1), Some("let x;"));
+        assert_eq!(file.line(2), Some("let é = 1;"));
+        assert_eq!(file.line(3), Some(""));
+        assert_eq!(file.line(4), None);
+
+        let cursor = file.cursor(15).unwrap();
+        assert_eq!(cursor, Cursor::new(2, 6, 15));
+        assert_eq!(file.offset(2, 6), Some(15));
+        assert_eq!(file.cursor(13), None);
+    }
+
+    #[test]
+    fn test_utf16() {
+        let file = SourceFile::new("main", "a😀b");
+
+        let b = file.cursor(5).unwrap();
+        assert_eq!(b.column, 2);
+        assert_eq!(file.utf16_column(b), Some(3));
+        assert_eq!(file.utf16_cursor(1, 3), Some(b));
+        assert_eq!(file.utf16_cursor(1, 2), None);
+        assert_eq!(file.utf16_cursor(1, 4).map(|cursor| cursor.offset), Some(6));
+    }
+
+    #[test]
+    fn test_source_map() {
+        let mut map = SourceMap::new();
+        let main = map.add("main", "fn main() {}");
+        let lib = map.add("lib", "pub mod a;");
+
+        assert_ne!(main, lib);
+        assert_eq!(map.find("lib"), Some(lib));
+
+        let span = Span::new(Cursor::new(1, 4, 4), Cursor::new(1, 7, 7)).in_file(Some(lib));
+        assert_eq!(map.snippet(&span), Some("mod"));
+        assert_eq!(map.snippet(&span.in_file(None)), None);
+    }
+}
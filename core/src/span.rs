@@ -1,27 +1,50 @@
-use std::ops::Add;
+use std::ops::{Add, Range};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+use crate::source::FileId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Cursor {
+    /// The line, counted from 1.
     pub line: usize,
+    /// The column, counted in chars from 0.
     pub column: usize,
+    /// The byte offset in the source, the lexers advance it by the
+    /// length of each unit.
+    pub offset: usize,
 }
 
-impl Ord for Cursor {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        if self.line < self.other.line {
-            return std::cmp::Ordering::Less;
-        }
-        if self.line > self.other.line {
-            return std::cmp::Ordering::Greater;
+impl Cursor {
+    pub fn new(line: usize, column: usize, offset: usize) -> Self {
+        Self {
+            line,
+            column,
+            offset,
         }
+    }
+}
+
+impl PartialOrd for Cursor {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-        self.column.cmp(&other.column)
+impl Ord for Cursor {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.line
+            .cmp(&other.line)
+            .then(self.column.cmp(&other.column))
+            .then(self.offset.cmp(&other.offset))
     }
 }
 
 impl Default for Cursor {
     fn default() -> Self {
-        Self { line: 1, column: 0 }
+        Self {
+            line: 1,
+            column: 0,
+            offset: 0,
+        }
     }
 }
 
@@ -56,33 +79,36 @@ impl std::ops::AddAssign<NextColumn> for Cursor {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 /// The location of the Token in the stream.
 pub struct Span {
+    /// The source, if it is registered in a [SourceMap](crate::source::SourceMap).
+    pub file: Option<FileId>,
     pub from: Cursor,
     pub to: Cursor,
 }
 
 impl From<Cursor> for Span {
     fn from(value: Cursor) -> Self {
-        Self {
-            from: value,
-            to: value,
-        }
+        Self::new(value, value)
     }
 }
 
 impl FromIterator<Span> for Span {
     fn from_iter<T: IntoIterator<Item = Span>>(iter: T) -> Self {
-        let mut span = Span::default();
+        let mut iter = iter.into_iter();
+
+        let Some(mut span) = iter.next() else {
+            return Span::default();
+        };
 
         for item in iter {
             if item.from < span.from {
-                span.from = iter.from;
+                span.from = item.from;
             }
 
             if item.to > span.to {
-                span.to = iter.to;
+                span.to = item.to;
             }
         }
 
@@ -92,7 +118,21 @@ impl FromIterator<Span> for Span {
 
 impl Span {
     pub fn new(from: Cursor, to: Cursor) -> Self {
-        Self { from, to }
+        Self {
+            file: None,
+            from,
+            to,
+        }
+    }
+
+    /// The span, located in the file.
+    pub fn in_file(self, file: Option<FileId>) -> Self {
+        Self { file, ..self }
+    }
+
+    /// The byte range of the span in its source.
+    pub fn range(&self) -> Range<usize> {
+        self.from.offset..self.to.offset
     }
 }
 
@@ -101,8 +141,8 @@ impl Add<NextLine> for Span {
 
     fn add(self, rhs: NextLine) -> Self::Output {
         Self {
-            from: self.from,
             to: self.to + rhs,
+            ..self
         }
     }
 }
@@ -112,8 +152,8 @@ impl Add<NextColumn> for Span {
 
     fn add(self, rhs: NextColumn) -> Self::Output {
         Self {
-            from: self.from,
             to: self.to + rhs,
+            ..self
         }
     }
 }
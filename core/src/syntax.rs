@@ -1,19 +1,102 @@
 use itertools::Itertools;
-use yalp_shared::symbol::{Symbol, SymbolName};
 
 use std::borrow::{Borrow, Cow};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::ops::{Deref, DerefMut};
 
-use pb_bnf::syntax::BnfSyntax;
+use crate::{ErrorKind, YalpError, YalpResult};
 
 pub type RuleId = usize;
 pub type StaticSymbol = SymbolName<'static>;
+pub type StaticRule = Rule<'static>;
+pub type StaticSyntax = Syntax<'static>;
+
+/// Identifier of the augmented start symbol.
+pub const START: &str = "<start>";
+
+/// Identifier of the end-of-stream terminal.
+pub const EOS: &str = "<eos>";
+
+/// The identifier of a symbol, such as `E` or `<ident>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SymbolName<'syntax>(Cow<'syntax, str>);
+
+impl<'syntax> SymbolName<'syntax> {
+    pub const fn new(id: &'syntax str) -> Self {
+        Self(Cow::Borrowed(id))
+    }
+
+    pub fn is(&self, id: &str) -> bool {
+        self.0 == id
+    }
+}
+
+impl AsRef<str> for SymbolName<'_> {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for SymbolName<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for SymbolName<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<'syntax> From<&'syntax str> for SymbolName<'syntax> {
+    fn from(value: &'syntax str) -> Self {
+        Self(Cow::Borrowed(value))
+    }
+}
+
+impl From<String> for SymbolName<'_> {
+    fn from(value: String) -> Self {
+        Self(Cow::Owned(value))
+    }
+}
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
-pub struct Syntax<'syntax>(Cow<'syntax, [Rule<'syntax>]>);
+pub struct Syntax<'syntax> {
+    rules: Cow<'syntax, [Rule<'syntax>]>,
+    /// The names of the terminals in error messages, such as
+    /// "identifier" for `<ident>`.
+    names: BTreeMap<String, String>,
+}
 
 impl<'syntax> Syntax<'syntax> {
+    pub const fn new(rules: &'syntax [Rule<'syntax>]) -> Self {
+        Self {
+            rules: Cow::Borrowed(rules),
+            names: BTreeMap::new(),
+        }
+    }
+
+    /// Name the terminal in error messages.
+    pub fn with_name(mut self, terminal: impl ToString, name: impl ToString) -> Self {
+        self.names.insert(terminal.to_string(), name.to_string());
+        self
+    }
+
+    /// The name of the symbol in error messages, if it has been named.
+    pub fn display_name(&self, symbol: &str) -> Option<&str> {
+        self.names.get(symbol).map(String::as_str)
+    }
+
+    /// Iterate over the named symbols, and their names.
+    pub fn iter_names(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.names
+            .iter()
+            .map(|(symbol, name)| (symbol.as_str(), name.as_str()))
+    }
+
     /// Iterate the rules behind a specific non-terminal symbols.
     pub fn iter_rules_by_symbol<'a>(
         &'a self,
@@ -33,19 +116,22 @@ impl<'syntax> Syntax<'syntax> {
 
 impl<'syntax> FromIterator<Rule<'syntax>> for Syntax<'syntax> {
     fn from_iter<T: IntoIterator<Item = Rule<'syntax>>>(iter: T) -> Self {
-        Self(iter.into_iter().collect())
+        Self {
+            rules: iter.into_iter().collect(),
+            names: BTreeMap::default(),
+        }
     }
 }
 
 impl<'syntax> AsRef<[Rule<'syntax>]> for Syntax<'syntax> {
     fn as_ref(&self) -> &[Rule<'syntax>] {
-        self.0.borrow()
+        self.rules.borrow()
     }
 }
 
 impl<'syntax> AsMut<Vec<Rule<'syntax>>> for Syntax<'syntax> {
     fn as_mut(&mut self) -> &mut Vec<Rule<'syntax>> {
-        self.0.to_mut()
+        self.rules.to_mut()
     }
 }
 
@@ -55,6 +141,15 @@ pub struct Rule<'syntax> {
     pub rhs: Definition<'syntax>,
 }
 
+impl<'syntax> Rule<'syntax> {
+    pub const fn new(lhs: SymbolName<'syntax>, rhs: &'syntax [SymbolName<'syntax>]) -> Self {
+        Self {
+            lhs,
+            rhs: Definition(Cow::Borrowed(rhs)),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Definition<'syntax>(Cow<'syntax, [SymbolName<'syntax>]>);
 
@@ -76,43 +171,30 @@ impl<'syntax> FromIterator<SymbolName<'syntax>> for Definition<'syntax> {
     }
 }
 
-impl<'syntax> From<BnfSyntax<'syntax>> for Syntax<'syntax> {
-    fn from(value: BnfSyntax<'syntax>) -> Self {
-        let mut syntax = Self::default();
-
-        value.iter().cloned().enumerate().for_each(|(i, rule)| {
-            // if root = A0...An, then root = B and B = A0...An
-            if i == 0 && rule.rhs().len() > 1 {
-                syntax.push(Rule {
-                    lhs: SymbolName::from("root"),
-                    rhs: Definition::from_iter([rule.lhs().clone()]),
-                });
-            }
-
-            rule.rhs().iter().for_each(|def| {
-                syntax.push(Rule {
-                    lhs: rule.lhs().clone(),
-                    rhs: def.iter().cloned().map(|term| term.into_symbol()).collect(),
-                })
-            });
-        });
-
-        syntax
-    }
-}
-
 /// Preprocessed syntax for parsing generation
 pub struct PrepSyntax<'syntax> {
     pub symbols: SymbolSet<'syntax>,
     pub rules: Vec<PrepRule<'syntax>>,
+    /// The augmented start rules, one per entry point.
+    ///
+    /// The first entry is always the root rule (#0).
+    pub entries: Vec<RuleId>,
 }
 
+/// The left-hand side of the augmented start rules.
+static START_SYMBOL: StaticSymbol = StaticSymbol::new(START);
+
 impl<'syntax> PrepSyntax<'syntax> {
     pub fn start(&self) -> Option<PrepSymbol<'syntax>> {
         self.symbols.start.map(PrepSymbol::NonTerminal)
     }
 
-    pub fn into_term(&self, symbol: &'syntax SymbolName) -> PrepSymbol<'syntax> {
+    /// Checks if the symbol is the lhs of the root rule.
+    pub fn is_start(&self, symbol: PrepSymbol<'syntax>) -> bool {
+        self.start() == Some(symbol)
+    }
+
+    pub fn into_term(&self, symbol: &'syntax SymbolName<'syntax>) -> PrepSymbol<'syntax> {
         if self.symbols.terminals.contains(symbol) {
             PrepSymbol::Terminal(symbol)
         } else {
@@ -123,42 +205,126 @@ impl<'syntax> PrepSyntax<'syntax> {
     pub fn sym(&self, id: &str) -> Option<PrepSymbol<'syntax>> {
         self.symbols.iter().find(|sym| sym.is(id))
     }
-}
 
-impl<'syntax> From<&Syntax<'syntax>> for PrepSyntax<'syntax> {
-    fn from(syntax: &Syntax<'syntax>) -> Self {
-        let symbols = SymbolSet::from(syntax);
-        let rules = syntax.iter().enumerate().map(|(id, rule)| {
-            let lhs = PrepSymbol::NonTerminal(&rule.lhs);
-            let mut rhs = rule
-                .rhs
-                .iter()
-                .map(|sym| {
-                    if symbols.terminals.contains(sym) {
-                        PrepSymbol::Terminal(sym)
-                    } else {
-                        PrepSymbol::NonTerminal(sym)
-                    }
-                })
-                .collect::<PrepDefinition>();
-
-            // root rule, add <eos>
-            if id == 0 {
-                rhs.push(PrepSymbol::EOS);
+    /// Iterate over the rules.
+    pub fn iter(&self) -> impl Iterator<Item = &PrepRule<'syntax>> {
+        self.rules.iter()
+    }
+
+    /// Iterate over the rules deriving the symbol.
+    pub fn iter_by_symbol(
+        &self,
+        symbol: PrepSymbol<'syntax>,
+    ) -> impl Iterator<Item = &PrepRule<'syntax>> {
+        self.rules.iter().filter(move |rule| rule.lhs == symbol)
+    }
+
+    /// Returns the rule.
+    ///
+    /// # Panics
+    /// Panics if the rule does not exist.
+    pub fn borrow_rule(&self, id: RuleId) -> &PrepRule<'syntax> {
+        &self.rules[id]
+    }
+
+    /// Preprocess the syntax, with additional entry points.
+    ///
+    /// Each entry is a non-terminal symbol which gets its own augmented
+    /// start rule (<start> -> entry <eos>), so one table can parse any of them.
+    pub fn with_entries<Error>(
+        syntax: &'syntax Syntax<'_>,
+        entries: &[&str],
+    ) -> YalpResult<Self, Error> {
+        let mut prep = Self::from(syntax);
+
+        for &entry in entries {
+            let symbol = syntax
+                .iter_symbols()
+                .find(|sym| sym.is(entry) && prep.symbols.non_terminals.contains(sym))
+                .ok_or_else(|| YalpError::new(ErrorKind::unknown_symbol(entry), None))?;
+
+            let id = prep.rules.len();
+
+            prep.rules.push(PrepRule {
+                id,
+                lhs: PrepSymbol::NonTerminal(&START_SYMBOL),
+                rhs: PrepDefinition(vec![PrepSymbol::NonTerminal(symbol), PrepSymbol::EOS]),
+            });
+
+            prep.entries.push(id);
+        }
+
+        Ok(prep)
+    }
+
+    /// Iterate over the entry points' symbol, the root rule's lhs comes first.
+    pub fn iter_entries(&self) -> impl Iterator<Item = PrepSymbol<'syntax>> + '_ {
+        self.entries.iter().enumerate().map(|(i, &rule_id)| {
+            let rule = &self.rules[rule_id];
+            if i == 0 {
+                rule.lhs
+            } else {
+                rule.rhs[0]
             }
-        });
+        })
+    }
+}
 
-        Self { symbols, rules }
+impl<'syntax> From<&'syntax Syntax<'_>> for PrepSyntax<'syntax> {
+    fn from(syntax: &'syntax Syntax<'_>) -> Self {
+        let symbols = SymbolSet::from(syntax);
+        let rules = syntax
+            .as_ref()
+            .iter()
+            .enumerate()
+            .map(|(id, rule)| {
+                let lhs = PrepSymbol::NonTerminal(&rule.lhs);
+                let mut rhs = rule
+                    .rhs
+                    .as_ref()
+                    .iter()
+                    .map(|sym| {
+                        if symbols.terminals.contains(sym) {
+                            PrepSymbol::Terminal(sym)
+                        } else {
+                            PrepSymbol::NonTerminal(sym)
+                        }
+                    })
+                    .collect::<PrepDefinition>();
+
+                // root rule, add <eos>
+                if id == 0 {
+                    rhs.push(PrepSymbol::EOS);
+                }
+
+                PrepRule { id, lhs, rhs }
+            })
+            .collect();
+
+        Self {
+            symbols,
+            rules,
+            entries: vec![0],
+        }
     }
 }
 
 /// Preprocessed syntax rule for parsing generation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PrepRule<'a> {
+    pub id: RuleId,
     pub lhs: PrepSymbol<'a>,
     pub rhs: PrepDefinition<'a>,
 }
 
+impl<'a> PrepRule<'a> {
+    pub fn contains(&self, symbol: PrepSymbol<'a>) -> bool {
+        self.rhs.contains(&symbol)
+    }
+}
+
 /// Preprocessed rule definition for parsing generation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PrepDefinition<'a>(Vec<PrepSymbol<'a>>);
 
 impl<'a> Deref for PrepDefinition<'a> {
@@ -181,11 +347,11 @@ impl<'a> FromIterator<PrepSymbol<'a>> for PrepDefinition<'a> {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 /// Preprocess rule definition term for parsing generation
 pub enum PrepSymbol<'a> {
-    Terminal(&'a SymbolName),
-    NonTerminal(&'a SymbolName),
+    Terminal(&'a SymbolName<'a>),
+    NonTerminal(&'a SymbolName<'a>),
     EOS,
 }
 
@@ -194,28 +360,38 @@ impl PrepSymbol<'_> {
         matches!(self, Self::EOS)
     }
 
+    /// The end of stream is a terminal.
     pub fn is_terminal(&self) -> bool {
-        matches!(self, Self::Terminal(_))
+        matches!(self, Self::Terminal(_) | Self::EOS)
     }
 
     pub fn is_non_terminal(&self) -> bool {
         matches!(self, Self::NonTerminal(_))
     }
 
-    pub fn is(&self, symbol: &SymbolName) -> bool {
+    pub fn is(&self, id: &str) -> bool {
+        match self {
+            PrepSymbol::Terminal(sym) => sym.is(id),
+            PrepSymbol::NonTerminal(sym) => sym.is(id),
+            PrepSymbol::EOS => id == EOS,
+        }
+    }
+}
+
+impl std::fmt::Display for PrepSymbol<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            PrepSymbol::Terminal(sym) => *sym == symbol,
-            PrepSymbol::NonTerminal(sym) => *sym == symbol,
-            PrepSymbol::EOS => false,
+            PrepSymbol::Terminal(sym) | PrepSymbol::NonTerminal(sym) => sym.fmt(f),
+            PrepSymbol::EOS => f.write_str(EOS),
         }
     }
 }
 
 #[derive(Default, Clone)]
 pub struct SymbolSet<'syntax> {
-    pub terminals: HashSet<SymbolName<'syntax>>,
-    pub non_terminals: HashSet<SymbolName<'syntax>>,
-    pub start: Option<SymbolName<'syntax>>,
+    pub terminals: HashSet<&'syntax SymbolName<'syntax>>,
+    pub non_terminals: HashSet<&'syntax SymbolName<'syntax>>,
+    pub start: Option<&'syntax SymbolName<'syntax>>,
 }
 
 impl<'syntax> SymbolSet<'syntax> {
@@ -223,20 +399,25 @@ impl<'syntax> SymbolSet<'syntax> {
         self.terminals
             .iter()
             .copied()
-            .map(Symbol::Terminal)
-            .chain(self.non_terminals.iter().copied().map(Symbol::NonTerminal))
+            .map(PrepSymbol::Terminal)
+            .chain(
+                self.non_terminals
+                    .iter()
+                    .copied()
+                    .map(PrepSymbol::NonTerminal),
+            )
     }
 }
 
-impl<'a> From<&'a Syntax> for SymbolSet<'a> {
-    fn from(syntax: &'a Syntax) -> Self {
+impl<'syntax> From<&'syntax Syntax<'_>> for SymbolSet<'syntax> {
+    fn from(syntax: &'syntax Syntax<'_>) -> Self {
         let mut set = SymbolSet::default();
 
         syntax.iter_symbols().for_each(|sym| {
-            if syntax.iter_rules_by_symbol(sym).any(|_| true) {
-                set.non_terminals.insert(sym)
+            if syntax.iter_rules_by_symbol(sym).next().is_some() {
+                set.non_terminals.insert(sym);
             } else {
-                set.terminals.insert(sym)
+                set.terminals.insert(sym);
             }
         });
 
@@ -1,33 +1,208 @@
-use crate::span::Span;
+use std::{any::Any, borrow::Cow, fmt::Debug, sync::Arc};
+
+use crate::{
+    lexer::{Keyword, Symbol},
+    span::Span,
+};
 
 pub mod traits {
+    use crate::{
+        lexer::{Keyword, Symbol},
+        span::Span,
+    };
+
     pub trait Token: Clone {
         fn symbol_id(&self) -> &str;
+
+        /// The location of the token, if it keeps track of it.
+        fn span(&self) -> Option<Span> {
+            None
+        }
+
+        /// The keyword the token spells, cheaper to compare than its kind.
+        fn keyword(&self) -> Option<Keyword> {
+            None
+        }
+
+        /// The interned value of the token, such as an identifier.
+        fn symbol(&self) -> Option<Symbol> {
+            None
+        }
+    }
+
+    /// A token which can be detached from the stream it borrows from.
+    pub trait IntoOwnedToken {
+        type Owned: 'static;
+
+        fn into_owned(self) -> Self::Owned;
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Token<'stream> {
-    pub kind: String,
-    pub value: &'stream str,
+/// A token, whose value is a slice of the input : `str` for text,
+/// and `[u8]` for binary inputs.
+///
+/// Tokens are equal regardless of their payloads, which are not comparable.
+pub struct Token<'stream, Slice = str>
+where
+    Slice: ?Sized + ToOwned + 'static,
+{
+    /// The kind, borrowed from the lexer's table.
+    pub kind: Cow<'stream, str>,
+    /// The value, borrowed from in-memory sources.
+    pub value: Cow<'stream, Slice>,
     pub span: Span,
+    /// The trivia (comments, ...) preceding the token.
+    pub trivia: Vec<Token<'stream, Slice>>,
+    /// The value computed by the lexer's callback.
+    pub payload: Option<Payload>,
+    /// The keyword, if the lexer found the value in its keywords.
+    pub keyword: Option<Keyword>,
+    /// The interned value, if the lexer interns the tokens of its kind.
+    pub symbol: Option<Symbol>,
+}
+
+/// A value computed from a token's slice, such as a parsed integer
+/// or an unescaped string.
+///
+/// Payloads are shared between the clones of a token, and can be
+/// sent across threads along with it.
+#[derive(Clone)]
+pub struct Payload(Arc<dyn Any + Send + Sync>);
+
+impl Payload {
+    pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl Debug for Payload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Payload").finish_non_exhaustive()
+    }
+}
+
+impl<'stream, Slice> PartialEq for Token<'stream, Slice>
+where
+    Slice: ?Sized + ToOwned + PartialEq + 'static,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.value == other.value
+            && self.span == other.span
+            && self.trivia == other.trivia
+            && self.keyword == other.keyword
+            && self.symbol == other.symbol
+    }
 }
 
-impl<'kind> traits::Token for Token<'kind> {
+impl<'stream, Slice> Eq for Token<'stream, Slice> where Slice: ?Sized + ToOwned + Eq + 'static {}
+
+impl<'stream, Slice> traits::Token for Token<'stream, Slice>
+where
+    Slice: ?Sized + ToOwned + 'static,
+{
     fn symbol_id(&self) -> &str {
         &self.kind
     }
+
+    fn span(&self) -> Option<Span> {
+        Some(self.span)
+    }
+
+    fn keyword(&self) -> Option<Keyword> {
+        self.keyword
+    }
+
+    fn symbol(&self) -> Option<Symbol> {
+        self.symbol
+    }
 }
 
-impl<'stream> Token<'stream> {
-    pub fn new<S>(kind: S, value: &'stream str, span: Span) -> Self
+impl<'stream, Slice> Token<'stream, Slice>
+where
+    Slice: ?Sized + ToOwned + 'static,
+{
+    pub fn new<S, V>(kind: S, value: V, span: Span) -> Self
     where
-        S: ToString,
+        S: Into<Cow<'stream, str>>,
+        V: Into<Cow<'stream, Slice>>,
     {
         Self {
-            kind: kind.to_string(),
-            value,
+            kind: kind.into(),
+            value: value.into(),
             span,
+            trivia: vec![],
+            payload: None,
+            keyword: None,
+            symbol: None,
         }
     }
+
+    /// The payload, if it is a T.
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        self.payload.as_ref().and_then(Payload::downcast_ref)
+    }
+
+    /// The token, owning its value so that it outlives the stream.
+    pub fn into_owned(self) -> Token<'static, Slice> {
+        Token {
+            kind: Cow::Owned(self.kind.into_owned()),
+            value: Cow::Owned(self.value.into_owned()),
+            span: self.span,
+            trivia: self.trivia.into_iter().map(Token::into_owned).collect(),
+            payload: self.payload,
+            keyword: self.keyword,
+            symbol: self.symbol,
+        }
+    }
+}
+
+impl<'stream, Slice> traits::IntoOwnedToken for Token<'stream, Slice>
+where
+    Slice: ?Sized + ToOwned + 'static,
+{
+    type Owned = Token<'static, Slice>;
+
+    fn into_owned(self) -> Self::Owned {
+        Token::into_owned(self)
+    }
+}
+
+impl<'stream, Slice> Clone for Token<'stream, Slice>
+where
+    Slice: ?Sized + ToOwned + 'static,
+{
+    fn clone(&self) -> Self {
+        Self {
+            kind: self.kind.clone(),
+            value: self.value.clone(),
+            span: self.span,
+            trivia: self.trivia.clone(),
+            payload: self.payload.clone(),
+            keyword: self.keyword,
+            symbol: self.symbol,
+        }
+    }
+}
+
+impl<'stream, Slice> Debug for Token<'stream, Slice>
+where
+    Slice: ?Sized + ToOwned + Debug + 'static,
+    Slice::Owned: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Token")
+            .field("kind", &self.kind)
+            .field("value", &self.value)
+            .field("span", &self.span)
+            .field("trivia", &self.trivia)
+            .field("payload", &self.payload)
+            .field("keyword", &self.keyword)
+            .field("symbol", &self.symbol)
+            .finish()
+    }
 }
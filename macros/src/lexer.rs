@@ -1,29 +1,55 @@
 use proc_macro2::{Group, Ident, Literal, TokenStream, TokenTree};
-use yalp_core::{traits::Token as _, YalpError, YalpResult};
+use yalp_core::{span::Cursor, token::traits::{IntoOwnedToken, Token as _}, Span, YalpError, YalpResult};
 
 use crate::Error;
 
 #[derive(Debug, Clone)]
 pub(crate) struct Token(TokenTree);
 
+impl Token {
+    /// The span of the token tree, with line and columns.
+    fn locate(&self) -> Span {
+        let span = self.0.span();
+        let range = span.byte_range();
+
+        Span::new(
+            Cursor::new(span.start().line, span.start().column, range.start),
+            Cursor::new(span.end().line, span.end().column, range.end),
+        )
+    }
+}
+
 impl yalp_core::token::traits::Token for Token {
     fn symbol_id(&self) -> &str {
         match &self.0 {
             TokenTree::Group(_) => "<group>",
             TokenTree::Ident(_) => "<ident>",
-            TokenTree::Punct(punct) => match punct.to_string().as_str() {
-                ":" => ":",
-                "," => ",",
-                ";" => ";",
-                "=" => "=",
-                ">" => ">",
-                "<" => "<",
-                "-" => "-",
+            TokenTree::Punct(punct) => match punct.as_char() {
+                ':' => ":",
+                ',' => ",",
+                ';' => ";",
+                '=' => "=",
+                '>' => ">",
+                '<' => "<",
+                '-' => "-",
                 _ => "<illegal>",
             },
             TokenTree::Literal(_) => "<lit>",
         }
     }
+
+    fn span(&self) -> Option<Span> {
+        Some(self.locate())
+    }
+}
+
+/// Token trees do not borrow from the stream.
+impl IntoOwnedToken for Token {
+    type Owned = Token;
+
+    fn into_owned(self) -> Self::Owned {
+        self
+    }
 }
 
 impl TryFrom<Token> for Group {
@@ -32,7 +58,7 @@ impl TryFrom<Token> for Group {
     fn try_from(value: Token) -> Result<Self, Self::Error> {
         match value.0 {
             TokenTree::Group(group) => Ok(group),
-            _ => Err(yalp_core::ErrorKind::unexpected_symbol("<group>", [value.symbol_id()]).into()),
+            _ => Err(yalp_core::ErrorKind::unexpected_symbol(value.symbol_id(), ["<group>"]).into()),
         }
     }
 }
@@ -43,7 +69,7 @@ impl TryFrom<Token> for Ident {
     fn try_from(value: Token) -> Result<Self, Self::Error> {
         match value.0 {
             TokenTree::Ident(ident) => Ok(ident),
-            _ => Err(yalp_core::ErrorKind::unexpected_symbol("<ident>", [value.symbol_id()]).into()),
+            _ => Err(yalp_core::ErrorKind::unexpected_symbol(value.symbol_id(), ["<ident>"]).into()),
         }
     }
 }
@@ -54,13 +80,13 @@ impl TryFrom<Token> for Literal {
     fn try_from(value: Token) -> Result<Self, Self::Error> {
         match value.0 {
             TokenTree::Literal(lit) => Ok(lit),
-            _ => Err(yalp_core::ErrorKind::unexpected_symbol("<lit>", [value.symbol_id()]).into()),
+            _ => Err(yalp_core::ErrorKind::unexpected_symbol(value.symbol_id(), ["<lit>"]).into()),
         }
     }
 }
 
 pub(crate) struct Lexer {
-    current_span: yalp_core::Span,
+    current_span: Span,
     stream: proc_macro2::token_stream::IntoIter,
 }
 
@@ -68,7 +94,7 @@ impl Lexer {
     pub fn new(stream: TokenStream) -> Self {
         Self {
             stream: stream.into_iter(),
-            current_span: yalp_core::Span::default(),
+            current_span: Span::default(),
         }
     }
 }
@@ -77,16 +103,16 @@ impl Iterator for Lexer {
     type Item = YalpResult<Token, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let tt = self.stream.next()?;
-        self.current_span = yalp_core::Span::new(0, 0);
-        Some(Ok(Token(tt)))
+        let tok = Token(self.stream.next()?);
+        self.current_span = tok.locate();
+        Some(Ok(tok))
     }
 }
 
-impl yalp_core::traits::Lexer<Error> for Lexer {
+impl yalp_core::lexer::traits::Lexer<Error> for Lexer {
     type Token = Token;
 
-    fn span(&self) -> yalp_core::Span {
+    fn span(&self) -> Span {
         self.current_span
     }
 }
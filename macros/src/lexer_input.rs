@@ -0,0 +1,514 @@
+//! The `lexer!` macro.
+//!
+//! The patterns are compiled into automata while the macro expands, and
+//! the generated lexer only assembles their tables on first use.
+use std::collections::HashSet;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    braced,
+    ext::IdentExt as _,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    spanned::Spanned as _,
+    Attribute, Ident, LitStr, Path, Token as T, Type, Visibility,
+};
+use yalp_core::{
+    dfa::Classes as _,
+    lexer::{LexerBuilder, LexerTable, ModeChange, TokenRole},
+    NoCustomError,
+};
+
+use crate::parse_symbol_ident_set;
+
+/// A declared token : `Variant [: kind] = "pattern" [=> action, ...];`
+struct TokenRule {
+    variant: Ident,
+    /// The kind, spelled as a terminal of `grammar!`.
+    kind: String,
+    kind_span: Span,
+    pattern: LitStr,
+    actions: Vec<RuleAction>,
+}
+
+enum RuleAction {
+    Skip,
+    Trivia,
+    Push(Ident),
+    Pop,
+    Callback(Path),
+}
+
+/// A mode : `mode name { rules }`.
+struct ModeRules {
+    name: Ident,
+    /// The name, borrowed by the lexer builder.
+    label: String,
+    rules: Vec<TokenRule>,
+}
+
+pub(crate) struct LexerInput {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    error: Option<Type>,
+    /// The terminals of the grammar the lexer feeds.
+    terminals: Option<Vec<String>>,
+    rules: Vec<TokenRule>,
+    modes: Vec<ModeRules>,
+}
+
+/// Parse a symbol ident, as the terminals of `grammar!` are: `<ident-chain>`,
+/// `ident-chain` or a literal.
+fn parse_kind(input: ParseStream) -> syn::Result<(String, Span)> {
+    let span = input.span();
+
+    if input.peek(syn::Lit) {
+        let lit: proc_macro2::Literal = input.parse()?;
+        return Ok((lit.to_string(), span));
+    }
+
+    let bracketed = input.peek(T![<]);
+
+    if bracketed {
+        input.parse::<T![<]>()?;
+    }
+
+    let mut chain = input.call(Ident::parse_any)?.to_string();
+
+    while input.peek(T![-]) {
+        input.parse::<T![-]>()?;
+        chain.push('-');
+        chain.push_str(&input.call(Ident::parse_any)?.to_string());
+    }
+
+    if bracketed {
+        input.parse::<T![>]>()?;
+        chain = format!("<{chain}>");
+    }
+
+    Ok((chain, span))
+}
+
+impl Parse for RuleAction {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let action: Ident = input.parse()?;
+
+        match action.to_string().as_str() {
+            "skip" => Ok(Self::Skip),
+            "trivia" => Ok(Self::Trivia),
+            "pop" => Ok(Self::Pop),
+            "push" => {
+                let content;
+                parenthesized!(content in input);
+                Ok(Self::Push(content.parse()?))
+            }
+            "callback" => {
+                let content;
+                parenthesized!(content in input);
+                Ok(Self::Callback(content.parse()?))
+            }
+            _ => Err(syn::Error::new(
+                action.span(),
+                "expected skip, trivia, push(mode), pop or callback(path)",
+            )),
+        }
+    }
+}
+
+impl Parse for TokenRule {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let variant: Ident = input.parse()?;
+
+        let (kind, kind_span) = if input.peek(T![:]) {
+            input.parse::<T![:]>()?;
+            parse_kind(input)?
+        } else {
+            (variant.to_string(), variant.span())
+        };
+
+        input.parse::<T![=]>()?;
+        let pattern: LitStr = input.parse()?;
+
+        let mut actions = vec![];
+
+        if input.peek(T![=>]) {
+            input.parse::<T![=>]>()?;
+
+            loop {
+                actions.push(input.parse()?);
+
+                if !input.peek(T![,]) {
+                    break;
+                }
+
+                input.parse::<T![,]>()?;
+            }
+        }
+
+        input.parse::<T![;]>()?;
+
+        Ok(Self {
+            variant,
+            kind,
+            kind_span,
+            pattern,
+            actions,
+        })
+    }
+}
+
+/// Parse rules until the end of the stream, or a mode.
+fn parse_rules(input: ParseStream) -> syn::Result<Vec<TokenRule>> {
+    let mut rules = vec![];
+
+    while !input.is_empty() && !is_mode(input) {
+        rules.push(input.parse()?);
+    }
+
+    Ok(rules)
+}
+
+fn is_mode(input: ParseStream) -> bool {
+    input.peek(Ident)
+        && input.peek2(Ident)
+        && input
+            .fork()
+            .parse::<Ident>()
+            .is_ok_and(|ident| ident == "mode")
+}
+
+impl Parse for LexerInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attrs = vec![];
+        let mut error = None;
+        let mut terminals = None;
+
+        for attr in input.call(Attribute::parse_outer)? {
+            if attr.path().is_ident("error") {
+                error = Some(attr.parse_args()?);
+            } else if attr.path().is_ident("terminals") {
+                let stream: TokenStream = attr.parse_args()?;
+                let set = parse_symbol_ident_set(stream).map_err(|err| {
+                    syn::Error::new(attr.span(), format!("invalid terminals: {}", err.kind()))
+                })?;
+                terminals = Some(set.0);
+            } else {
+                attrs.push(attr);
+            }
+        }
+
+        let vis = input.parse()?;
+        input.parse::<T![struct]>()?;
+        let name = input.parse()?;
+        input.parse::<T![;]>()?;
+
+        let rules = parse_rules(input)?;
+        let mut modes = vec![];
+
+        while !input.is_empty() {
+            input.parse::<Ident>()?;
+            let name: Ident = input.parse()?;
+
+            let content;
+            braced!(content in input);
+
+            modes.push(ModeRules {
+                label: name.to_string(),
+                name,
+                rules: content.call(parse_rules)?,
+            });
+        }
+
+        Ok(Self {
+            attrs,
+            vis,
+            name,
+            error,
+            terminals,
+            rules,
+            modes,
+        })
+    }
+}
+
+impl LexerInput {
+    /// The rules, with the mode they are declared in.
+    fn iter_rules(&self) -> impl Iterator<Item = (Option<&ModeRules>, &TokenRule)> {
+        self.rules.iter().map(|rule| (None, rule)).chain(
+            self.modes
+                .iter()
+                .flat_map(|mode| mode.rules.iter().map(move |rule| (Some(mode), rule))),
+        )
+    }
+
+    /// The name of the mode, as borrowed by the lexer builder.
+    fn label(&self, mode: &Ident) -> &str {
+        self.modes
+            .iter()
+            .find(|rules| rules.name == *mode)
+            .map(|rules| rules.label.as_str())
+            .expect("the modes are checked")
+    }
+
+    /// Check the rules against each other, and against the terminals.
+    fn check(&self) -> syn::Result<()> {
+        let mut variants = HashSet::new();
+
+        for (_, rule) in self.iter_rules() {
+            if !variants.insert(rule.variant.to_string()) {
+                return Err(syn::Error::new(
+                    rule.variant.span(),
+                    format!("the token {} is declared twice", rule.variant),
+                ));
+            }
+
+            for action in &rule.actions {
+                if let RuleAction::Push(mode) = action {
+                    if !self.modes.iter().any(|rules| rules.name == *mode) {
+                        return Err(syn::Error::new(
+                            mode.span(),
+                            format!("unknown lexer mode {mode}"),
+                        ));
+                    }
+                }
+            }
+
+            let emitted = !rule
+                .actions
+                .iter()
+                .any(|action| matches!(action, RuleAction::Skip | RuleAction::Trivia));
+
+            if let Some(terminals) = &self.terminals {
+                if emitted && !terminals.contains(&rule.kind) {
+                    return Err(syn::Error::new(
+                        rule.kind_span,
+                        format!(
+                            "{} is not a terminal of the grammar, expected one of {}",
+                            rule.kind,
+                            terminals.join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compile the patterns.
+    fn build(&self) -> syn::Result<LexerTable<'_>> {
+        let mut builder = LexerBuilder::new();
+
+        for (mode, rule) in self.iter_rules() {
+            if let Some(mode) = mode {
+                builder = builder.mode(&mode.label);
+            }
+
+            builder = builder
+                .regex::<NoCustomError>(&rule.kind, &rule.pattern.value())
+                .map_err(|err| syn::Error::new(rule.pattern.span(), err.kind().to_string()))?;
+
+            for action in &rule.actions {
+                builder = match action {
+                    RuleAction::Skip => builder.skip(),
+                    RuleAction::Trivia => builder.trivia(),
+                    RuleAction::Push(mode) => builder.push_mode(self.label(mode)),
+                    RuleAction::Pop => builder.pop_mode(),
+                    RuleAction::Callback(_) => builder,
+                };
+            }
+        }
+
+        builder
+            .build::<NoCustomError>()
+            .map_err(|err| syn::Error::new(self.name.span(), err.kind().to_string()))
+    }
+
+    pub fn to_token_stream(&self) -> syn::Result<TokenStream> {
+        self.check()?;
+
+        let table = self.build()?;
+
+        let Self {
+            attrs, vis, name, ..
+        } = self;
+
+        let kind_enum = format_ident!("{}Kind", name);
+        let error = self
+            .error
+            .as_ref()
+            .map(|error| quote! { #error })
+            .unwrap_or_else(|| quote! { yalp::NoCustomError });
+
+        let rules = self.iter_rules().map(|(_, rule)| rule).collect::<Vec<_>>();
+        let variants = rules.iter().map(|rule| &rule.variant).collect::<Vec<_>>();
+        let kinds = rules
+            .iter()
+            .map(|rule| rule.kind.as_str())
+            .collect::<Vec<_>>();
+
+        // A kind can be shared by tokens of different modes.
+        let mut seen = HashSet::new();
+        let from_kind = rules
+            .iter()
+            .filter(|rule| seen.insert(rule.kind.as_str()))
+            .map(|rule| {
+                let (kind, variant) = (&rule.kind, &rule.variant);
+                quote! { #kind => Some(Self::#variant), }
+            });
+
+        let tokens = table
+            .iter_tokens()
+            .zip(&rules)
+            .map(|((kind, change, role), rule)| {
+                let change = match change {
+                    Some(ModeChange::Push(mode)) => {
+                        quote! { Some(yalp::lexer::ModeChange::Push(#mode)) }
+                    }
+                    Some(ModeChange::Pop) => quote! { Some(yalp::lexer::ModeChange::Pop) },
+                    None => quote! { None },
+                };
+
+                let role = match role {
+                    TokenRole::Token => quote! { yalp::lexer::TokenRole::Token },
+                    TokenRole::Skip => quote! { yalp::lexer::TokenRole::Skip },
+                    TokenRole::Trivia => quote! { yalp::lexer::TokenRole::Trivia },
+                };
+
+                let callback = rule
+                    .actions
+                    .iter()
+                    .rev()
+                    .find_map(|action| match action {
+                        RuleAction::Callback(path) => Some(path),
+                        _ => None,
+                    })
+                    .map(|path| {
+                        quote! { Some(yalp::lexer::TokenCallback::new::<#error, str>(#path)) }
+                    })
+                    .unwrap_or_else(|| quote! { None });
+
+                quote! { (#kind, #change, #role, #callback) }
+            });
+
+        let automata = table.iter_automata().map(|(mode, dfa)| {
+            let starts = dfa.classes().representatives();
+
+            let transitions = dfa.transitions().iter().map(|next| match next {
+                Some(next) => quote! { Some(#next) },
+                None => quote! { None },
+            });
+
+            let accepts = (0..dfa.state_count()).map(|state| {
+                let tags = dfa.accepts(state);
+                quote! { vec![#(#tags),*] }
+            });
+
+            quote! {
+                (
+                    #mode,
+                    yalp::dfa::CompactDfa::from_raw_parts(
+                        yalp::dfa::CharClasses::from_starts(vec![#(#starts),*]),
+                        vec![#(#transitions),*],
+                        vec![#(#accepts),*],
+                    ),
+                )
+            }
+        });
+
+        Ok(quote! {
+            /// The kinds of the tokens of the lexer.
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #vis enum #kind_enum {
+                #(#variants),*
+            }
+
+            impl #kind_enum {
+                /// The kinds, by declaration order.
+                pub const ALL: &'static [Self] = &[#(Self::#variants),*];
+
+                pub const fn as_str(self) -> &'static str {
+                    match self {
+                        #(Self::#variants => #kinds),*
+                    }
+                }
+
+                /// The first variant of the kind, the variants sharing a
+                /// kind cannot be told apart.
+                pub fn from_kind(kind: &str) -> Option<Self> {
+                    match kind {
+                        #(#from_kind)*
+                        _ => None,
+                    }
+                }
+            }
+
+            #(#attrs)*
+            #vis struct #name<'stream> {
+                inner: yalp::lexer::DfaLexer<'static, 'static, 'stream, #error>,
+            }
+
+            impl<'stream> #name<'stream> {
+                /// The table of the lexer, compiled by the macro.
+                pub fn table() -> &'static yalp::lexer::LexerTable<'static> {
+                    static TABLE: std::sync::OnceLock<yalp::lexer::LexerTable<'static>> =
+                        std::sync::OnceLock::new();
+
+                    TABLE.get_or_init(|| {
+                        yalp::lexer::LexerTable::from_compiled(
+                            [#(#tokens),*],
+                            vec![#(#automata),*],
+                        )
+                    })
+                }
+
+                pub fn new(source: &'stream str) -> Self {
+                    Self {
+                        inner: Self::table().lex(source),
+                    }
+                }
+
+                /// Only match the tokens expected by the parser.
+                pub fn contextual(self) -> Self {
+                    Self {
+                        inner: self.inner.contextual(),
+                    }
+                }
+
+                pub fn into_inner(self) -> yalp::lexer::DfaLexer<'static, 'static, 'stream, #error> {
+                    self.inner
+                }
+            }
+
+            impl<'stream> Iterator for #name<'stream> {
+                type Item = yalp::YalpResult<yalp::token::Token<'stream>, #error>;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    self.inner.next()
+                }
+            }
+
+            impl<'stream> yalp::traits::Lexer<#error> for #name<'stream> {
+                type Token = yalp::token::Token<'stream>;
+
+                fn span(&self) -> yalp::Span {
+                    yalp::traits::Lexer::span(&self.inner)
+                }
+
+                fn is_contextual(&self) -> bool {
+                    yalp::traits::Lexer::is_contextual(&self.inner)
+                }
+
+                fn expect(&mut self, terminals: &[&str]) {
+                    yalp::traits::Lexer::expect(&mut self.inner, terminals)
+                }
+            }
+        })
+    }
+}
+
+pub fn parse_lexer(stream: TokenStream) -> syn::Result<LexerInput> {
+    syn::parse2(stream)
+}
@@ -1,35 +1,48 @@
 extern crate proc_macro;
 use proc_macro::TokenStream;
-use quote::quote;
 
 pub(crate) mod grammar;
 pub(crate) mod rule;
 pub(crate) mod symbol;
 
 pub(crate) mod lexer;
+pub(crate) mod lexer_input;
 
 pub(crate) use grammar::parse_grammar;
 pub(crate) use lexer::{Lexer, Token};
+pub(crate) use lexer_input::parse_lexer;
 pub(crate) use symbol::{parse_symbol_ident_set, SymbolIdentSet};
-pub(crate) use rule::{parse_rule_set, RuleSet, Rule};
+pub(crate) use rule::{parse_rule_set, RuleSet};
 
-pub(crate) type Error = ();
+pub(crate) type Error = yalp_core::NoCustomError;
+
+/// Build a rule of the grammars used to parse the macros' inputs.
+pub(crate) fn rule(lhs: &'static str, rhs: &[&'static str]) -> yalp_core::syntax::Rule<'static> {
+    yalp_core::syntax::Rule {
+        lhs: lhs.into(),
+        rhs: rhs.iter().copied().map(Into::into).collect(),
+    }
+}
 
 /// Declares a new grammar
 ///
+/// The end of stream is implied by the root rule.
+///
 /// # Example
 /// ```
-/// grammar! {
+/// use yalp::{grammar, syntax::Syntax};
+///
+/// static GRAMMAR: Syntax<'static> = grammar! {
 ///     terminals: [<term>, "+", 0, 1],
-///     non_terminals: [],
+///     non_terminals: [E, B],
 ///     rules: {
-///         <start> => E <eos>;
+///         <start> => E;
 ///         E => E "+" B;
 ///         E => B;
 ///         B => 0;
 ///         B => 1;
 ///     }
-/// }
+/// };
 /// ```
 #[proc_macro]
 pub fn grammar(stream: TokenStream) -> TokenStream {
@@ -37,7 +50,56 @@ pub fn grammar(stream: TokenStream) -> TokenStream {
 }
 
 pub(crate) fn process_grammar_macro(stream: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
-    parse_grammar(stream).unwrap().into_token_stream()
+    parse_grammar(stream)
+        .map(|grammar| grammar.to_token_stream())
+        .unwrap_or_else(|err| syn::Error::new(proc_macro2::Span::call_site(), err).into_compile_error())
+}
+
+/// Declares a new lexer, whose automata are compiled with the crate.
+///
+/// Each token is a variant of the generated `<Name>Kind` enum, its kind
+/// defaults to the variant's name. The kinds are checked against the
+/// `terminals` of the grammar, if they are given.
+///
+/// # Example
+/// ```
+/// use std::num::ParseIntError;
+///
+/// use yalp::{lexer, token::Payload, ErrorKind, YalpError, YalpResult};
+///
+/// fn parse_int(value: &str) -> YalpResult<Payload, ParseIntError> {
+///     value
+///         .parse::<i64>()
+///         .map(Payload::new)
+///         .map_err(|err| YalpError::new(ErrorKind::Other(err), None))
+/// }
+///
+/// lexer! {
+///     #[terminals(number, "+", <ident>, "\"", chars)]
+///     #[error(ParseIntError)]
+///     pub struct Lexer;
+///
+///     Number: number = "[0-9]+" => callback(parse_int);
+///     Plus: "+" = "\\+";
+///     Ident: <ident> = "[a-z]+";
+///     Ws = "\\s+" => skip;
+///     Open: "\"" = "\"" => push(string);
+///
+///     mode string {
+///         Chars: chars = "[^\"]+";
+///         Close: "\"" = "\"" => pop;
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn lexer(stream: TokenStream) -> TokenStream {
+    process_lexer_macro(stream.into()).into()
+}
+
+pub(crate) fn process_lexer_macro(stream: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    parse_lexer(stream)
+        .and_then(|lexer| lexer.to_token_stream())
+        .unwrap_or_else(syn::Error::into_compile_error)
 }
 
 #[cfg(test)]
@@ -46,20 +108,73 @@ mod tests {
 
     use proc_macro2::TokenStream;
 
-    use super::{parse_grammar, process_grammar_macro};
+    use super::{process_grammar_macro, process_lexer_macro};
 
     #[test]
     pub fn test_grammar_macro() {
         let stream = TokenStream::from_str("
-            terminals: [E, B, 0, <long-terminal>],
-            non_terminals: [],
+            terminals: [B, 0, <long-terminal>],
+            non_terminals: [E],
+            rules: {
+                <start> => E;
+                E => B <long-terminal>;
+            }
+        ").expect("cannot parse macro");
+
+        let grammar = process_grammar_macro(stream).to_string();
+
+        assert!(grammar.contains("Syntax :: new (RULES)"));
+        assert!(grammar.contains("\"<long-terminal>\""));
+        assert!(!grammar.contains("compile_error"));
+    }
+
+    #[test]
+    pub fn test_grammar_macro_checks_symbols() {
+        let stream = TokenStream::from_str("
+            terminals: [B],
+            non_terminals: [E, F],
             rules: {
-                <start> => E <eos>;
+                <start> => E;
+                E => B;
             }
         ").expect("cannot parse macro");
 
-        let ast = process_grammar_macro(stream);
+        let grammar = process_grammar_macro(stream).to_string();
 
-        println!("{ast}");
+        assert!(grammar.contains("compile_error"));
+        assert!(grammar.contains("F"));
+    }
+
+    #[test]
+    pub fn test_lexer_macro() {
+        let stream = TokenStream::from_str(r#"
+            #[terminals(number, "+")]
+            pub struct Lexer;
+
+            Number: number = "[0-9]+";
+            Plus: "+" = "\\+";
+            Ws = "\\s+" => skip;
+        "#).expect("cannot parse macro");
+
+        let lexer = process_lexer_macro(stream).to_string();
+
+        assert!(lexer.contains("enum LexerKind"));
+        assert!(!lexer.contains("compile_error"));
+    }
+
+    #[test]
+    pub fn test_lexer_macro_checks_terminals() {
+        let stream = TokenStream::from_str(r#"
+            #[terminals(number, "+")]
+            struct Lexer;
+
+            Number: nubmer = "[0-9]+";
+        "#).expect("cannot parse macro");
+
+        let lexer = process_lexer_macro(stream).to_string();
+
+        assert!(lexer.contains("compile_error"));
+        assert!(lexer.contains("nubmer is not a terminal of the grammar"));
     }
 }
+
@@ -211,11 +211,7 @@ impl syn::parse::Parse for SymbolFragment {
             Ok(Self("extern".to_owned()))
         } else if input.peek(LitBool) {
             let lit = input.parse::<LitBool>()?;
-            let val = lit
-                .value()
-                .then(|| String::from("true"))
-                .unwrap_or(String::from("false"));
-            Ok(Self(val))
+            Ok(Self(lit.value().to_string()))
         } else if input.peek(LitInt) {
             let lit = input.parse::<LitInt>()?;
             let val = lit.to_string();
@@ -1,14 +1,22 @@
-use yalp::{ConstGrammar, grammar};
+use yalp::{grammar, lr::LrTable, syntax::Syntax, NoCustomError};
 
-const GRAMMAR: ConstGrammar<'static, 9, 6> = grammar! {
+static GRAMMAR: Syntax<'static> = grammar! {
     terminals: [0, 1, "+", "*"],
     non_terminals: [E, B],
     rules: {
-        <start> => E <eos>;
+        <start> => E;
         E => E "+" B;
         E => E "*" B;
         E => B;
         B => 0;
         B => 1;
     }
-};
\ No newline at end of file
+};
+
+#[test]
+fn test_grammar_macro() {
+    assert_eq!(GRAMMAR.as_ref().len(), 6);
+    assert!(GRAMMAR.as_ref()[1].lhs.is("E"));
+
+    LrTable::build::<1, NoCustomError>(&GRAMMAR).expect("cannot build table");
+}
@@ -0,0 +1,70 @@
+use std::num::ParseIntError;
+
+use yalp::{lexer, token::Payload, ErrorKind, YalpError, YalpResult};
+
+fn parse_int(value: &str) -> YalpResult<Payload, ParseIntError> {
+    value
+        .parse::<i64>()
+        .map(Payload::new)
+        .map_err(|err| YalpError::new(ErrorKind::Other(err), None))
+}
+
+lexer! {
+    #[terminals(number, "+", <ident>, "\"", chars)]
+    #[error(ParseIntError)]
+    pub struct Lexer;
+
+    Number: number = "[0-9]+" => callback(parse_int);
+    Plus: "+" = "\\+";
+    Ident: <ident> = "[a-z]+";
+    Ws = "\\s+" => skip;
+    Open: "\"" = "\"" => push(string);
+
+    mode string {
+        Chars: chars = "[^\"]+";
+        Close: "\"" = "\"" => pop;
+    }
+}
+
+#[test]
+fn test_lexer_macro() {
+    let tokens = Lexer::new("12 + x \"a b\"")
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    let kinds = tokens
+        .iter()
+        .map(|tok| LexerKind::from_kind(&tok.kind))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        kinds,
+        [
+            Some(LexerKind::Number),
+            Some(LexerKind::Plus),
+            Some(LexerKind::Ident),
+            Some(LexerKind::Open),
+            Some(LexerKind::Chars),
+            // The closing quote shares its kind with the opening one.
+            Some(LexerKind::Open),
+        ]
+    );
+
+    assert_eq!(tokens[0].payload::<i64>(), Some(&12));
+    assert_eq!(tokens[4].value, "a b");
+}
+
+#[test]
+fn test_lexer_macro_errors() {
+    let err = Lexer::new("12 $").nth(1).unwrap().unwrap_err();
+
+    assert_eq!(err.span().unwrap().from.column, 3);
+}
+
+#[test]
+fn test_lexer_macro_callback_errors() {
+    let err = Lexer::new("1 99999999999999999999").nth(1).unwrap().unwrap_err();
+
+    assert!(matches!(err.kind(), ErrorKind::Other(_)));
+    assert_eq!(err.span().unwrap().from.column, 2);
+}